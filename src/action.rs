@@ -0,0 +1,519 @@
+use crate::theme::ThemeName;
+use fitch::{custom::CustomRule, export::Format, normal::Form, Rule};
+use std::{cmp::Reverse, fmt::Display};
+
+/// Everything the user can do from the normal mode, either through a key or the command palette.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Action {
+    Introduce,
+    Eliminate,
+    AddAssumption,
+    AddSubproof,
+    EndSubproof,
+    AddTentative,
+    Justify,
+    DeleteLastRow,
+    Undo,
+    Redo,
+    Copy,
+    Paste,
+    CopyProof,
+    NewTab,
+    CloseTab,
+    DuplicateTab,
+    NextTab,
+    PreviousTab,
+    /// Opening a proof file in a new tab.
+    Open,
+    /// Saving the proof to the file of its tab, asking for one the first time.
+    Save,
+    Help,
+    CommandPalette,
+    Quit,
+    FocusUp,
+    FocusDown,
+    Apply(Rule),
+    SetTheme(ThemeName),
+    Export(Format),
+    ImportCarnap,
+    SaveLemma,
+    ApplyLemma,
+    CheckSoundness,
+    /// Converting to a normal form, or to the Tseitin CNF for `None`.
+    Normalize(Option<Form>),
+}
+
+/// A key press that can be bound to an action, written `x`, `C-x` (Ctrl) or `M-x` (Alt).
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Key {
+    pub code: char,
+    pub ctrl: bool,
+    pub alt: bool,
+}
+
+impl Key {
+    pub const fn plain(code: char) -> Key {
+        Key {
+            code,
+            ctrl: false,
+            alt: false,
+        }
+    }
+
+    pub const fn ctrl(code: char) -> Key {
+        Key {
+            code,
+            ctrl: true,
+            alt: false,
+        }
+    }
+
+    pub const fn alt(code: char) -> Key {
+        Key {
+            code,
+            ctrl: false,
+            alt: true,
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<Key> {
+        let (key, rest): (fn(char) -> Key, &str) = if let Some(rest) = text.strip_prefix("C-") {
+            (Key::ctrl, rest)
+        } else if let Some(rest) = text.strip_prefix("M-") {
+            (Key::alt, rest)
+        } else {
+            (Key::plain, text)
+        };
+        let mut chars = rest.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(key(c)),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.ctrl {
+            write!(f, "C-")?;
+        }
+        if self.alt {
+            write!(f, "M-")?;
+        }
+        write!(f, "{}", self.code)
+    }
+}
+
+type Menu = Vec<(Key, Rule)>;
+
+/// The keys of the normal mode and of the introduce and eliminate menus.
+pub struct Keymap {
+    pub normal: Vec<(Key, Action)>,
+    pub introduce: Menu,
+    pub eliminate: Menu,
+}
+
+pub const INTRODUCE_RULES: [Rule; 10] = [
+    Rule::IntroAbs,
+    Rule::IntroAnd,
+    Rule::IntroOr,
+    Rule::IntroNot,
+    Rule::IntroImpl,
+    Rule::IntroIff,
+    Rule::IntroXor,
+    Rule::IntroNand,
+    Rule::IntroNor,
+    Rule::IntroVerum,
+];
+
+pub const ELIMINATE_RULES: [Rule; 9] = [
+    Rule::ElimAbs,
+    Rule::ElimAnd,
+    Rule::ElimOr,
+    Rule::ElimNot,
+    Rule::ElimImpl,
+    Rule::ElimIff,
+    Rule::ElimXor,
+    Rule::ElimNand,
+    Rule::ElimNor,
+];
+
+impl Keymap {
+    /// The keys go with the connectives in the order of [`INTRODUCE_RULES`]; ⊤ has no
+    /// elimination, so the eliminate menu leaves its key out.
+    fn menus(keys: [Key; 10]) -> (Menu, Menu) {
+        (
+            keys.into_iter().zip(INTRODUCE_RULES).collect(),
+            keys.into_iter().zip(ELIMINATE_RULES).collect(),
+        )
+    }
+
+    pub fn default_preset() -> Keymap {
+        let (introduce, eliminate) =
+            Keymap::menus(['a', 'n', 'o', 't', 'm', 'f', 'x', 'd', 'r', 'v'].map(Key::plain));
+        Keymap {
+            normal: vec![
+                (Key::plain('i'), Action::Introduce),
+                (Key::plain('e'), Action::Eliminate),
+                (Key::plain('a'), Action::AddAssumption),
+                (Key::plain('s'), Action::AddSubproof),
+                (Key::plain('n'), Action::EndSubproof),
+                (Key::plain('r'), Action::Apply(Rule::Reiter)),
+                (Key::plain('t'), Action::AddTentative),
+                (Key::plain('j'), Action::Justify),
+                (Key::plain('d'), Action::DeleteLastRow),
+                (Key::plain('u'), Action::Undo),
+                (Key::plain('U'), Action::Redo),
+                (Key::plain('y'), Action::Copy),
+                (Key::plain('p'), Action::Paste),
+                (Key::plain('c'), Action::CopyProof),
+                (Key::plain('T'), Action::NewTab),
+                (Key::plain('W'), Action::CloseTab),
+                (Key::plain('D'), Action::DuplicateTab),
+                (Key::plain(']'), Action::NextTab),
+                (Key::plain('['), Action::PreviousTab),
+                (Key::plain('o'), Action::Open),
+                (Key::plain('w'), Action::Save),
+                (Key::plain('?'), Action::Help),
+                (Key::plain(':'), Action::CommandPalette),
+                (Key::plain('q'), Action::Quit),
+            ],
+            introduce,
+            eliminate,
+        }
+    }
+
+    /// Rows are moved with `j`/`k` and the menus use the connectives themselves.
+    pub fn vim() -> Keymap {
+        let (introduce, eliminate) =
+            Keymap::menus(['#', '&', '|', '~', '>', '=', '^', 'A', 'O', '%'].map(Key::plain));
+        Keymap {
+            normal: vec![
+                (Key::plain('i'), Action::Introduce),
+                (Key::plain('e'), Action::Eliminate),
+                (Key::plain('a'), Action::AddAssumption),
+                (Key::plain('o'), Action::AddSubproof),
+                (Key::plain('O'), Action::EndSubproof),
+                (Key::plain('r'), Action::Apply(Rule::Reiter)),
+                (Key::plain('t'), Action::AddTentative),
+                (Key::plain('J'), Action::Justify),
+                (Key::plain('x'), Action::DeleteLastRow),
+                (Key::plain('u'), Action::Undo),
+                (Key::ctrl('r'), Action::Redo),
+                (Key::plain('y'), Action::Copy),
+                (Key::plain('p'), Action::Paste),
+                (Key::plain('Y'), Action::CopyProof),
+                (Key::plain('T'), Action::NewTab),
+                (Key::ctrl('w'), Action::CloseTab),
+                (Key::plain('D'), Action::DuplicateTab),
+                (Key::plain(']'), Action::NextTab),
+                (Key::plain('['), Action::PreviousTab),
+                (Key::ctrl('o'), Action::Open),
+                (Key::ctrl('s'), Action::Save),
+                (Key::plain('k'), Action::FocusUp),
+                (Key::plain('j'), Action::FocusDown),
+                (Key::plain('?'), Action::Help),
+                (Key::plain(':'), Action::CommandPalette),
+                (Key::plain('q'), Action::Quit),
+            ],
+            introduce,
+            eliminate,
+        }
+    }
+
+    /// Commands live on Alt, rows are moved with `C-p`/`C-n` and the menus use the connectives.
+    pub fn emacs() -> Keymap {
+        let (introduce, eliminate) =
+            Keymap::menus(['#', '&', '|', '~', '>', '=', '^', 'A', 'O', '%'].map(Key::plain));
+        Keymap {
+            normal: vec![
+                (Key::alt('i'), Action::Introduce),
+                (Key::alt('e'), Action::Eliminate),
+                (Key::alt('a'), Action::AddAssumption),
+                (Key::alt('s'), Action::AddSubproof),
+                (Key::alt('n'), Action::EndSubproof),
+                (Key::alt('r'), Action::Apply(Rule::Reiter)),
+                (Key::alt('t'), Action::AddTentative),
+                (Key::alt('j'), Action::Justify),
+                (Key::alt('d'), Action::DeleteLastRow),
+                (Key::alt('u'), Action::Undo),
+                (Key::alt('U'), Action::Redo),
+                (Key::alt('w'), Action::Copy),
+                (Key::ctrl('y'), Action::Paste),
+                (Key::alt('W'), Action::CopyProof),
+                (Key::ctrl('t'), Action::NewTab),
+                (Key::alt('k'), Action::CloseTab),
+                (Key::alt('c'), Action::DuplicateTab),
+                (Key::alt(']'), Action::NextTab),
+                (Key::alt('['), Action::PreviousTab),
+                (Key::alt('o'), Action::Open),
+                (Key::ctrl('s'), Action::Save),
+                (Key::ctrl('p'), Action::FocusUp),
+                (Key::ctrl('n'), Action::FocusDown),
+                (Key::alt('?'), Action::Help),
+                (Key::alt('x'), Action::CommandPalette),
+                (Key::alt('q'), Action::Quit),
+            ],
+            introduce,
+            eliminate,
+        }
+    }
+
+    pub fn preset(name: &str) -> Option<Keymap> {
+        match name {
+            "default" => Some(Keymap::default_preset()),
+            "vim" => Some(Keymap::vim()),
+            "emacs" => Some(Keymap::emacs()),
+            _ => None,
+        }
+    }
+
+    /// Binds `key` to `action`, dropping whatever was bound to either before.
+    pub fn bind_normal(&mut self, key: Key, action: Action) {
+        self.normal.retain(|(k, a)| *k != key && *a != action);
+        self.normal.push((key, action));
+    }
+
+    /// Binds `key` to `rule` in the introduce or eliminate menu, depending on the rule.
+    pub fn bind_rule(&mut self, key: Key, rule: Rule) {
+        let menu = if rule.introduces() {
+            &mut self.introduce
+        } else {
+            &mut self.eliminate
+        };
+        menu.retain(|(k, r)| *k != key && *r != rule);
+        menu.push((key, rule));
+    }
+
+    /// Adds the registered custom rules to the menus, each on the first char of its name that
+    /// is still free in its menu, or on a free digit.
+    pub fn bind_custom_rules(&mut self) {
+        for (rule, custom) in CustomRule::all() {
+            let menu = if custom.introduces {
+                &self.introduce
+            } else {
+                &self.eliminate
+            };
+            let free = custom
+                .name
+                .chars()
+                .filter(|c| c.is_ascii_alphanumeric())
+                .map(|c| c.to_ascii_lowercase())
+                .chain('1'..='9')
+                .map(Key::plain)
+                .find(|key| menu.iter().all(|(k, _)| k != key));
+            if let Some(key) = free {
+                self.bind_rule(key, rule);
+            }
+        }
+    }
+}
+
+impl Action {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Introduce => "introduce",
+            Action::Eliminate => "eliminate",
+            Action::AddAssumption => "add assumption",
+            Action::AddSubproof => "add subproof",
+            Action::EndSubproof => "end subproof",
+            Action::AddTentative => "add tentative",
+            Action::Justify => "justify",
+            Action::DeleteLastRow => "delete last row",
+            Action::Undo => "undo",
+            Action::Redo => "redo",
+            Action::Copy => "yank row or subproof",
+            Action::Paste => "paste",
+            Action::CopyProof => "copy proof to clipboard",
+            Action::NewTab => "new tab",
+            Action::CloseTab => "close tab",
+            Action::DuplicateTab => "duplicate tab",
+            Action::NextTab => "next tab",
+            Action::PreviousTab => "previous tab",
+            Action::Open => "open proof",
+            Action::Save => "save proof",
+            Action::Help => "help",
+            Action::CommandPalette => "command palette",
+            Action::Quit => "quit",
+            Action::FocusUp => "focus previous row",
+            Action::FocusDown => "focus next row",
+            Action::Apply(rule) => rule.name(),
+            Action::SetTheme(ThemeName::Dark) => "dark theme",
+            Action::SetTheme(ThemeName::Light) => "light theme",
+            Action::SetTheme(ThemeName::HighContrast) => "high-contrast theme",
+            Action::SetTheme(ThemeName::Monochrome) => "monochrome theme",
+            Action::Export(Format::FitchSty) => "export to fitch.sty",
+            Action::Export(Format::Lplfitch) => "export to lplfitch",
+            Action::Export(Format::Logicproof) => "export to logicproof",
+            Action::Export(Format::Html) => "export to HTML",
+            Action::Export(Format::Svg) => "export to SVG",
+            Action::Export(Format::Text) => "export to plain text",
+            Action::Export(Format::Markdown) => "export to Markdown",
+            Action::ImportCarnap => "import from Carnap",
+            Action::SaveLemma => "save proof as lemma",
+            Action::ApplyLemma => "apply lemma",
+            Action::CheckSoundness => "check rule soundness",
+            Action::Normalize(Some(Form::ImplicationFree)) => "convert to implication-free form",
+            Action::Normalize(Some(Form::Negation)) => "convert to negation normal form",
+            Action::Normalize(Some(Form::Conjunctive)) => "convert to conjunctive normal form",
+            Action::Normalize(Some(Form::Disjunctive)) => "convert to disjunctive normal form",
+            Action::Normalize(None) => "convert to Tseitin CNF",
+        }
+    }
+
+    /// The actions offered by the command palette. The menus are left out since every rule is
+    /// listed on its own.
+    pub fn all() -> Vec<Action> {
+        let mut res = vec![
+            Action::AddAssumption,
+            Action::AddSubproof,
+            Action::EndSubproof,
+            Action::AddTentative,
+            Action::Justify,
+            Action::DeleteLastRow,
+            Action::Undo,
+            Action::Redo,
+            Action::Copy,
+            Action::Paste,
+            Action::CopyProof,
+            Action::NewTab,
+            Action::CloseTab,
+            Action::DuplicateTab,
+            Action::NextTab,
+            Action::PreviousTab,
+            Action::Open,
+            Action::Save,
+            Action::Help,
+            Action::Quit,
+        ];
+        res.extend(Rule::ALL.into_iter().map(Action::Apply));
+        res.extend(ThemeName::ALL.into_iter().map(Action::SetTheme));
+        res.extend(Format::ALL.into_iter().map(Action::Export));
+        res.extend([
+            Action::ImportCarnap,
+            Action::SaveLemma,
+            Action::ApplyLemma,
+            Action::CheckSoundness,
+        ]);
+        res.extend(
+            Form::ALL
+                .into_iter()
+                .map(|form| Action::Normalize(Some(form))),
+        );
+        res.push(Action::Normalize(None));
+        res
+    }
+
+    /// Finds an action by its [`Action::name`], menus and row movement included.
+    pub fn from_name(name: &str) -> Option<Action> {
+        [
+            Action::Introduce,
+            Action::Eliminate,
+            Action::CommandPalette,
+            Action::FocusUp,
+            Action::FocusDown,
+        ]
+        .into_iter()
+        .chain(Action::all())
+        .find(|a| a.name() == name)
+    }
+}
+
+/// Formats a key hint the way the info bar shows them, e.g. `add [s]ubproof`.
+pub fn key_label(key: Key, name: &str) -> String {
+    match name.find(key.code) {
+        Some(i) if !key.ctrl && !key.alt => format!(
+            "{}[{}]{}",
+            &name[..i],
+            key,
+            &name[i + key.code.len_utf8()..]
+        ),
+        _ => format!("[{}] {}", key, name),
+    }
+}
+
+/// Scores how well `query` fuzzy-matches `name`: every char of the query has to appear in order,
+/// with consecutive chars and word starts being worth more. `None` means no match.
+fn fuzzy_score(query: &str, name: &str) -> Option<i32> {
+    let name: Vec<char> = name.chars().collect();
+    let mut score = 0;
+    let mut last: Option<usize> = None;
+    let mut from = 0;
+    for q in query.chars().filter(|c| !c.is_whitespace()) {
+        let found = (from..name.len()).find(|i| name[*i].eq_ignore_ascii_case(&q))?;
+        score += 1;
+        if found == 0 || name[found - 1] == ' ' {
+            score += 3;
+        }
+        match last {
+            Some(l) if l + 1 == found => score += 2,
+            Some(l) => score -= (found - l - 1).min(3) as i32,
+            None => (),
+        }
+        last = Some(found);
+        from = found + 1;
+    }
+    Some(score)
+}
+
+/// The palette actions matching `query`, best match first.
+pub fn search(query: &str) -> Vec<Action> {
+    let mut res: Vec<(i32, Action)> = Action::all()
+        .into_iter()
+        .filter_map(|a| fuzzy_score(query, a.name()).map(|s| (s, a)))
+        .collect();
+    res.sort_by_key(|(score, _)| Reverse(*score));
+    res.into_iter().map(|(_, a)| a).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{key_label, search, Action, Key, Keymap};
+    use fitch::Rule;
+
+    #[test]
+    fn key_labels() {
+        assert_eq!(key_label(Key::plain('n'), "end subproof"), "e[n]d subproof");
+        assert_eq!(key_label(Key::plain('?'), "help"), "[?] help");
+        assert_eq!(key_label(Key::alt('q'), "quit"), "[M-q] quit");
+    }
+
+    #[test]
+    fn keys() {
+        assert_eq!(Key::parse("C-x"), Some(Key::ctrl('x')));
+        assert_eq!(Key::parse("M-?"), Some(Key::alt('?')));
+        assert_eq!(Key::parse("&"), Some(Key::plain('&')));
+        assert_eq!(Key::parse("ab"), None);
+        assert_eq!(Key::parse("C-"), None);
+        assert_eq!(Key::alt('x').to_string(), "M-x");
+    }
+
+    #[test]
+    fn default_menu_keys() {
+        let keys = Keymap::default_preset();
+        assert!(keys.introduce.contains(&(Key::plain('m'), Rule::IntroImpl)));
+        assert!(keys.eliminate.contains(&(Key::plain('m'), Rule::ElimImpl)));
+        assert!(!keys.introduce.iter().any(|(k, _)| *k == Key::plain('i')));
+    }
+
+    #[test]
+    fn rebinding() {
+        let mut keys = Keymap::default_preset();
+        keys.bind_normal(Key::plain('i'), Action::Quit);
+        assert!(keys.normal.contains(&(Key::plain('i'), Action::Quit)));
+        assert!(!keys.normal.iter().any(|(k, _)| *k == Key::plain('q')));
+        assert!(!keys.normal.iter().any(|(_, a)| *a == Action::Introduce));
+        keys.bind_rule(Key::plain('&'), Rule::ElimAnd);
+        assert!(keys.eliminate.contains(&(Key::plain('&'), Rule::ElimAnd)));
+        assert_eq!(keys.introduce.len(), 10);
+    }
+
+    #[test]
+    fn fuzzy_search() {
+        assert_eq!(search("intimp")[0], Action::Apply(Rule::IntroImpl));
+        assert_eq!(search("elim or")[0], Action::Apply(Rule::ElimOr));
+        assert_eq!(search("del")[0], Action::DeleteLastRow);
+        assert!(search("xyz").is_empty());
+        assert_eq!(search("").len(), Action::all().len());
+    }
+}
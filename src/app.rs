@@ -1,669 +1,1685 @@
 use crate::{
-    fitch::Fitch,
-    parser::{self, parse_expression},
-    state::{AbsurdumState, AndState, IffState, ImpliesState, NotState, OrState, State},
-    ui::Renderer,
+    action::{key_label, search, Action, Key, Keymap},
+    clipboard,
+    config::Config,
+    editor::LineEditor,
+    help::help_text,
+    state::{
+        AbsurdumState, AndState, IffState, ImpliesState, LemmaState, NotState, OrState, State,
+    },
+    ui::{ExpressionBox, Info, Popup, Renderer, TabBar},
+};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+#[cfg(feature = "json")]
+use fitch::json;
+use fitch::{
+    carnap,
+    custom::{self, CounterInstance, CustomRule},
+    export::{export, Format},
+    lemma::Lemma,
+    normal::{self, Form},
+    parser::{self, parse_citations, parse_expression, parse_justification},
+    Fitch, Fragment, Proposition, RowStatus, Rule, Styled, SymbolStyle,
+};
+use std::{
+    borrow::Borrow,
+    fs, mem,
+    path::{Path, PathBuf},
+    rc::Rc,
 };
-use crossterm::event::{self, Event, KeyCode, KeyEventKind};
 
-pub struct App {
+/// A proof open in its own tab.
+struct Tab {
+    title: String,
+    /// The file the proof was opened from or last saved to.
+    path: Option<PathBuf>,
     model: Fitch,
-    renderer: Renderer,
     state: State,
-    expression_buffer: String,
-    expression_cursor: u16,
+    focus: Option<usize>,
+    undo: Vec<Fitch>,
+    redo: Vec<Fitch>,
+    /// The model as it was last recorded in the undo history.
+    checkpoint: Fitch,
+}
+
+impl Tab {
+    fn new(title: String, model: Fitch) -> Tab {
+        Tab {
+            title,
+            path: None,
+            checkpoint: model.clone(),
+            model,
+            state: State::Noraml,
+            focus: None,
+            undo: Vec::new(),
+            redo: Vec::new(),
+        }
+    }
+
+    /// Records the model in the undo history if it changed since the last call.
+    fn checkpoint(&mut self) {
+        if self.model != self.checkpoint {
+            let previous = mem::replace(&mut self.checkpoint, self.model.clone());
+            self.undo.push(previous);
+            self.redo.clear();
+        }
+    }
+
+    fn undo(&mut self) -> bool {
+        match self.undo.pop() {
+            None => false,
+            Some(previous) => {
+                self.redo
+                    .push(mem::replace(&mut self.model, previous.clone()));
+                self.checkpoint = previous;
+                true
+            }
+        }
+    }
+
+    fn redo(&mut self) -> bool {
+        match self.redo.pop() {
+            None => false,
+            Some(next) => {
+                self.undo.push(mem::replace(&mut self.model, next.clone()));
+                self.checkpoint = next;
+                true
+            }
+        }
+    }
+
+    fn clamp_focus(&mut self) {
+        if self.focus >= Some(self.model.len()) {
+            self.focus = self.model.len().checked_sub(1);
+        }
+    }
+}
+
+pub struct App {
+    /// The tab on screen; the others are kept in `tabs`, with `current` being where the active
+    /// one sits among them.
+    tab: Tab,
+    tabs: Vec<Tab>,
+    current: usize,
+    opened: usize,
+    clipboard: Option<Fragment>,
+    renderer: Renderer,
+    symbols: SymbolStyle,
+    bars: SymbolStyle,
+    keys: Keymap,
+    expression: LineEditor,
     info_buffer: String,
     warning: bool,
+    completion: Option<(Vec<Rc<Proposition>>, usize)>,
 }
 
 impl App {
-    pub fn new() -> std::io::Result<App> {
+    pub fn new(config: Config) -> std::io::Result<App> {
         let mut app = App {
-            model: Fitch::new(),
-            renderer: Renderer::new()?,
-            state: State::Noraml,
-            expression_buffer: String::new(),
-            expression_cursor: 0,
+            tab: Tab::new("proof 1".to_string(), Fitch::new()),
+            tabs: Vec::new(),
+            current: 0,
+            opened: 1,
+            clipboard: None,
+            renderer: Renderer::new(config.symbols, config.bars, config.theme.theme())?,
+            symbols: config.symbols,
+            bars: config.bars,
+            keys: config.keys,
+            expression: LineEditor::new(),
             info_buffer: String::new(),
             warning: false,
+            completion: None,
         };
         app.render();
         Ok(app)
     }
 
-    fn render(&mut self) {
-        let (title, render_box) = match self.state {
-            State::AddAssumption => ("Assumption expression", true),
-            State::AddSubproof => ("Subproof expression", true),
+    fn switch_tab(&mut self, index: usize) {
+        let active = mem::replace(&mut self.tab, Tab::new(String::new(), Fitch::new()));
+        self.tabs.insert(self.current, active);
+        self.tab = self.tabs.remove(index);
+        self.current = index;
+    }
+
+    /// Opens `model` in a new tab right after the active one.
+    fn open_tab(&mut self, title: String, model: Fitch) {
+        let active = mem::replace(&mut self.tab, Tab::new(title, model));
+        self.tabs.insert(self.current, active);
+        self.current += 1;
+    }
+
+    fn close_tab(&mut self) {
+        if self.tabs.is_empty() {
+            self.info_buffer.push_str("Cannot close the only tab");
+            self.warning = true;
+            return;
+        }
+        if self.current == self.tabs.len() {
+            self.current -= 1;
+        }
+        self.tab = self.tabs.remove(self.current);
+    }
+
+    fn copy(&mut self) {
+        match self.tab.focus.and_then(|row| self.tab.model.copy(row)) {
+            None => {
+                self.info_buffer
+                    .push_str("Focus a row with Up/Down before yanking it");
+                self.warning = true;
+            }
+            Some(fragment) => self.clipboard = Some(fragment),
+        }
+    }
+
+    fn paste(&mut self) {
+        let fragment = match &self.clipboard {
+            None => {
+                self.info_buffer.push_str("Nothing has been yanked yet");
+                self.warning = true;
+                return;
+            }
+            Some(v) => v,
+        };
+        match self.tab.model.paste(fragment) {
+            None => {
+                self.info_buffer
+                    .push_str("Delete all deductions before pasting a premise");
+                self.warning = true;
+            }
+            Some(rows) => {
+                let count = rows.len();
+                let invalid = rows
+                    .filter(|row| self.tab.model.row_status(*row) != RowStatus::Valid)
+                    .count();
+                if invalid > 0 {
+                    self.info_buffer.push_str(
+                        format!("{} of the {} pasted rows do not hold here", invalid, count)
+                            .as_str(),
+                    );
+                    self.warning = true;
+                }
+            }
+        }
+    }
+
+    fn expression_box_title(&self) -> Option<&'static str> {
+        match self.tab.state {
+            State::AddAssumption => Some("Assumption expression"),
+            State::AddSubproof => Some("Subproof expression"),
             State::AbsurdumState(AbsurdumState::EliminateGetProposition(_))
             | State::OrState(OrState::IntroduceGetProposition(_)) => {
-                ("Expression to introduce", true)
+                Some("Expression to introduce")
+            }
+            State::AndState(AndState::IntroduceGetAssumptions) => {
+                Some("Rows to conjoin, e.g. 0, 1, 2")
             }
+            State::AndState(AndState::EliminateChooseConjunct(_)) => Some("Conjunct to extract"),
             State::AbsurdumState(_)
-            | State::AndState(AndState::IntroduceGetLeftAssumption)
-            | State::AndState(AndState::IntroduceGetRightAssumption(_))
             | State::OrState(OrState::IntroduceGetAssumption)
-            | State::NotState(_) => ("Assumption index", true),
+            | State::NotState(_) => Some("Assumption index"),
             State::AndState(AndState::EliminateGetAssumption)
             | State::OrState(OrState::EliminateGetAssumption) => {
-                ("And expression to eliminate", true)
+                Some("And expression to eliminate")
             }
-            State::AndState(AndState::EliminateGetProposition(_)) => ("Resulting expression", true),
-            State::OrState(OrState::EliminateGetLeftSubproof(_))
-            | State::OrState(OrState::EliminateGetRightSubproof(_, _))
-            | State::ImpliesState(ImpliesState::Introduce)
+            State::OrState(OrState::EliminateGetSubproofs(_)) => {
+                Some("Subproofs to use, one per disjunct")
+            }
+            State::ImpliesState(ImpliesState::Introduce)
             | State::IffState(IffState::IntroduceGetLeftSubproof)
-            | State::IffState(IffState::IntroduceGetRightSubproof(_)) => ("Subproof to use", true),
-            State::Reiterate => ("Select proposition to reiterate", true),
-            State::ImpliesState(ImpliesState::EliminateGetAssumption)
-            | State::IffState(IffState::EliminateGetAssumption) => {
-                ("Implication to eliminate", true)
+            | State::IffState(IffState::IntroduceGetRightSubproof(_)) => Some("Subproof to use"),
+            State::Reiterate => Some("Select proposition to reiterate"),
+            State::Definition(rule) if rule.name().starts_with("introduce") => {
+                Some("Definition to fold")
+            }
+            State::Definition(_) => Some("Expression to unfold"),
+            State::CommandPalette(_) => Some("Command"),
+            State::Export(_) => Some("File to export to"),
+            State::ImportCarnap => Some("Carnap proof to import"),
+            State::Open => Some("Proof file to open"),
+            State::Save => Some("File to save the proof to"),
+            State::SaveLemma => Some("Name of the lemma"),
+            State::CheckSoundness => {
+                Some("Rule to check, e.g. (φ → ψ), ψ ⊢ φ (empty for the custom rules)")
+            }
+            State::Normalize(_) => Some("Expression to convert (empty for the focused row)"),
+            State::LemmaState(LemmaState::Choose) => Some("Lemma to apply"),
+            State::LemmaState(LemmaState::CitePremises(_)) => {
+                Some("Rows matching its premises, in order")
+            }
+            State::LemmaState(LemmaState::TypeConclusion(_, _)) => {
+                Some("Instance of the conclusion to introduce")
+            }
+            State::AddTentative => Some("Tentative expression"),
+            State::TentativeJustification(_) | State::Justify(_) => {
+                Some("Justification, e.g. I & 0, 1 (empty to leave unjustified)")
             }
+            State::ImpliesState(ImpliesState::EliminateGetAssumption)
+            | State::IffState(IffState::EliminateGetAssumption) => Some("Implication to eliminate"),
             State::ImpliesState(ImpliesState::EliminateGetLeft(_))
-            | State::IffState(IffState::EliminateGetTruth(_)) => ("Index of the truth", true),
-            _ => ("", false),
-        };
+            | State::IffState(IffState::EliminateGetTruth(_)) => Some("Index of the truth"),
+            _ => None,
+        }
+    }
 
-        self.renderer.render(
-            &self.model,
-            &self.info_text(),
+    fn render(&mut self) {
+        let highlight = self.expects_expression();
+        let expression_box = self.expression_box_title().map(|title| ExpressionBox {
             title,
-            &self.expression_buffer,
-            self.expression_cursor,
-            render_box,
+            buffer: self.expression.buffer(),
+            cursor_offset: self.expression.cursor() as u16,
+            highlight,
+        });
+        let popup = match self.tab.state {
+            State::Help(scroll) => Some(Popup {
+                title: "Help",
+                lines: help_text(&self.keys.normal),
+                selected: None,
+                scroll,
+            }),
+            State::Report(title, ref lines, scroll) => Some(Popup {
+                title,
+                lines: lines.clone(),
+                selected: None,
+                scroll,
+            }),
+            State::CommandPalette(selected) => Some(Popup {
+                title: "Matching commands",
+                lines: search(self.expression.buffer())
+                    .iter()
+                    .map(|a| a.name().to_string())
+                    .collect(),
+                selected: Some(selected),
+                scroll: 0,
+            }),
+            _ => None,
+        };
+        let titles = self.tabs[..self.current]
+            .iter()
+            .chain([&self.tab])
+            .chain(&self.tabs[self.current..])
+            .map(|t| t.title.as_str())
+            .collect();
+        self.renderer.render(
+            TabBar {
+                titles,
+                selected: self.current,
+            },
+            &self.tab.model,
+            Info {
+                text: &self.info_text(),
+                warning: self.warning,
+            },
+            expression_box,
+            popup,
+            self.tab.focus,
         );
     }
 
     pub fn listen(&mut self) {
         loop {
-            if self.state == State::Quit {
+            if self.tab.state == State::Quit {
                 break;
             }
 
-            if let Event::Key(key) = event::read().unwrap() {
-                if key.kind != KeyEventKind::Press {
-                    continue;
-                }
-
-                match self.state {
-                    State::Noraml => self.listen_normal(&key.code),
-                    State::AddAssumption => self.listen_add_assumption(&key.code),
-                    State::AddSubproof => self.listen_add_subproof(&key.code),
-                    State::Reiterate => self.listen_reiterate(&key.code),
-                    State::IntroduceChoice => self.listen_introduce(&key.code),
-                    State::EliminateChoice => self.listen_eliminate(&key.code),
-                    State::AbsurdumState(_) => self.listen_absurdum(&key.code),
-                    State::AndState(_) => self.listen_and(&key.code),
-                    State::OrState(_) => self.listen_or(&key.code),
-                    State::NotState(_) => self.listen_not(&key.code),
-                    State::ImpliesState(_) => self.listen_implies(&key.code),
-                    State::IffState(_) => self.listen_iff(&key.code),
+            match event::read().unwrap() {
+                Event::Key(key) if key.kind != KeyEventKind::Press => continue,
+                Event::Key(key) => match self.tab.state {
+                    State::Noraml => self.listen_normal(&key),
+                    State::AddAssumption => self.listen_add_assumption(&key),
+                    State::AddSubproof => self.listen_add_subproof(&key),
+                    State::Reiterate => self.listen_reiterate(&key),
+                    State::AddTentative | State::TentativeJustification(_) | State::Justify(_) => {
+                        self.listen_tentative(&key)
+                    }
+                    State::IntroduceChoice => self.listen_introduce(&key),
+                    State::EliminateChoice => self.listen_eliminate(&key),
+                    State::AbsurdumState(_) => self.listen_absurdum(&key),
+                    State::AndState(_) => self.listen_and(&key),
+                    State::OrState(_) => self.listen_or(&key),
+                    State::NotState(_) => self.listen_not(&key),
+                    State::ImpliesState(_) => self.listen_implies(&key),
+                    State::IffState(_) => self.listen_iff(&key),
+                    State::Definition(_) => self.listen_definition(&key),
+                    State::Help(_) => self.listen_help(&key),
+                    State::CommandPalette(_) => self.listen_command_palette(&key),
+                    State::Export(_) => self.listen_export(&key),
+                    State::ImportCarnap => self.listen_import_carnap(&key),
+                    State::Open => self.listen_open(&key),
+                    State::Save => self.listen_save(&key),
+                    State::SaveLemma => self.listen_save_lemma(&key),
+                    State::LemmaState(_) => self.listen_lemma(&key),
+                    State::CheckSoundness => self.listen_check_soundness(&key),
+                    State::Normalize(_) => self.listen_normalize(&key),
+                    State::Report(_, _, _) => self.listen_report(&key),
                     _ => unreachable!(),
+                },
+                Event::Paste(text) if self.expression_box_title().is_some() => {
+                    self.expression.insert_str(&text)
                 }
+                _ => (),
             }
 
+            self.tab.checkpoint();
             self.render();
             self.info_buffer.clear();
             self.warning = false;
         }
     }
 
-    fn listen_iff(&mut self, code: &KeyCode) {
-        let handler = |app_context: &mut App| match app_context.state {
+    fn listen_iff(&mut self, key: &KeyEvent) {
+        let handler = |app_context: &mut App| match app_context.tab.state {
             State::IffState(IffState::IntroduceGetLeftSubproof) => {
-                match app_context.expression_buffer.parse() {
+                match app_context.expression.buffer().parse() {
                     Err(_) => {
                         app_context
                             .info_buffer
                             .push_str("The input value is not a valid index");
                     }
                     Ok(left) => {
-                        app_context.state =
+                        app_context.tab.state =
                             State::IffState(IffState::IntroduceGetRightSubproof(left));
                         app_context.reset_expression_box();
                     }
                 }
             }
             State::IffState(IffState::IntroduceGetRightSubproof(left)) => {
-                match app_context.expression_buffer.parse() {
+                match app_context.expression.buffer().parse() {
                     Err(_) => {
                         app_context
                             .info_buffer
                             .push_str("The input value is not a valid index");
                     }
                     Ok(right) => {
-                        if !app_context.model.introduce_iff(left, right) {
+                        if !app_context.tab.model.introduce_iff(left, right) {
                             app_context
                                 .info_buffer
                                 .push_str("Select the left subproof then the right subproof");
                             app_context.warning = true;
                         }
-                        app_context.state = State::Noraml;
+                        app_context.tab.state = State::Noraml;
                         app_context.reset_expression_box();
                     }
                 }
             }
             State::IffState(IffState::EliminateGetAssumption) => {
-                match app_context.expression_buffer.parse() {
+                match app_context.expression.buffer().parse() {
                     Err(_) => {
                         app_context
                             .info_buffer
                             .push_str("The input value is not a valid index");
                     }
                     Ok(to_elim) => {
-                        app_context.state = State::IffState(IffState::EliminateGetTruth(to_elim));
+                        app_context.tab.state =
+                            State::IffState(IffState::EliminateGetTruth(to_elim));
                         app_context.reset_expression_box();
                     }
                 }
             }
             State::IffState(IffState::EliminateGetTruth(to_elim)) => {
-                match app_context.expression_buffer.parse() {
+                match app_context.expression.buffer().parse() {
                     Err(_) => {
                         app_context
                             .info_buffer
                             .push_str("The input value is not a valid index");
                     }
                     Ok(truth) => {
-                        if !app_context.model.eliminate_iff(to_elim, truth) {
+                        if !app_context.tab.model.eliminate_iff(to_elim, truth) {
                             app_context.info_buffer.push_str(
                                 "Choose the double implication to eliminate then the truth",
                             );
                             app_context.warning = true;
                         }
-                        app_context.state = State::Noraml;
+                        app_context.tab.state = State::Noraml;
                         app_context.reset_expression_box();
                     }
                 }
             }
             _ => (),
         };
-        self.handle_expression_box_event(code, handler);
+        self.handle_expression_box_event(key, handler);
     }
 
-    fn listen_implies(&mut self, code: &KeyCode) {
-        let handler = |app_context: &mut App| match app_context.state {
+    fn listen_implies(&mut self, key: &KeyEvent) {
+        let handler = |app_context: &mut App| match app_context.tab.state {
             State::ImpliesState(ImpliesState::Introduce) => {
-                match app_context.expression_buffer.parse() {
+                match app_context.expression.buffer().parse() {
                     Err(_) => {
                         app_context
                             .info_buffer
                             .push_str("The input value is not a valid index");
                     }
                     Ok(subproof) => {
-                        if !app_context.model.introduce_implies(subproof) {
+                        if !app_context.tab.model.introduce_implies(subproof) {
                             app_context.info_buffer.push_str("Invalid subproof");
                             app_context.warning = true;
                         }
-                        app_context.state = State::Noraml;
+                        app_context.tab.state = State::Noraml;
                         app_context.reset_expression_box();
                     }
                 }
             }
             State::ImpliesState(ImpliesState::EliminateGetAssumption) => {
-                match app_context.expression_buffer.parse() {
+                match app_context.expression.buffer().parse() {
                     Err(_) => {
                         app_context
                             .info_buffer
                             .push_str("The input value is not a valid index");
                     }
                     Ok(to_elim) => {
-                        app_context.state =
+                        app_context.tab.state =
                             State::ImpliesState(ImpliesState::EliminateGetLeft(to_elim));
                         app_context.reset_expression_box();
                     }
                 }
             }
             State::ImpliesState(ImpliesState::EliminateGetLeft(to_elim)) => {
-                match app_context.expression_buffer.parse() {
+                match app_context.expression.buffer().parse() {
                     Err(_) => {
                         app_context
                             .info_buffer
                             .push_str("The input value is not a valid index");
                     }
                     Ok(truth) => {
-                        if !app_context.model.eliminate_implies(to_elim, truth) {
+                        if !app_context.tab.model.eliminate_implies(to_elim, truth) {
                             app_context
                                 .info_buffer
                                 .push_str("Choose the implication to eliminate then the truth");
                             app_context.warning = true;
                         }
-                        app_context.state = State::Noraml;
+                        app_context.tab.state = State::Noraml;
                         app_context.reset_expression_box();
                     }
                 }
             }
             _ => (),
         };
-        self.handle_expression_box_event(code, handler);
+        self.handle_expression_box_event(key, handler);
     }
 
-    fn listen_not(&mut self, code: &KeyCode) {
-        let handler = |app_context: &mut App| match app_context.state {
-            State::NotState(NotState::Introduce) => match app_context.expression_buffer.parse() {
+    fn listen_not(&mut self, key: &KeyEvent) {
+        let handler = |app_context: &mut App| match app_context.tab.state {
+            State::NotState(NotState::Introduce) => match app_context.expression.buffer().parse() {
                 Err(_) => {
                     app_context
                         .info_buffer
                         .push_str("The input value is not a valid index");
                 }
                 Ok(index) => {
-                    if !app_context.model.introduce_not(index) {
+                    if !app_context.tab.model.introduce_not(index) {
                         app_context
                             .info_buffer
                             .push_str("Subproof assumption does not generate an absurdum");
                         app_context.warning = true;
                     }
-                    app_context.state = State::Noraml;
+                    app_context.tab.state = State::Noraml;
                     app_context.reset_expression_box();
                 }
             },
-            State::NotState(NotState::Eliminate) => match app_context.expression_buffer.parse() {
+            State::NotState(NotState::Eliminate) => match app_context.expression.buffer().parse() {
                 Err(_) => {
                     app_context
                         .info_buffer
                         .push_str("The input value is not a valid index");
                 }
                 Ok(index) => {
-                    if !app_context.model.eliminate_not(index) {
+                    if !app_context.tab.model.eliminate_not(index) {
                         app_context
                             .info_buffer
                             .push_str("Expression selected is not a double negation");
                         app_context.warning = true;
                     }
-                    app_context.state = State::Noraml;
+                    app_context.tab.state = State::Noraml;
                     app_context.reset_expression_box();
                 }
             },
             _ => (),
         };
-        self.handle_expression_box_event(code, handler);
+        self.handle_expression_box_event(key, handler);
     }
 
-    fn listen_reiterate(&mut self, code: &KeyCode) {
-        let handler = |app_context: &mut App| match app_context.expression_buffer.parse() {
+    fn listen_definition(&mut self, key: &KeyEvent) {
+        let handler = |app_context: &mut App| {
+            let rule = match app_context.tab.state {
+                State::Definition(v) => v,
+                _ => unreachable!(),
+            };
+            match app_context.expression.buffer().parse() {
+                Err(_) => {
+                    app_context
+                        .info_buffer
+                        .push_str("The input value is not a valid index");
+                }
+                Ok(index) => {
+                    let model = &mut app_context.tab.model;
+                    let done = match rule {
+                        Rule::IntroXor => model.introduce_xor(index),
+                        Rule::ElimXor => model.eliminate_xor(index),
+                        Rule::IntroNand => model.introduce_nand(index),
+                        Rule::ElimNand => model.eliminate_nand(index),
+                        Rule::IntroNor => model.introduce_nor(index),
+                        Rule::ElimNor => model.eliminate_nor(index),
+                        _ => unreachable!(),
+                    };
+                    if !done {
+                        app_context
+                            .info_buffer
+                            .push_str("Expression selected does not match the definition");
+                        app_context.warning = true;
+                    }
+                    app_context.tab.state = State::Noraml;
+                    app_context.reset_expression_box();
+                }
+            }
+        };
+        self.handle_expression_box_event(key, handler);
+    }
+
+    fn listen_reiterate(&mut self, key: &KeyEvent) {
+        let handler = |app_context: &mut App| match app_context.expression.buffer().parse() {
             Err(_) => {
                 app_context
                     .info_buffer
                     .push_str("The input value is not a valid index");
             }
             Ok(i) => {
-                if !app_context.model.reiterate(i) {
+                if !app_context.tab.model.reiterate(i) {
                     app_context.info_buffer.push_str("Invalid index");
                     app_context.warning = true;
                 }
-                app_context.state = State::Noraml;
+                app_context.tab.state = State::Noraml;
                 app_context.reset_expression_box();
             }
         };
-        self.handle_expression_box_event(code, handler);
+        self.handle_expression_box_event(key, handler);
+    }
+
+    fn listen_tentative(&mut self, key: &KeyEvent) {
+        let handler = |app_context: &mut App| match app_context.tab.state {
+            State::AddTentative => match parse_expression(app_context.expression.buffer()) {
+                parser::Result::Failure => app_context
+                    .info_buffer
+                    .push_str("Expression entered is invalid"),
+                parser::Result::Success(expr, _) => {
+                    app_context.tab.state = State::TentativeJustification(expr);
+                    app_context.reset_expression_box();
+                }
+            },
+            State::TentativeJustification(ref expr) => {
+                let expr = expr.clone();
+                if app_context.expression.buffer().trim().is_empty() {
+                    app_context.tab.model.add_unjustified(&expr);
+                    app_context.tab.state = State::Noraml;
+                    app_context.reset_expression_box();
+                    return;
+                }
+                match parse_justification(app_context.expression.buffer()) {
+                    None => app_context
+                        .info_buffer
+                        .push_str("Justification entered is invalid"),
+                    Some((rule, citations)) => {
+                        app_context.tab.model.add_tentative(&expr, rule, citations);
+                        app_context.tab.state = State::Noraml;
+                        app_context.reset_expression_box();
+                    }
+                }
+            }
+            State::Justify(row) => match parse_justification(app_context.expression.buffer()) {
+                None => app_context
+                    .info_buffer
+                    .push_str("Justification entered is invalid"),
+                Some((rule, citations)) => {
+                    if !app_context.tab.model.justify(row, rule, citations) {
+                        app_context
+                            .info_buffer
+                            .push_str("Assumptions don't need a justification");
+                        app_context.warning = true;
+                    }
+                    app_context.tab.state = State::Noraml;
+                    app_context.reset_expression_box();
+                }
+            },
+            _ => unreachable!(),
+        };
+        self.handle_expression_box_event(key, handler);
     }
 
-    fn listen_or(&mut self, code: &KeyCode) {
-        let handler = |app_context: &mut App| match app_context.state {
+    fn listen_or(&mut self, key: &KeyEvent) {
+        let handler = |app_context: &mut App| match app_context.tab.state {
             State::OrState(OrState::IntroduceGetAssumption) => {
-                match app_context.expression_buffer.parse() {
+                match app_context.expression.buffer().parse() {
                     Err(_) => {
                         app_context
                             .info_buffer
                             .push_str("The input value is not a valid index");
                     }
                     Ok(ass) => {
-                        app_context.state = State::OrState(OrState::IntroduceGetProposition(ass));
+                        app_context.tab.state =
+                            State::OrState(OrState::IntroduceGetProposition(ass));
                         app_context.reset_expression_box();
                     }
                 }
             }
             State::OrState(OrState::IntroduceGetProposition(ass)) => {
-                match parse_expression(&app_context.expression_buffer) {
+                match parse_expression(app_context.expression.buffer()) {
                     parser::Result::Failure => app_context
                         .info_buffer
                         .push_str("The input expression is not valid"),
                     parser::Result::Success(ris, _) => {
-                        if !app_context.model.introduce_or(ass, &ris) {
+                        if !app_context.tab.model.introduce_or(ass, &ris) {
                             app_context.info_buffer.push_str(
                                 "Select the left or right prop used in the input expression",
                             );
                             app_context.warning = true;
                         }
-                        app_context.state = State::Noraml;
+                        app_context.tab.state = State::Noraml;
                         app_context.reset_expression_box();
                     }
                 }
             }
             State::OrState(OrState::EliminateGetAssumption) => {
-                match app_context.expression_buffer.parse() {
+                match app_context.expression.buffer().parse() {
                     Err(_) => {
                         app_context
                             .info_buffer
                             .push_str("The input value is not a valid index");
                     }
                     Ok(ass) => {
-                        app_context.state = State::OrState(OrState::EliminateGetLeftSubproof(ass));
+                        app_context.tab.state = State::OrState(OrState::EliminateGetSubproofs(ass));
                         app_context.reset_expression_box();
                     }
                 }
             }
-            State::OrState(OrState::EliminateGetLeftSubproof(ass)) => {
-                match app_context.expression_buffer.parse() {
-                    Err(_) => {
+            State::OrState(OrState::EliminateGetSubproofs(ass)) => {
+                match parse_citations(app_context.expression.buffer()) {
+                    None => {
                         app_context
                             .info_buffer
-                            .push_str("The input value is not a valid index");
+                            .push_str("The input values are not valid indices");
                     }
-                    Ok(left) => {
-                        app_context.state =
-                            State::OrState(OrState::EliminateGetRightSubproof(ass, left));
-                        app_context.reset_expression_box();
-                    }
-                }
-            }
-            State::OrState(OrState::EliminateGetRightSubproof(ass, left)) => {
-                match app_context.expression_buffer.parse() {
-                    Err(_) => {
-                        app_context
-                            .info_buffer
-                            .push_str("The input value is not a valid index");
-                    }
-                    Ok(right) => {
-                        if !app_context.model.eliminate_or(ass, left, right) {
-                            app_context
-                                .info_buffer
-                                .push_str("Select the or to eliminate then the 2 subproofs");
+                    Some(subproofs) => {
+                        if !app_context.tab.model.eliminate_or(ass, &subproofs) {
+                            app_context.info_buffer.push_str(
+                                "Select the or to eliminate then one subproof per disjunct",
+                            );
                             app_context.warning = true;
                         }
-                        app_context.state = State::Noraml;
+                        app_context.tab.state = State::Noraml;
                         app_context.reset_expression_box();
                     }
                 }
             }
             _ => (),
         };
-        self.handle_expression_box_event(code, handler);
+        self.handle_expression_box_event(key, handler);
     }
 
-    fn listen_and(&mut self, code: &KeyCode) {
-        let handler = |app_context: &mut App| match app_context.state {
-            State::AndState(AndState::IntroduceGetLeftAssumption) => {
-                match app_context.expression_buffer.parse() {
-                    Err(_) => {
-                        app_context
-                            .info_buffer
-                            .push_str("The input value is not a valid index");
-                    }
-                    Ok(left) => {
-                        app_context.state =
-                            State::AndState(AndState::IntroduceGetRightAssumption(left));
-                        app_context.reset_expression_box();
-                    }
-                }
-            }
-            State::AndState(AndState::IntroduceGetRightAssumption(left)) => {
-                match app_context.expression_buffer.parse() {
-                    Err(_) => {
+    fn listen_and(&mut self, key: &KeyEvent) {
+        let handler = |app_context: &mut App| match app_context.tab.state {
+            State::AndState(AndState::IntroduceGetAssumptions) => {
+                match parse_citations(app_context.expression.buffer()) {
+                    None => {
                         app_context
                             .info_buffer
-                            .push_str("The input value is not a valid index");
+                            .push_str("The input values are not valid indices");
                     }
-                    Ok(right) => {
-                        if !app_context.model.introduce_and(left, right) {
+                    Some(rows) => {
+                        if !app_context.tab.model.introduce_and(&rows) {
                             app_context
                                 .info_buffer
                                 .push_str("Selected assumptions are not valid");
                             app_context.warning = true;
                         }
-                        app_context.state = State::Noraml;
+                        app_context.tab.state = State::Noraml;
                         app_context.reset_expression_box();
                     }
                 }
             }
             State::AndState(AndState::EliminateGetAssumption) => {
-                match app_context.expression_buffer.parse() {
+                match app_context.expression.buffer().parse() {
                     Err(_) => {
                         app_context
                             .info_buffer
                             .push_str("The input value is not a valid index");
                     }
                     Ok(left) => {
-                        app_context.state =
-                            State::AndState(AndState::EliminateGetProposition(left));
+                        match app_context.tab.model.proposition(left).map(|p| p.borrow()) {
+                            Some(Proposition::And(_)) => {
+                                app_context.tab.state =
+                                    State::AndState(AndState::EliminateChooseConjunct(left))
+                            }
+                            _ => {
+                                app_context
+                                    .info_buffer
+                                    .push_str("Selected row is not a conjunction");
+                                app_context.warning = true;
+                                app_context.tab.state = State::Noraml;
+                            }
+                        }
                         app_context.reset_expression_box();
                     }
                 }
             }
-            State::AndState(AndState::EliminateGetProposition(assum)) => {
-                match parse_expression(&app_context.expression_buffer) {
-                    parser::Result::Failure => app_context
-                        .info_buffer
-                        .push_str("Expression entered is invalid"),
-                    parser::Result::Success(r, _) => {
-                        if !app_context.model.eliminate_and(assum, &r) {
-                            app_context
-                                .info_buffer
-                                .push_str("Input expression is neither left or right prop in AND");
+            State::AndState(AndState::EliminateChooseConjunct(assum)) => {
+                let conjunct = match app_context.tab.model.proposition(assum).map(|p| p.borrow()) {
+                    Some(Proposition::And(conjuncts)) => app_context
+                        .expression
+                        .buffer()
+                        .trim()
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|n| conjuncts.get(n.checked_sub(1)?))
+                        .cloned(),
+                    _ => unreachable!(),
+                };
+                match conjunct {
+                    None => {
+                        app_context
+                            .info_buffer
+                            .push_str("The input value is not a conjunct's position");
+                    }
+                    Some(conjunct) => {
+                        if !app_context.tab.model.eliminate_and(assum, &conjunct) {
+                            app_context.info_buffer.push_str("Invalid conjunct");
                             app_context.warning = true;
                         }
-                        app_context.state = State::Noraml;
+                        app_context.tab.state = State::Noraml;
                         app_context.reset_expression_box();
                     }
                 }
             }
             _ => unreachable!(),
         };
-        self.handle_expression_box_event(code, handler);
+        self.handle_expression_box_event(key, handler);
     }
 
-    fn listen_absurdum(&mut self, code: &KeyCode) {
-        let handler = |app_context: &mut App| match app_context.state {
+    fn listen_absurdum(&mut self, key: &KeyEvent) {
+        let handler = |app_context: &mut App| match app_context.tab.state {
             State::AbsurdumState(AbsurdumState::IntroduceGetAssumption1) => {
-                match app_context.expression_buffer.parse() {
+                match app_context.expression.buffer().parse() {
                     Err(_) => {
                         app_context
                             .info_buffer
                             .push_str("The input value is not a valid index");
                     }
                     Ok(v) => {
-                        app_context.state =
+                        app_context.tab.state =
                             State::AbsurdumState(AbsurdumState::IntroduceGetAssumption2(v));
                         app_context.reset_expression_box();
                     }
                 }
             }
             State::AbsurdumState(AbsurdumState::IntroduceGetAssumption2(a1)) => {
-                match app_context.expression_buffer.parse() {
+                match app_context.expression.buffer().parse() {
                     Err(_) => {
                         app_context
                             .info_buffer
                             .push_str("The input value is not a valid index");
                     }
                     Ok(a2) => {
-                        if !app_context.model.introduce_absurdum(a1, a2) {
+                        if !app_context.tab.model.introduce_absurdum(a1, a2) {
                             app_context
                                 .info_buffer
                                 .push_str("The input index are not valid");
                             app_context.warning = true;
                         }
                         app_context.reset_expression_box();
-                        app_context.state = State::Noraml;
+                        app_context.tab.state = State::Noraml;
                     }
                 }
             }
             State::AbsurdumState(AbsurdumState::EliminateGetAssumption) => {
-                match app_context.expression_buffer.parse() {
+                match app_context.expression.buffer().parse() {
                     Err(_) => {
                         app_context
                             .info_buffer
                             .push_str("The input value is not a valid index");
                     }
                     Ok(assum) => {
-                        app_context.state =
+                        app_context.tab.state =
                             State::AbsurdumState(AbsurdumState::EliminateGetProposition(assum));
                         app_context.reset_expression_box();
                     }
                 }
             }
             State::AbsurdumState(AbsurdumState::EliminateGetProposition(assum)) => {
-                match parse_expression(&app_context.expression_buffer) {
+                match parse_expression(app_context.expression.buffer()) {
                     parser::Result::Failure => app_context
                         .info_buffer
                         .push_str("Expression entered is invalid"),
                     parser::Result::Success(ded, _) => {
-                        if !app_context.model.eliminate_absurdum(assum, &ded) {
+                        if !app_context.tab.model.eliminate_absurdum(assum, &ded) {
                             app_context
                                 .info_buffer
                                 .push_str("The assumption you selected is not an absurdum");
                             app_context.warning = true;
                         }
                         app_context.reset_expression_box();
-                        app_context.state = State::Noraml;
+                        app_context.tab.state = State::Noraml;
                     }
                 }
             }
             _ => unreachable!(),
         };
-        self.handle_expression_box_event(code, handler);
+        self.handle_expression_box_event(key, handler);
     }
 
-    fn listen_eliminate(&mut self, code: &KeyCode) {
-        match code {
-            KeyCode::Esc => self.state = State::Noraml,
-            KeyCode::Char('a') => {
-                self.state = State::AbsurdumState(AbsurdumState::EliminateGetAssumption)
+    fn listen_eliminate(&mut self, key: &KeyEvent) {
+        match key.code {
+            KeyCode::Esc => self.tab.state = State::Noraml,
+            _ => {
+                if let Some(rule) = bound(&self.keys.eliminate, key) {
+                    self.start_rule(rule);
+                }
             }
-            KeyCode::Char('n') => self.state = State::AndState(AndState::EliminateGetAssumption),
-            KeyCode::Char('o') => self.state = State::OrState(OrState::EliminateGetAssumption),
-            KeyCode::Char('t') => self.state = State::NotState(NotState::Eliminate),
-            KeyCode::Char('i') => {
-                self.state = State::ImpliesState(ImpliesState::EliminateGetAssumption)
+        }
+    }
+
+    fn listen_introduce(&mut self, key: &KeyEvent) {
+        match key.code {
+            KeyCode::Esc => self.tab.state = State::Noraml,
+            _ => {
+                if let Some(rule) = bound(&self.keys.introduce, key) {
+                    self.start_rule(rule);
+                }
             }
-            KeyCode::Char('f') => self.state = State::IffState(IffState::EliminateGetAssumption),
-            _ => (),
         }
     }
 
-    fn listen_introduce(&mut self, code: &KeyCode) {
-        match code {
-            KeyCode::Esc => self.state = State::Noraml,
-            KeyCode::Char('a') => {
-                self.state = State::AbsurdumState(AbsurdumState::IntroduceGetAssumption1)
+    fn start_rule(&mut self, rule: Rule) {
+        self.tab.state = State::start(rule);
+        if rule == Rule::IntroVerum {
+            self.tab.model.introduce_verum();
+        }
+    }
+
+    fn listen_help(&mut self, key: &KeyEvent) {
+        if let State::Help(scroll) = self.tab.state {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('?') => {
+                    self.tab.state = State::Noraml
+                }
+                KeyCode::Up => self.tab.state = State::Help(scroll.saturating_sub(1)),
+                KeyCode::Down => self.tab.state = State::Help(scroll + 1),
+                KeyCode::PageUp => self.tab.state = State::Help(scroll.saturating_sub(10)),
+                KeyCode::PageDown => self.tab.state = State::Help(scroll + 10),
+                _ => (),
             }
-            KeyCode::Char('n') => {
-                self.state = State::AndState(AndState::IntroduceGetLeftAssumption)
+        }
+    }
+
+    fn listen_command_palette(&mut self, key: &KeyEvent) {
+        let selected = match self.tab.state {
+            State::CommandPalette(v) => v,
+            _ => unreachable!(),
+        };
+        let matches = search(self.expression.buffer());
+        match key.code {
+            KeyCode::Up => self.tab.state = State::CommandPalette(selected.saturating_sub(1)),
+            KeyCode::Down if selected + 1 < matches.len() => {
+                self.tab.state = State::CommandPalette(selected + 1)
+            }
+            KeyCode::Down => (),
+            KeyCode::Enter => match matches.get(selected) {
+                None => self.info_buffer.push_str("No command matches"),
+                Some(action) => {
+                    self.reset_expression_box();
+                    self.tab.state = State::Noraml;
+                    self.perform(*action);
+                }
+            },
+            _ => {
+                self.handle_expression_box_event(key, |_| ());
+                if self.tab.state != State::Noraml {
+                    self.tab.state = State::CommandPalette(0);
+                }
+            }
+        }
+    }
+
+    fn listen_export(&mut self, key: &KeyEvent) {
+        let handler = |app_context: &mut App| {
+            let format = match app_context.tab.state {
+                State::Export(v) => v,
+                _ => unreachable!(),
+            };
+            let path = app_context.expression.buffer().trim().to_string();
+            match fs::write(
+                &path,
+                export(
+                    &app_context.tab.model,
+                    format,
+                    app_context.symbols,
+                    app_context.bars,
+                ),
+            ) {
+                Err(e) => {
+                    app_context
+                        .info_buffer
+                        .push_str(format!("Could not write {}: {}", path, e).as_str());
+                    app_context.warning = true;
+                }
+                Ok(()) => {
+                    app_context
+                        .info_buffer
+                        .push_str(format!("Exported to {}", path).as_str());
+                    app_context.tab.state = State::Noraml;
+                    app_context.reset_expression_box();
+                }
+            }
+        };
+        self.handle_expression_box_event(key, handler);
+    }
+
+    /// Opens the imported proof in a new tab and says how many of its rows do not hold.
+    fn listen_import_carnap(&mut self, key: &KeyEvent) {
+        let handler = |app_context: &mut App| {
+            let path = app_context.expression.buffer().trim().to_string();
+            let res = fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| carnap::import(&text));
+            match res {
+                Err(e) => {
+                    app_context
+                        .info_buffer
+                        .push_str(format!("Could not import {}: {}", path, e).as_str());
+                    app_context.warning = true;
+                }
+                Ok(model) => {
+                    app_context.tab.state = State::Noraml;
+                    app_context.reset_expression_box();
+                    let failing = (0..model.len())
+                        .filter(|row| model.row_status(*row) != RowStatus::Valid)
+                        .count();
+                    let info = match failing {
+                        0 => format!("Imported {}", path),
+                        n => format!("Imported {}, {} of its rows do not hold", path, n),
+                    };
+                    app_context.info_buffer.push_str(info.as_str());
+                    app_context.warning = failing > 0;
+                    app_context.open_tab(path, model);
+                }
+            }
+        };
+        self.handle_expression_box_event(key, handler);
+    }
+
+    /// Opens the proof file in a new tab named after it.
+    fn listen_open(&mut self, key: &KeyEvent) {
+        let handler = |app_context: &mut App| {
+            let path = PathBuf::from(app_context.expression.buffer().trim());
+            match read_proof_file(&path) {
+                Err(e) => {
+                    app_context
+                        .info_buffer
+                        .push_str(format!("Could not open {}: {}", path.display(), e).as_str());
+                    app_context.warning = true;
+                }
+                Ok(model) => {
+                    app_context.tab.state = State::Noraml;
+                    app_context.reset_expression_box();
+                    app_context
+                        .info_buffer
+                        .push_str(format!("Opened {}", path.display()).as_str());
+                    app_context.open_tab(path.display().to_string(), model);
+                    app_context.tab.path = Some(path);
+                }
+            }
+        };
+        self.handle_expression_box_event(key, handler);
+    }
+
+    fn listen_save(&mut self, key: &KeyEvent) {
+        let handler = |app_context: &mut App| {
+            let path = PathBuf::from(app_context.expression.buffer().trim());
+            if app_context.save(path) {
+                app_context.tab.state = State::Noraml;
+                app_context.reset_expression_box();
+            }
+        };
+        self.handle_expression_box_event(key, handler);
+    }
+
+    /// Writes the proof to `path`, which becomes the file of the tab, and says whether it could.
+    fn save(&mut self, path: PathBuf) -> bool {
+        match write_proof_file(&path, &self.tab.model, self.symbols, self.bars) {
+            Err(e) => {
+                self.info_buffer
+                    .push_str(format!("Could not save {}: {}", path.display(), e).as_str());
+                self.warning = true;
+                false
+            }
+            Ok(()) => {
+                self.info_buffer
+                    .push_str(format!("Saved {}", path.display()).as_str());
+                self.tab.title = path.display().to_string();
+                self.tab.path = Some(path);
+                true
+            }
+        }
+    }
+
+    fn listen_save_lemma(&mut self, key: &KeyEvent) {
+        let handler = |app_context: &mut App| {
+            let name = app_context.expression.buffer().trim().to_string();
+            let res = Lemma::from_proof(&name, &app_context.tab.model).and_then(|lemma| {
+                let path = Config::save_lemma(&lemma)?;
+                lemma.register();
+                Ok(path)
+            });
+            match res {
+                Err(e) => {
+                    app_context.info_buffer.push_str(&e);
+                    app_context.warning = true;
+                }
+                Ok(path) => {
+                    app_context
+                        .info_buffer
+                        .push_str(format!("Saved lemma {} to {}", name, path.display()).as_str());
+                    app_context.tab.state = State::Noraml;
+                    app_context.reset_expression_box();
+                }
+            }
+        };
+        self.handle_expression_box_event(key, handler);
+    }
+
+    fn listen_check_soundness(&mut self, key: &KeyEvent) {
+        let handler =
+            |app_context: &mut App| match soundness_report(app_context.expression.buffer()) {
+                Err(e) => {
+                    app_context.info_buffer.push_str(e);
+                    app_context.warning = true;
+                }
+                Ok(lines) => {
+                    app_context.tab.state = State::Report("Soundness", lines, 0);
+                    app_context.reset_expression_box();
+                }
+            };
+        self.handle_expression_box_event(key, handler);
+    }
+
+    fn listen_normalize(&mut self, key: &KeyEvent) {
+        let handler = |app_context: &mut App| {
+            let form = match app_context.tab.state {
+                State::Normalize(form) => form,
+                _ => unreachable!(),
+            };
+            let text = app_context.expression.buffer();
+            let prop = if text.trim().is_empty() {
+                let focused = app_context.tab.focus;
+                focused.and_then(|row| app_context.tab.model.proposition(row).cloned())
+            } else {
+                match parse_expression(text) {
+                    parser::Result::Success(prop, rest) if rest.trim().is_empty() => Some(prop),
+                    _ => None,
+                }
+            };
+            let Some(prop) = prop else {
+                app_context
+                    .info_buffer
+                    .push_str("Type an expression or focus a row to convert");
+                app_context.warning = true;
+                return;
+            };
+            match normal_form_report(&prop, form, app_context.symbols) {
+                Err(e) => {
+                    app_context.info_buffer.push_str(e);
+                    app_context.warning = true;
+                }
+                Ok(lines) => {
+                    let title = form.map_or("Tseitin CNF", |form| form.name());
+                    app_context.tab.state = State::Report(title, lines, 0);
+                    app_context.reset_expression_box();
+                }
+            }
+        };
+        self.handle_expression_box_event(key, handler);
+    }
+
+    fn listen_report(&mut self, key: &KeyEvent) {
+        if let State::Report(_, _, scroll) = &mut self.tab.state {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => self.tab.state = State::Noraml,
+                KeyCode::Up => *scroll = scroll.saturating_sub(1),
+                KeyCode::Down => *scroll += 1,
+                KeyCode::PageUp => *scroll = scroll.saturating_sub(10),
+                KeyCode::PageDown => *scroll += 10,
+                _ => (),
             }
-            KeyCode::Char('o') => self.state = State::OrState(OrState::IntroduceGetAssumption),
-            KeyCode::Char('t') => self.state = State::NotState(NotState::Introduce),
-            KeyCode::Char('i') => self.state = State::ImpliesState(ImpliesState::Introduce),
-            KeyCode::Char('f') => self.state = State::IffState(IffState::IntroduceGetLeftSubproof),
-            _ => (),
         }
     }
 
-    fn listen_add_subproof(&mut self, code: &KeyCode) {
+    fn listen_lemma(&mut self, key: &KeyEvent) {
+        let handler = |app_context: &mut App| match &app_context.tab.state {
+            State::LemmaState(LemmaState::Choose) => {
+                match Lemma::find(app_context.expression.buffer().trim()) {
+                    None => app_context.info_buffer.push_str("No lemma has this name"),
+                    Some(rule) => {
+                        app_context.tab.state = State::LemmaState(LemmaState::CitePremises(rule));
+                        app_context.reset_expression_box();
+                    }
+                }
+            }
+            State::LemmaState(LemmaState::CitePremises(rule)) => {
+                let rule = *rule;
+                // Rules that are not registered fail when applied, so they ask for no conclusion
+                let determines_conclusion = match rule {
+                    Rule::Lemma(id) => Lemma::get(id).is_none_or(|l| l.determines_conclusion()),
+                    Rule::Custom(id) => {
+                        CustomRule::get(id).is_none_or(|r| r.determines_conclusion())
+                    }
+                    _ => unreachable!(),
+                };
+                match parse_citations(app_context.expression.buffer()) {
+                    None => {
+                        app_context
+                            .info_buffer
+                            .push_str("The input values are not valid indices");
+                    }
+                    Some(rows) if !determines_conclusion => {
+                        app_context.tab.state =
+                            State::LemmaState(LemmaState::TypeConclusion(rule, rows));
+                        app_context.reset_expression_box();
+                    }
+                    Some(rows) => {
+                        if !apply_instance(&mut app_context.tab.model, rule, &rows, None) {
+                            app_context
+                                .info_buffer
+                                .push_str("Selected rows are not an instance of the premises");
+                            app_context.warning = true;
+                        }
+                        app_context.tab.state = State::Noraml;
+                        app_context.reset_expression_box();
+                    }
+                }
+            }
+            State::LemmaState(LemmaState::TypeConclusion(rule, rows)) => {
+                let (rule, rows) = (*rule, rows.clone());
+                match parse_expression(app_context.expression.buffer()) {
+                    parser::Result::Failure => app_context
+                        .info_buffer
+                        .push_str("Expression entered is invalid"),
+                    parser::Result::Success(conclusion, _) => {
+                        if !apply_instance(
+                            &mut app_context.tab.model,
+                            rule,
+                            &rows,
+                            Some(&conclusion),
+                        ) {
+                            app_context
+                                .info_buffer
+                                .push_str("Selected rows and expression are not an instance");
+                            app_context.warning = true;
+                        }
+                        app_context.tab.state = State::Noraml;
+                        app_context.reset_expression_box();
+                    }
+                }
+            }
+            _ => unreachable!(),
+        };
+        self.handle_expression_box_event(key, handler);
+    }
+
+    fn listen_add_subproof(&mut self, key: &KeyEvent) {
         let handler = |app_context: &mut App| {
-            let buf = app_context.expression_buffer.clone();
+            let buf = app_context.expression.buffer().to_string();
             match parse_expression(&buf) {
                 parser::Result::Failure => app_context
                     .info_buffer
                     .push_str("Expression entered is invalid"),
                 parser::Result::Success(expr, _) => {
-                    app_context.model.add_subproof(&expr);
-                    app_context.state = State::Noraml;
+                    app_context.tab.model.add_subproof(&expr);
+                    app_context.tab.state = State::Noraml;
                     app_context.reset_expression_box();
                 }
             }
         };
-        self.handle_expression_box_event(code, handler);
+        self.handle_expression_box_event(key, handler);
     }
 
-    fn listen_add_assumption(&mut self, code: &KeyCode) {
+    fn listen_add_assumption(&mut self, key: &KeyEvent) {
         let handler = |app_context: &mut App| {
-            let buf = app_context.expression_buffer.clone();
+            let buf = app_context.expression.buffer().to_string();
             match parse_expression(&buf) {
                 parser::Result::Failure => app_context
                     .info_buffer
                     .push_str("Expression entered is invalid"),
                 parser::Result::Success(expr, _) => {
-                    if !app_context.model.add_assumption(&expr) {
+                    if !app_context.tab.model.add_assumption(&expr) {
                         app_context
                             .info_buffer
                             .push_str("Delete all deductions before adding assumptions");
                         app_context.warning = true;
                     }
-                    app_context.state = State::Noraml;
+                    app_context.tab.state = State::Noraml;
                     app_context.reset_expression_box();
                 }
             }
         };
-        self.handle_expression_box_event(code, handler);
-    }
-
-    fn listen_normal(&mut self, code: &KeyCode) {
-        match code {
-            KeyCode::Char('i') => self.state = State::IntroduceChoice,
-            KeyCode::Char('e') => self.state = State::EliminateChoice,
-            KeyCode::Char('a') => self.state = State::AddAssumption,
-            KeyCode::Char('s') => self.state = State::AddSubproof,
-            KeyCode::Char('n') => self.model.end_subproof(),
-            KeyCode::Char('r') => self.state = State::Reiterate,
-            KeyCode::Char('d') => self.model.delete_last_row(),
-            KeyCode::Char('q') => self.state = State::Quit,
-            _ => (),
+        self.handle_expression_box_event(key, handler);
+    }
+
+    fn listen_normal(&mut self, key: &KeyEvent) {
+        match key.code {
+            KeyCode::Up => self.perform(Action::FocusUp),
+            KeyCode::Down => self.perform(Action::FocusDown),
+            _ => {
+                if let Some(action) = bound(&self.keys.normal, key) {
+                    self.perform(action);
+                }
+            }
+        }
+    }
+
+    fn perform(&mut self, action: Action) {
+        match action {
+            Action::Introduce => self.tab.state = State::IntroduceChoice,
+            Action::Eliminate => self.tab.state = State::EliminateChoice,
+            Action::AddAssumption => self.tab.state = State::AddAssumption,
+            Action::AddSubproof => self.tab.state = State::AddSubproof,
+            Action::EndSubproof => self.tab.model.end_subproof(),
+            Action::AddTentative => self.tab.state = State::AddTentative,
+            Action::Justify => match self.tab.focus {
+                Some(row) => self.tab.state = State::Justify(row),
+                None => {
+                    self.info_buffer
+                        .push_str("Focus a row with Up/Down before justifying it");
+                    self.warning = true;
+                }
+            },
+            Action::DeleteLastRow => {
+                self.tab.model.delete_last_row();
+                self.tab.clamp_focus();
+            }
+            Action::Undo | Action::Redo => {
+                let done = match action {
+                    Action::Undo => self.tab.undo(),
+                    _ => self.tab.redo(),
+                };
+                if !done {
+                    self.info_buffer.push_str("Nothing to ");
+                    self.info_buffer.push_str(action.name());
+                    self.warning = true;
+                }
+                self.tab.clamp_focus();
+            }
+            Action::Copy => self.copy(),
+            Action::Paste => self.paste(),
+            Action::CopyProof => {
+                let text = export(&self.tab.model, Format::Markdown, self.symbols, self.bars);
+                match clipboard::copy(&text) {
+                    Ok(()) => self.info_buffer.push_str("Copied the proof as Markdown"),
+                    Err(e) => {
+                        self.info_buffer
+                            .push_str(format!("Could not copy the proof: {}", e).as_str());
+                        self.warning = true;
+                    }
+                }
+            }
+            Action::NewTab | Action::DuplicateTab => {
+                self.opened += 1;
+                let (title, model) = match action {
+                    Action::NewTab => (format!("proof {}", self.opened), Fitch::new()),
+                    _ => (format!("{} (copy)", self.tab.title), self.tab.model.clone()),
+                };
+                self.open_tab(title, model);
+            }
+            Action::CloseTab => self.close_tab(),
+            Action::NextTab => self.switch_tab((self.current + 1) % (self.tabs.len() + 1)),
+            Action::PreviousTab => {
+                self.switch_tab((self.current + self.tabs.len()) % (self.tabs.len() + 1))
+            }
+            Action::Open => self.tab.state = State::Open,
+            Action::Save => match self.tab.path.clone() {
+                Some(path) => {
+                    self.save(path);
+                }
+                None => self.tab.state = State::Save,
+            },
+            Action::Help => self.tab.state = State::Help(0),
+            Action::CommandPalette => self.tab.state = State::CommandPalette(0),
+            Action::Quit => self.tab.state = State::Quit,
+            Action::FocusUp => self.tab.focus = self.tab.focus.map(|f| f.saturating_sub(1)),
+            Action::FocusDown => {
+                self.tab.focus = match self.tab.focus {
+                    None if !self.tab.model.is_empty() => Some(0),
+                    Some(f) if f + 1 < self.tab.model.len() => Some(f + 1),
+                    f => f,
+                }
+            }
+            Action::Apply(rule) => self.start_rule(rule),
+            Action::SetTheme(theme) => self.renderer.set_theme(theme.theme()),
+            Action::Export(format) => self.tab.state = State::Export(format),
+            Action::ImportCarnap => self.tab.state = State::ImportCarnap,
+            Action::SaveLemma => self.tab.state = State::SaveLemma,
+            Action::ApplyLemma => self.tab.state = State::LemmaState(LemmaState::Choose),
+            Action::CheckSoundness => self.tab.state = State::CheckSoundness,
+            Action::Normalize(form) => self.tab.state = State::Normalize(form),
+        }
+    }
+
+    fn expects_expression(&self) -> bool {
+        matches!(
+            self.tab.state,
+            State::AddAssumption
+                | State::AddSubproof
+                | State::AddTentative
+                | State::AbsurdumState(AbsurdumState::EliminateGetProposition(_))
+                | State::OrState(OrState::IntroduceGetProposition(_))
+                | State::LemmaState(LemmaState::TypeConclusion(_, _))
+        )
+    }
+
+    /// Replaces the expression with the next (or previous) subformula already in the proof that
+    /// starts like what has been typed so far.
+    fn complete(&mut self, forward: bool) {
+        let (candidates, index) = match self.completion.take() {
+            Some((candidates, index)) => {
+                let len = candidates.len();
+                let index = if forward {
+                    (index + 1) % len
+                } else {
+                    (index + len - 1) % len
+                };
+                (candidates, index)
+            }
+            None => {
+                let candidates = self.completion_candidates();
+                if candidates.is_empty() {
+                    return;
+                }
+                let index = if forward { 0 } else { candidates.len() - 1 };
+                (candidates, index)
+            }
+        };
+        self.expression.set(&candidates[index].to_string());
+        self.completion = Some((candidates, index));
+    }
+
+    fn completion_candidates(&self) -> Vec<Rc<Proposition>> {
+        let candidates = match self.tab.state {
+            State::OrState(OrState::IntroduceGetProposition(row)) => {
+                let cited = match self.tab.model.proposition(row) {
+                    None => return Vec::new(),
+                    Some(v) => v,
+                };
+                let mut res = Vec::new();
+                for p in self.tab.model.subformulas(Some(row)) {
+                    let existing = match p.borrow() {
+                        Proposition::Or(disjuncts) if disjuncts.contains(cited) => Some(p.clone()),
+                        _ => None,
+                    };
+                    for or in existing.into_iter().chain([
+                        Proposition::new_or(cited, &p),
+                        Proposition::new_or(&p, cited),
+                    ]) {
+                        if !res.contains(&or) {
+                            res.push(or);
+                        }
+                    }
+                }
+                res
+            }
+            State::AbsurdumState(AbsurdumState::EliminateGetProposition(row)) => {
+                self.tab.model.subformulas(Some(row))
+            }
+            _ => self.tab.model.subformulas(None),
+        };
+
+        let compact = |s: &str| s.chars().filter(|c| !c.is_whitespace()).collect::<String>();
+        let typed = compact(self.expression.buffer());
+        let matching: Vec<Rc<Proposition>> = candidates
+            .iter()
+            .filter(|c| compact(&c.to_string()).starts_with(&typed))
+            .cloned()
+            .collect();
+        if matching.is_empty() {
+            candidates
+        } else {
+            matching
         }
     }
 
     fn reset_expression_box(&mut self) {
-        self.expression_buffer.clear();
-        self.expression_cursor = 0;
+        self.expression.clear();
     }
 
     // Not sure if it avoids much code duplication. It also requires a clone thus another
     // allocation. If it starts to become bothersome just revert this code section to
     // 103cd74bd33e8bb550512cb745b2ff6bf89e35e1
-    fn handle_expression_box_event(&mut self, code: &KeyCode, mut handler: impl FnMut(&mut App)) {
-        match code {
-            KeyCode::Enter => handler(self),
-            KeyCode::Backspace if !self.expression_buffer.is_empty() => {
-                if self.expression_cursor > 0 {
-                    self.expression_buffer
-                        .remove(self.expression_cursor as usize - 1);
-                    self.expression_cursor -= 1;
+    fn handle_expression_box_event(&mut self, key: &KeyEvent, mut handler: impl FnMut(&mut App)) {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        if !matches!(key.code, KeyCode::Tab | KeyCode::BackTab) {
+            self.completion = None;
+        }
+        match key.code {
+            KeyCode::Enter => {
+                if self.expects_expression() {
+                    self.expression.remember();
                 }
+                handler(self)
             }
-            KeyCode::Char(c) => {
-                self.expression_buffer
-                    .insert(self.expression_cursor as usize, *c);
-                self.expression_cursor += 1;
-            }
+            KeyCode::Backspace if alt => self.expression.delete_word(),
+            KeyCode::Char('w') if ctrl => self.expression.delete_word(),
+            KeyCode::Backspace => self.expression.backspace(),
+            KeyCode::Delete => self.expression.delete(),
+            KeyCode::Char('a') if ctrl => self.expression.home(),
+            KeyCode::Char('e') if ctrl => self.expression.end(),
+            KeyCode::Char(c) if !ctrl && !alt => self.expression.insert(c),
             KeyCode::Esc => {
-                self.expression_buffer.clear();
-                self.state = State::Noraml
-            }
-            KeyCode::Left if self.expression_cursor > 0 => self.expression_cursor -= 1,
-            KeyCode::Right if (self.expression_cursor as usize) < self.expression_buffer.len() => {
-                self.expression_cursor += 1
+                self.expression.clear();
+                self.tab.state = State::Noraml
             }
+            KeyCode::Left if ctrl => self.expression.word_left(),
+            KeyCode::Right if ctrl => self.expression.word_right(),
+            KeyCode::Left => self.expression.left(),
+            KeyCode::Right => self.expression.right(),
+            KeyCode::Home => self.expression.home(),
+            KeyCode::End => self.expression.end(),
+            KeyCode::Tab if self.expects_expression() => self.complete(true),
+            KeyCode::BackTab if self.expects_expression() => self.complete(false),
+            KeyCode::Up => self.expression.history_up(),
+            KeyCode::Down => self.expression.history_down(),
             _ => (),
         }
     }
 
     fn info_text(&self) -> String {
-        match self.state {
-            State::Noraml if !self.warning => [
-                "[i]ntroduce",
-                "[e]liminate",
-                "add [a]ssumption",
-                "add [s]ubproof",
-                "e[n]d subproof",
-                "[r]eiterate",
-                "[d]elete last row",
-                "[q]uit",
-            ]
-            .join("   ")
-            .to_string(),
-            State::IntroduceChoice | State::EliminateChoice => {
-                ["[a]bsurdum", "a[n]d", "[o]r", "no[t]", "[i]mplies", "i[f]f"]
-                    .join("    ")
-                    .to_string()
+        match self.tab.state {
+            State::Noraml if !self.warning => {
+                let mut text = self
+                    .keys
+                    .normal
+                    .iter()
+                    .map(|(key, action)| key_label(*key, action.name()))
+                    .collect::<Vec<String>>()
+                    .join("   ");
+                if let Some(row) = self.tab.focus {
+                    match self.tab.model.row_status(row) {
+                        RowStatus::Valid => (),
+                        RowStatus::Unjustified => {
+                            text.push_str(format!("\nRow {} is unjustified", row).as_str())
+                        }
+                        RowStatus::Invalid(reason) => {
+                            text.push_str(format!("\nRow {}: {}", row, reason).as_str())
+                        }
+                    }
+                }
+                text
+            }
+            State::AndState(AndState::EliminateChooseConjunct(assum))
+                if self.info_buffer.is_empty() =>
+            {
+                match self.tab.model.proposition(assum).map(|p| p.borrow()) {
+                    Some(Proposition::And(conjuncts)) => conjuncts
+                        .iter()
+                        .enumerate()
+                        .map(|(i, c)| format!("{}: {}", i + 1, c))
+                        .collect::<Vec<String>>()
+                        .join("    "),
+                    _ => unreachable!(),
+                }
+            }
+            State::LemmaState(LemmaState::CitePremises(rule)) if self.info_buffer.is_empty() => {
+                match rule {
+                    Rule::Lemma(id) => Lemma::get(id).map(|l| l.to_string()),
+                    Rule::Custom(id) => CustomRule::get(id).map(|r| r.to_string()),
+                    _ => None,
+                }
+                .unwrap_or_default()
+            }
+            State::IntroduceChoice => self
+                .keys
+                .introduce
+                .iter()
+                .map(|(key, rule)| key_label(*key, &menu_label(rule)))
+                .collect::<Vec<String>>()
+                .join("    "),
+            State::EliminateChoice => self
+                .keys
+                .eliminate
+                .iter()
+                .map(|(key, rule)| key_label(*key, &menu_label(rule)))
+                .collect::<Vec<String>>()
+                .join("    "),
+            State::Help(_) | State::Report(_, _, _) => {
+                "Up/Down, PageUp/PageDown: scroll   Esc: close".to_string()
+            }
+            State::CommandPalette(_) if self.info_buffer.is_empty() => {
+                "Up/Down: select   Enter: run   Esc: cancel".to_string()
             }
             _ => self.info_buffer.clone(),
         }
     }
 }
+
+/// Reads a proof file: JSON when its name ends in `.json` and the `json` feature is on, the
+/// layout the TUI shows otherwise.
+fn read_proof_file(path: &Path) -> Result<Fitch, String> {
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    #[cfg(feature = "json")]
+    if path.extension().is_some_and(|e| e == "json") {
+        return json::load(&text).map(|document| document.rows);
+    }
+    parser::parse_proof(&text)
+}
+
+/// Writes a proof file the way [`read_proof_file`] reads it.
+fn write_proof_file(
+    path: &Path,
+    model: &Fitch,
+    symbols: SymbolStyle,
+    bars: SymbolStyle,
+) -> Result<(), String> {
+    let text = export(model, Format::Text, symbols, bars);
+    #[cfg(feature = "json")]
+    let text = match path.extension().is_some_and(|e| e == "json") {
+        true => json::save(&json::Document::new(model.clone(), None)),
+        false => text,
+    };
+    fs::write(path, text).map_err(|e| e.to_string())
+}
+
+/// Checks the rule typed in `text` or, when it is empty, every custom rule, one line each.
+fn soundness_report(text: &str) -> Result<Vec<String>, &'static str> {
+    let line = |name: &str, counter: Option<CounterInstance>| match counter {
+        None => format!("{}: sound", name),
+        Some(counter) => format!("{}: unsound, {}", name, counter),
+    };
+    if !text.trim().is_empty() {
+        let (premises, conclusion) = custom::parse_schema(text)?;
+        return Ok(vec![line(
+            text.trim(),
+            custom::counter_instance(&premises, &conclusion),
+        )]);
+    }
+    let rules = CustomRule::all();
+    if rules.is_empty() {
+        return Err("No custom rules are loaded");
+    }
+    Ok(rules
+        .into_iter()
+        .map(|(_, rule)| line(&rule.name, rule.counter_instance()))
+        .collect())
+}
+
+/// The steps converting `prop` to `form`, one per line with the law applied, or the clauses of
+/// its Tseitin CNF after the atoms they introduce.
+fn normal_form_report(
+    prop: &Rc<Proposition>,
+    form: Option<Form>,
+    symbols: SymbolStyle,
+) -> Result<Vec<String>, &'static str> {
+    let styled = |prop: &Rc<Proposition>| Styled(prop, symbols).to_string();
+    let Some(form) = form else {
+        let encoded = normal::tseitin(prop);
+        let clauses = &encoded.clauses;
+        return Ok(encoded
+            .definitions
+            .iter()
+            .map(|(atom, definition)| format!("{} := {}", styled(atom), styled(definition)))
+            .chain(
+                clauses
+                    .clauses
+                    .iter()
+                    .map(|clause| styled(&clauses.clause(clause))),
+            )
+            .collect());
+    };
+    let steps = normal::trace(prop, form)?;
+    let width = steps
+        .iter()
+        .map(|step| styled(&step.result).chars().count())
+        .chain([styled(prop).chars().count()])
+        .max()
+        .unwrap_or(0);
+    let mut res = vec![format!("   {}", styled(prop))];
+    res.extend(steps.iter().map(|step| {
+        let result = styled(&step.result);
+        let padding = width - result.chars().count();
+        format!("=  {}{}   {}", result, " ".repeat(padding), step.law.name())
+    }));
+    if steps.is_empty() {
+        res.push(format!("already in {}", form.name()));
+    }
+    Ok(res)
+}
+
+/// Applies the lemma or custom rule `rule`, see [`Fitch::apply_lemma`].
+fn apply_instance(
+    model: &mut Fitch,
+    rule: Rule,
+    rows: &[usize],
+    conclusion: Option<&Rc<Proposition>>,
+) -> bool {
+    match rule {
+        Rule::Custom(_) => model.apply_custom(rule, rows, conclusion),
+        _ => model.apply_lemma(rule, rows, conclusion),
+    }
+}
+
+/// How the menus name `rule`: its connective, or its name for a custom rule.
+fn menu_label(rule: &Rule) -> String {
+    match rule {
+        Rule::Custom(_) => rule.symbol(),
+        rule => rule.connective().to_string(),
+    }
+}
+
+/// Looks up what `key` is bound to, ignoring Shift since it is already part of the char.
+fn bound<T: Copy>(bindings: &[(Key, T)], key: &KeyEvent) -> Option<T> {
+    let pressed = match key.code {
+        KeyCode::Char(code) => Key {
+            code,
+            ctrl: key.modifiers.contains(KeyModifiers::CONTROL),
+            alt: key.modifiers.contains(KeyModifiers::ALT),
+        },
+        _ => return None,
+    };
+    bindings
+        .iter()
+        .find(|(k, _)| *k == pressed)
+        .map(|(_, v)| *v)
+}
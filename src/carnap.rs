@@ -0,0 +1,306 @@
+use crate::{
+    fitch::{Fitch, Proposition, Rule},
+    parser::canonical_name,
+};
+use std::rc::Rc;
+
+/// Reads a proof written in Carnap's syntax, the one used by the "forall x" textbooks:
+///
+/// ```text
+/// P -> Q     :PR
+///   P        :AS
+///   Q        :->E 1,2
+/// P -> Q     :->I 2-3
+/// ```
+///
+/// Subproofs are indented further than the rows around them and cited as ranges. Rows whose
+/// rule has no counterpart here (`IP`, `MT`, `DS`, ...) are imported without a justification.
+/// Like [`crate::parser::parse_proof`], rows are not required to hold.
+pub fn import(text: &str) -> Result<Fitch, String> {
+    let mut fitch = Fitch::new();
+    let mut base = None;
+    let mut indents: Vec<usize> = Vec::new();
+    for (n, line) in text.lines().enumerate() {
+        let error = |message: String| format!("line {}: {}", n + 1, message);
+        let line = line.replace('\t', "    ");
+        if line.trim().is_empty() {
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+        let base = *base.get_or_insert(indent);
+        let (formula, justification) = line
+            .split_once(':')
+            .ok_or_else(|| error("missing `:` before the justification".to_string()))?;
+        let prop = parse_formula(formula).map_err(error)?;
+        let mut justification = justification.split_whitespace();
+        let rule = justification.next().unwrap_or("");
+        let citations = justification.collect::<String>();
+
+        match rule {
+            "PR" | "P" => {
+                if indent != base || !fitch.add_assumption(&prop) {
+                    return Err(error("premises have to come first".to_string()));
+                }
+            }
+            "AS" | "A" => {
+                while indents.last().is_some_and(|i| *i >= indent) {
+                    indents.pop();
+                    fitch.end_subproof();
+                }
+                if indent <= *indents.last().unwrap_or(&base) {
+                    return Err(error("subproofs have to be indented".to_string()));
+                }
+                indents.push(indent);
+                fitch.add_subproof(&prop);
+            }
+            _ => {
+                while indents.last().is_some_and(|i| *i > indent) {
+                    indents.pop();
+                    fitch.end_subproof();
+                }
+                if indent != *indents.last().unwrap_or(&base) {
+                    return Err(error("indentation matches no open subproof".to_string()));
+                }
+                let citations = parse_citations(&citations).map_err(error)?;
+                match rule_from_name(rule) {
+                    None => fitch.add_unjustified(&prop),
+                    Some(rule) => fitch.add_tentative(&prop, rule, citations),
+                }
+            }
+        }
+    }
+    Ok(fitch)
+}
+
+fn rule_from_name(name: &str) -> Option<Rule> {
+    let rule = match name {
+        "R" => Rule::Reiter,
+        "&I" | "/\\I" | "∧I" => Rule::IntroAnd,
+        "&E" | "/\\E" | "∧E" => Rule::ElimAnd,
+        "vI" | "\\/I" | "∨I" => Rule::IntroOr,
+        "vE" | "\\/E" | "∨E" => Rule::ElimOr,
+        "->I" | ">I" | "→I" => Rule::IntroImpl,
+        "->E" | ">E" | "→E" => Rule::ElimImpl,
+        "<->I" | "<>I" | "↔I" => Rule::IntroIff,
+        "<->E" | "<>E" | "↔E" => Rule::ElimIff,
+        "~I" | "-I" | "¬I" => Rule::IntroNot,
+        // forall x derives the absurdity from a sentence and its negation by eliminating ~
+        "~E" | "-E" | "¬E" | "_|_I" | "⊥I" => Rule::IntroAbs,
+        "X" | "_|_E" | "⊥E" => Rule::ElimAbs,
+        "DNE" => Rule::ElimNot,
+        _ => return None,
+    };
+    Some(rule)
+}
+
+/// Reads `1, 2-4` as rows 0 and 1: a subproof is cited by its first row.
+fn parse_citations(text: &str) -> Result<Vec<usize>, String> {
+    text.split(',')
+        .filter(|c| !c.trim().is_empty())
+        .map(|c| {
+            let first = c.split('-').next().unwrap_or("").trim();
+            match first.parse::<usize>() {
+                Ok(row) if row > 0 => Ok(row - 1),
+                _ => Err(format!("invalid citation `{}`", c.trim())),
+            }
+        })
+        .collect()
+}
+
+#[derive(Clone, PartialEq, Debug)]
+enum Token {
+    Atom(String),
+    Absurdum,
+    Not,
+    And,
+    Or,
+    Implies,
+    Iff,
+    Open,
+    Close,
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    const SYMBOLS: [(&str, Token); 21] = [
+        ("<->", Token::Iff),
+        ("<>", Token::Iff),
+        ("↔", Token::Iff),
+        ("->", Token::Implies),
+        (">", Token::Implies),
+        ("→", Token::Implies),
+        ("/\\", Token::And),
+        ("&", Token::And),
+        ("∧", Token::And),
+        ("\\/", Token::Or),
+        ("v", Token::Or),
+        ("∨", Token::Or),
+        ("~", Token::Not),
+        ("-", Token::Not),
+        ("¬", Token::Not),
+        ("_|_", Token::Absurdum),
+        ("⊥", Token::Absurdum),
+        ("(", Token::Open),
+        ("[", Token::Open),
+        (")", Token::Close),
+        ("]", Token::Close),
+    ];
+    let mut tokens = Vec::new();
+    let mut rest = text.trim_start();
+    while !rest.is_empty() {
+        // An atom is a capital letter, optionally with a numeric subscript: `P`, `P1` or `P₁`
+        if rest.starts_with(|c: char| c.is_ascii_uppercase()) {
+            let end = rest[1..]
+                .find(|c: char| !c.is_ascii_digit() && !('₀'..='₉').contains(&c))
+                .map_or(rest.len(), |end| end + 1);
+            tokens.push(Token::Atom(canonical_name(&rest[..end])));
+            rest = &rest[end..];
+        } else {
+            let (symbol, token) = SYMBOLS
+                .iter()
+                .find(|(symbol, _)| rest.starts_with(symbol))
+                .ok_or_else(|| format!("unexpected `{}`", rest.chars().next().unwrap()))?;
+            rest = &rest[symbol.len()..];
+            tokens.push(token.clone());
+        }
+        rest = rest.trim_start();
+    }
+    Ok(tokens)
+}
+
+/// Parses a formula where the outermost parentheses may be left out. Where they are left out
+/// elsewhere, `~` binds tightest, then `&`, `v`, `->` and `<->`.
+fn parse_formula(text: &str) -> Result<Rc<Proposition>, String> {
+    let tokens = tokenize(text)?;
+    let mut pos = 0;
+    let prop = parse_binary(&tokens, &mut pos, 0)?;
+    match tokens.get(pos) {
+        None => Ok(prop),
+        Some(_) => Err(format!("unexpected input in `{}`", text.trim())),
+    }
+}
+
+fn precedence(token: &Token) -> Option<u8> {
+    match token {
+        Token::Iff => Some(1),
+        Token::Implies => Some(2),
+        Token::Or => Some(3),
+        Token::And => Some(4),
+        _ => None,
+    }
+}
+
+fn parse_binary(tokens: &[Token], pos: &mut usize, min: u8) -> Result<Rc<Proposition>, String> {
+    let mut left = parse_unary(tokens, pos)?;
+    while let Some(op) = tokens.get(*pos) {
+        let prec = match precedence(op) {
+            Some(p) if p >= min => p,
+            _ => break,
+        };
+        *pos += 1;
+        // -> groups to the right, everything else to the left
+        let next = if *op == Token::Implies {
+            prec
+        } else {
+            prec + 1
+        };
+        let right = parse_binary(tokens, pos, next)?;
+        left = match op {
+            Token::Iff => Proposition::new_iff(&left, &right),
+            Token::Implies => Proposition::new_implies(&left, &right),
+            Token::Or => Proposition::new_or(&left, &right),
+            _ => Proposition::new_and(&left, &right),
+        };
+    }
+    Ok(left)
+}
+
+fn parse_unary(tokens: &[Token], pos: &mut usize) -> Result<Rc<Proposition>, String> {
+    let token = tokens.get(*pos).ok_or("incomplete formula")?;
+    *pos += 1;
+    match token {
+        Token::Atom(name) => Ok(Proposition::new_term(name)),
+        Token::Absurdum => Ok(Proposition::new_absurdum()),
+        Token::Not => Ok(Proposition::new_not(&parse_unary(tokens, pos)?)),
+        Token::Open => {
+            let prop = parse_binary(tokens, pos, 0)?;
+            match tokens.get(*pos) {
+                Some(Token::Close) => {
+                    *pos += 1;
+                    Ok(prop)
+                }
+                _ => Err("unbalanced parentheses".to_string()),
+            }
+        }
+        _ => Err("expected a sentence".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{import, parse_formula};
+    use crate::fitch::{Proposition, RowStatus};
+
+    #[test]
+    fn formulas() {
+        let p = Proposition::new_term("P");
+        let q = Proposition::new_term("Q");
+        let r = Proposition::new_term("R");
+        assert_eq!(
+            parse_formula("~P & Q -> R").unwrap(),
+            Proposition::new_implies(&Proposition::new_and(&Proposition::new_not(&p), &q), &r)
+        );
+        assert_eq!(
+            parse_formula("P -> Q -> R").unwrap(),
+            Proposition::new_implies(&p, &Proposition::new_implies(&q, &r))
+        );
+        assert_eq!(
+            parse_formula("[P \\/ _|_] <-> -(Q/\\R)").unwrap(),
+            Proposition::new_iff(
+                &Proposition::new_or(&p, &Proposition::new_absurdum()),
+                &Proposition::new_not(&Proposition::new_and(&q, &r))
+            )
+        );
+        assert!(parse_formula("(P & Q").is_err());
+        assert!(parse_formula("P Q").is_err());
+        assert!(parse_formula("p").is_err());
+    }
+
+    #[test]
+    fn subscripts() {
+        let p1 = Proposition::new_term("P1");
+        let a2 = Proposition::new_term("A2");
+        assert_eq!(
+            parse_formula("P1 & A₂").unwrap(),
+            Proposition::new_and(&p1, &a2)
+        );
+        assert_eq!(parse_formula("P₁").unwrap(), p1);
+        assert!(parse_formula("PQ").is_err());
+        assert!(parse_formula("P1Q").is_err());
+    }
+
+    #[test]
+    fn proofs() {
+        let proof = import(
+            "
+            P v Q     :PR
+            ~P        :PR
+              P       :AS
+              _|_     :~E 2,3
+              Q       :X 4
+              Q       :AS
+              Q       :R 6
+            Q         :vE 1, 3-5, 6-7
+            Q v Q     :MT 8
+            ",
+        )
+        .unwrap();
+        assert_eq!(proof.len(), 9);
+        assert!((0..8).all(|row| proof.row_status(row) == RowStatus::Valid));
+        assert_eq!(proof.row_status(8), RowStatus::Unjustified);
+
+        assert!(import("P :AS\nQ :PR").is_err());
+        assert!(import("P :PR\n  Q :->E 1").is_err());
+        assert!(import("P :PR\nP :R one").is_err());
+        assert!(import("P PR").is_err());
+    }
+}
@@ -0,0 +1,218 @@
+use crate::config::Config;
+#[cfg(feature = "json")]
+use fitch::json;
+use fitch::{
+    carnap,
+    custom::{self, CustomRule},
+    dimacs,
+    export::{export, Format},
+    parser::{self, parse_expression, parse_proof},
+    sat,
+    schema::Sequent,
+    Fitch, Proposition, RowStatus, Styled, SymbolStyle,
+};
+use std::{
+    fs,
+    io::{self, Read},
+    rc::Rc,
+};
+
+const USAGE: &str = "usage: fitch [export <format> <proof> [<output>] \
+    | import <format> <proof> [<output>] | instance <schema> <sequent> | sound [<rule>] \
+    | sat <expression> | valid <sequent> | dimacs encode <expression> [<output>] \
+    | dimacs decode <file>]";
+
+/// Runs a command given on the command line. The proof files read here are laid out the way the
+/// TUI shows proofs; `-` reads from stdin. Exports use the symbols set in the config file.
+pub fn run(args: &[String]) -> Result<(), String> {
+    match args.iter().map(String::as_str).collect::<Vec<&str>>()[..] {
+        #[cfg(feature = "json")]
+        ["export", "json", proof] => export_json(proof, None),
+        #[cfg(feature = "json")]
+        ["export", "json", proof, output] => export_json(proof, Some(output)),
+        ["export", "dimacs", proof] => export_dimacs(proof, None),
+        ["export", "dimacs", proof, output] => export_dimacs(proof, Some(output)),
+        ["export", format, proof] => export_command(format, proof, None),
+        ["export", format, proof, output] => export_command(format, proof, Some(output)),
+        ["import", "carnap", proof] => import_command(proof, None, carnap::import),
+        ["import", "carnap", proof, output] => import_command(proof, Some(output), carnap::import),
+        #[cfg(feature = "json")]
+        ["import", "json", proof] => import_command(proof, None, import_json),
+        #[cfg(feature = "json")]
+        ["import", "json", proof, output] => import_command(proof, Some(output), import_json),
+        ["instance", schema, sequent] => instance_command(schema, sequent),
+        ["sound"] => sound_rules(),
+        ["sound", rule] => sound_command(rule),
+        ["sat", expression] => sat_command(expression),
+        ["valid", sequent] => valid_command(sequent),
+        ["dimacs", "encode", expression] => encode_dimacs(expression, None),
+        ["dimacs", "encode", expression, output] => encode_dimacs(expression, Some(output)),
+        ["dimacs", "decode", file] => decode_dimacs(file),
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+fn read_proof(path: &str) -> Result<String, String> {
+    let mut text = String::new();
+    let res = match path {
+        "-" => io::stdin().read_to_string(&mut text).map(|_| text),
+        _ => fs::read_to_string(path),
+    };
+    res.map_err(|e| format!("{}: {}", path, e))
+}
+
+fn export_command(format: &str, proof: &str, output: Option<&str>) -> Result<(), String> {
+    let format = Format::from_name(format).ok_or_else(|| {
+        let names: Vec<&str> = Format::ALL.iter().map(|f| f.name()).collect();
+        format!(
+            "unknown format `{}`, expected one of: {}",
+            format,
+            names.join(", ")
+        )
+    })?;
+    let fitch = parse_proof(&read_proof(proof)?).map_err(|e| format!("{}: {}", proof, e))?;
+    let config = Config::load()?;
+    write_output(output, export(&fitch, format, config.symbols, config.bars))
+}
+
+#[cfg(feature = "json")]
+fn export_json(proof: &str, output: Option<&str>) -> Result<(), String> {
+    let fitch = parse_proof(&read_proof(proof)?).map_err(|e| format!("{}: {}", proof, e))?;
+    write_output(output, json::save(&json::Document::new(fitch, None)))
+}
+
+/// Writes the premises of the proof and its negated conclusion as a DIMACS CNF, which a solver
+/// finds unsatisfiable exactly when the premises entail the conclusion.
+fn export_dimacs(proof: &str, output: Option<&str>) -> Result<(), String> {
+    let fitch = parse_proof(&read_proof(proof)?).map_err(|e| format!("{}: {}", proof, e))?;
+    let text = dimacs::export_proof(&fitch).map_err(|e| format!("{}: {}", proof, e))?;
+    write_output(output, text)
+}
+
+fn encode_dimacs(expression: &str, output: Option<&str>) -> Result<(), String> {
+    write_output(output, dimacs::export(&parse_formula(expression)?))
+}
+
+/// Prints the formula a DIMACS CNF stands for.
+fn decode_dimacs(file: &str) -> Result<(), String> {
+    let clauses = dimacs::parse(&read_proof(file)?).map_err(|e| format!("{}: {}", file, e))?;
+    let symbols = Config::load()?.symbols;
+    println!("{}", Styled(&clauses.to_proposition(), symbols));
+    Ok(())
+}
+
+#[cfg(feature = "json")]
+fn import_json(text: &str) -> Result<Fitch, String> {
+    json::load(text).map(|document| document.rows)
+}
+
+/// Converts a proof read by `import` to the layout the other commands read, listing the rows
+/// that do not hold on stderr.
+fn import_command(
+    proof: &str,
+    output: Option<&str>,
+    import: fn(&str) -> Result<Fitch, String>,
+) -> Result<(), String> {
+    let fitch = import(&read_proof(proof)?).map_err(|e| format!("{}: {}", proof, e))?;
+    for row in 0..fitch.len() {
+        match fitch.row_status(row) {
+            RowStatus::Valid => (),
+            RowStatus::Invalid(reason) => eprintln!("{}: row {}: {}", proof, row, reason),
+            RowStatus::Unjustified => eprintln!("{}: row {}: unjustified", proof, row),
+        }
+    }
+    let text = export(&fitch, Format::Text, SymbolStyle::Ascii, SymbolStyle::Ascii);
+    write_output(output, text)
+}
+
+/// Checks that `sequent` is an instance of the rule `schema`, e.g. `(φ → ψ), φ ⊢ ψ`, and
+/// prints what each metavariable stands for.
+fn instance_command(schema: &str, sequent: &str) -> Result<(), String> {
+    let schema = Sequent::parse(schema).map_err(|e| format!("schema: {}", e))?;
+    let sequent = Sequent::parse(sequent).map_err(|e| format!("sequent: {}", e))?;
+    let substitution = schema.check_instance(&sequent)?;
+    let symbols = Config::load()?.symbols;
+    let mut metas: Vec<_> = substitution.into_iter().collect();
+    metas.sort_by(|l, r| l.0.cmp(&r.0));
+    for (meta, prop) in metas {
+        let meta = Proposition::new_meta(&meta);
+        println!("{} := {}", Styled(&meta, symbols), Styled(&prop, symbols));
+    }
+    Ok(())
+}
+
+fn parse_formula(expression: &str) -> Result<Rc<Proposition>, String> {
+    match parse_expression(expression) {
+        parser::Result::Success(prop, rest) if rest.trim().is_empty() => Ok(prop),
+        _ => Err("invalid expression".to_string()),
+    }
+}
+
+/// Prints a valuation satisfying `expression`, failing if there is none.
+fn sat_command(expression: &str) -> Result<(), String> {
+    match sat::satisfiable(&parse_formula(expression)?) {
+        None => Err("unsatisfiable".to_string()),
+        Some(model) => {
+            println!("satisfiable: {}", model);
+            Ok(())
+        }
+    }
+}
+
+/// Checks that the premises of `sequent` entail its conclusion, e.g. `(A | B), (~A) ⊢ B`, or
+/// that the conclusion is valid when there are none.
+fn valid_command(sequent: &str) -> Result<(), String> {
+    let sequent = Sequent::parse(sequent)?;
+    match sat::counterexample(&sequent.premises, &sequent.conclusion) {
+        None => {
+            println!("valid");
+            Ok(())
+        }
+        Some(model) => Err(format!("invalid: {}", model)),
+    }
+}
+
+/// Checks that the rule `rule`, written as in the rules file without its kind and name, is
+/// sound.
+fn sound_command(rule: &str) -> Result<(), String> {
+    let (premises, conclusion) = custom::parse_schema(rule)?;
+    match custom::counter_instance(&premises, &conclusion) {
+        None => {
+            println!("sound");
+            Ok(())
+        }
+        Some(counter) => Err(format!("unsound: {}", counter)),
+    }
+}
+
+/// Checks every rule of the rules file, failing if any of them is unsound.
+fn sound_rules() -> Result<(), String> {
+    let rules = CustomRule::all();
+    if rules.is_empty() {
+        return Err("no custom rules are loaded".to_string());
+    }
+    let mut unsound = 0;
+    for (_, rule) in rules {
+        match rule.counter_instance() {
+            None => println!("{}: sound", rule.name),
+            Some(counter) => {
+                println!("{}: unsound: {}", rule.name, counter);
+                unsound += 1;
+            }
+        }
+    }
+    match unsound {
+        0 => Ok(()),
+        n => Err(format!("{} unsound rule(s)", n)),
+    }
+}
+
+fn write_output(output: Option<&str>, text: String) -> Result<(), String> {
+    match output {
+        None => {
+            print!("{}", text);
+            Ok(())
+        }
+        Some(path) => fs::write(path, text).map_err(|e| format!("{}: {}", path, e)),
+    }
+}
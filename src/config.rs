@@ -0,0 +1,312 @@
+use crate::{
+    action::{Action, Key, Keymap, ELIMINATE_RULES, INTRODUCE_RULES},
+    theme::ThemeName,
+};
+use fitch::{
+    custom::{self, CustomRule},
+    lemma::{self, Lemma},
+    Rule, SymbolStyle, RULE_SYSTEM,
+};
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    env,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+};
+
+/// User settings, read from `$XDG_CONFIG_HOME/fitch/config.toml` (`~/.config` when unset):
+///
+/// ```toml
+/// preset = "vim"       # "default", "vim" or "emacs"
+/// symbols = "unicode"  # "ascii" or "unicode"
+/// bars = "unicode"     # the characters bars are drawn with, "ascii" or "unicode"
+/// theme = "light"      # "dark", "light", "high-contrast" or "monochrome"
+/// rules = "fitch"      # the rule system, "fitch" is the only one for now
+///
+/// [keys.normal]
+/// "add subproof" = "C-o"
+///
+/// [keys.introduce]
+/// and = "&"
+/// contradiction = "c"  # a custom rule, by its name
+/// ```
+///
+/// Without a `theme`, `NO_COLOR` selects the monochrome theme.
+pub struct Config {
+    pub keys: Keymap,
+    pub symbols: SymbolStyle,
+    pub bars: SymbolStyle,
+    pub theme: ThemeName,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    preset: Option<String>,
+    symbols: Option<String>,
+    bars: Option<String>,
+    theme: Option<String>,
+    rules: Option<String>,
+    keys: KeysFile,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct KeysFile {
+    normal: BTreeMap<String, String>,
+    introduce: BTreeMap<String, String>,
+    eliminate: BTreeMap<String, String>,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        let mut keys = Keymap::default_preset();
+        keys.bind_custom_rules();
+        Config {
+            keys,
+            symbols: SymbolStyle::Ascii,
+            bars: SymbolStyle::Ascii,
+            theme: ThemeName::from_env(),
+        }
+    }
+}
+
+fn parse_key(text: &str) -> Result<Key, String> {
+    Key::parse(text).ok_or(format!("invalid key `{}`", text))
+}
+
+fn symbol_style(value: Option<&str>) -> Result<SymbolStyle, String> {
+    match value {
+        None | Some("ascii") => Ok(SymbolStyle::Ascii),
+        Some("unicode") => Ok(SymbolStyle::Unicode),
+        Some(other) => Err(format!("unknown symbol style `{}`", other)),
+    }
+}
+
+/// The rule of the menu named by `connective`, or the custom rule of that name going in the
+/// same menu.
+fn menu_rule(rules: &[Rule], connective: &str) -> Result<Rule, String> {
+    rules
+        .iter()
+        .find(|r| r.connective() == connective)
+        .copied()
+        .or_else(|| {
+            CustomRule::find(connective).filter(|r| r.introduces() == rules[0].introduces())
+        })
+        .ok_or(format!("unknown connective `{}`", connective))
+}
+
+impl Config {
+    fn dir() -> Option<PathBuf> {
+        env::var_os("XDG_CONFIG_HOME")
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+            .map(|dir| dir.join("fitch"))
+    }
+
+    pub fn path() -> Option<PathBuf> {
+        Config::dir().map(|dir| dir.join("config.toml"))
+    }
+
+    /// The lemma library, `lemmas.txt` next to the config file. See [`fitch::lemma`] for its
+    /// format.
+    pub fn lemmas_path() -> Option<PathBuf> {
+        Config::dir().map(|dir| dir.join("lemmas.txt"))
+    }
+
+    /// Registers the lemmas of the library, if there is one.
+    pub fn load_lemmas() -> Result<(), String> {
+        match Config::lemmas_path() {
+            Some(path) if path.exists() => fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| lemma::parse_library(&text))
+                .map(|lemmas| {
+                    lemmas.into_iter().for_each(|lemma| {
+                        lemma.register();
+                    })
+                })
+                .map_err(|e| format!("{}: {}", path.display(), e)),
+            _ => Ok(()),
+        }
+    }
+
+    /// The custom rules, `rules.txt` next to the config file. See [`fitch::custom`] for its
+    /// format.
+    pub fn rules_path() -> Option<PathBuf> {
+        Config::dir().map(|dir| dir.join("rules.txt"))
+    }
+
+    /// Registers the custom rules, if there are any.
+    pub fn load_rules() -> Result<(), String> {
+        match Config::rules_path() {
+            Some(path) if path.exists() => fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| custom::parse_rules(&text))
+                .map(|rules| {
+                    rules.into_iter().for_each(|rule| {
+                        rule.register();
+                    })
+                })
+                .map_err(|e| format!("{}: {}", path.display(), e)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Adds `lemma` to the end of the library, creating it if needed.
+    pub fn save_lemma(lemma: &Lemma) -> Result<PathBuf, String> {
+        let path = Config::lemmas_path().ok_or("No config directory to keep lemmas in")?;
+        let write = || {
+            fs::create_dir_all(path.parent().unwrap())?;
+            let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+            writeln!(file, "{}", lemma)
+        };
+        write().map_err(|e| format!("{}: {}", path.display(), e))?;
+        Ok(path)
+    }
+
+    /// Loads the config file, falling back to the defaults when there is none.
+    pub fn load() -> Result<Config, String> {
+        match Config::path() {
+            Some(path) if path.exists() => fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| Config::parse(&text))
+                .map_err(|e| format!("{}: {}", path.display(), e)),
+            _ => Ok(Config::default()),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Config, String> {
+        let file: ConfigFile = toml::from_str(text).map_err(|e| e.message().to_string())?;
+
+        let mut keys = match file.preset {
+            None => Keymap::default_preset(),
+            Some(name) => Keymap::preset(&name).ok_or(format!("unknown preset `{}`", name))?,
+        };
+        keys.bind_custom_rules();
+        let symbols = symbol_style(file.symbols.as_deref())?;
+        let bars = symbol_style(file.bars.as_deref())?;
+        if let Some(rules) = file.rules.filter(|rules| rules != RULE_SYSTEM) {
+            return Err(format!("unknown rule system `{}`", rules));
+        }
+        let theme = match file.theme {
+            None => ThemeName::from_env(),
+            Some(name) => ThemeName::from_name(&name).ok_or(format!("unknown theme `{}`", name))?,
+        };
+
+        for (name, key) in file.keys.normal {
+            let action = Action::from_name(&name).ok_or(format!("unknown action `{}`", name))?;
+            keys.bind_normal(parse_key(&key)?, action);
+        }
+        for (connective, key) in file.keys.introduce {
+            keys.bind_rule(parse_key(&key)?, menu_rule(&INTRODUCE_RULES, &connective)?);
+        }
+        for (connective, key) in file.keys.eliminate {
+            keys.bind_rule(parse_key(&key)?, menu_rule(&ELIMINATE_RULES, &connective)?);
+        }
+
+        Ok(Config {
+            keys,
+            symbols,
+            bars,
+            theme,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Config, CustomRule};
+    use crate::{
+        action::{Action, Key},
+        theme::ThemeName,
+    };
+    use fitch::{custom, Rule, SymbolStyle};
+
+    #[test]
+    fn empty_config() {
+        let config = Config::parse("").unwrap();
+        assert_eq!(config.symbols, SymbolStyle::Ascii);
+        assert!(config
+            .keys
+            .normal
+            .contains(&(Key::plain('i'), Action::Introduce)));
+    }
+
+    #[test]
+    fn preset_and_overrides() {
+        let config = Config::parse(
+            r#"
+            preset = "emacs"
+            symbols = "unicode"
+            theme = "high-contrast"
+            rules = "fitch"
+
+            [keys.normal]
+            "add subproof" = "C-o"
+
+            [keys.eliminate]
+            implies = "M->"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.symbols, SymbolStyle::Unicode);
+        assert_eq!(config.bars, SymbolStyle::Ascii);
+        assert_eq!(config.theme, ThemeName::HighContrast);
+        assert!(config
+            .keys
+            .normal
+            .contains(&(Key::ctrl('o'), Action::AddSubproof)));
+        assert!(!config
+            .keys
+            .normal
+            .contains(&(Key::alt('s'), Action::AddSubproof)));
+        assert!(config
+            .keys
+            .eliminate
+            .contains(&(Key::alt('>'), Rule::ElimImpl)));
+    }
+
+    #[test]
+    fn custom_rules() {
+        let mut rules = custom::parse_rules(
+            "intro contradiction: [φ |- ψ], [φ |- (~ψ)] |- (~φ)\nelim ds: (φ | ψ), (~φ) |- ψ",
+        )
+        .unwrap()
+        .into_iter()
+        .map(CustomRule::register);
+        let (contradiction, ds) = (rules.next().unwrap(), rules.next().unwrap());
+
+        // `c` is free in the default introduce menu, `d` is taken in the eliminate one
+        let config = Config::parse("").unwrap();
+        assert!(config
+            .keys
+            .introduce
+            .contains(&(Key::plain('c'), contradiction)));
+        assert!(config.keys.eliminate.contains(&(Key::plain('s'), ds)));
+
+        let config = Config::parse("[keys.introduce]\ncontradiction = \"!\"").unwrap();
+        assert!(config
+            .keys
+            .introduce
+            .contains(&(Key::plain('!'), contradiction)));
+        assert!(Config::parse("[keys.eliminate]\ncontradiction = \"!\"").is_err());
+    }
+
+    #[test]
+    fn invalid_config() {
+        assert!(Config::parse("preset = \"nano\"").is_err());
+        assert!(Config::parse("symbols = \"latex\"").is_err());
+        assert!(Config::parse("bars = \"thick\"").is_err());
+        assert!(Config::parse("theme = \"solarized\"").is_err());
+        assert_eq!(
+            Config::parse("rules = \"lpl\"").err(),
+            Some("unknown rule system `lpl`".to_string())
+        );
+        assert!(Config::parse("[keys.normal]\nfly = \"f\"").is_err());
+        assert!(Config::parse("[keys.introduce]\nand = \"ab\"").is_err());
+        assert!(Config::parse("colour = 1").is_err());
+    }
+}
@@ -0,0 +1,420 @@
+//! Inference rules defined by the user, e.g. for the rules of a particular course. A
+//! [`CustomRule`] is a schema over metavariables: its premises are formulas or subproofs, and
+//! any instance of it is a valid step. Rules are registered on the thread they are used on and
+//! cited as [`Rule::Custom`].
+//!
+//! Rules are kept in a text file, one per line, each introducing or eliminating something.
+//! A subproof premise is written `[assumption |- result]` and is cited by its first row:
+//!
+//! ```text
+//! # negation introduction by contradiction
+//! intro contradiction: [φ |- ψ], [φ |- (¬ψ)] |- (¬φ)
+//! elim disjunctive-syllogism: (φ | ψ), (~φ) |- ψ
+//! ```
+//!
+//! Atoms in a rule are taken literally, only the metavariables stand for other formulas.
+//! Nothing stops a rule from being unsound; [`counter_instance`] checks it with the SAT solver.
+
+use crate::{
+    fitch::{Proposition, Rule},
+    parser::{self, parse_expression},
+    sat,
+    schema::{
+        matches, metavariables, parse_sequent, strip_turnstile, substitute, valid_name,
+        write_sequent, Substitution,
+    },
+};
+use std::{cell::RefCell, fmt::Display, rc::Rc};
+
+thread_local! {
+    static RULES: RefCell<Vec<Rc<CustomRule>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// A premise of a rule, or what a proof cites for it.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Premise {
+    Row(Rc<Proposition>),
+    /// A subproof from its assumption to its last row.
+    Subproof(Rc<Proposition>, Rc<Proposition>),
+}
+
+#[derive(PartialEq, Debug)]
+pub struct CustomRule {
+    pub name: String,
+    /// Whether the rule is listed among the introduction rules rather than the elimination ones.
+    pub introduces: bool,
+    pub premises: Vec<Premise>,
+    pub conclusion: Rc<Proposition>,
+}
+
+impl CustomRule {
+    /// Makes the rule available to proofs on this thread, returning the rule that cites it.
+    /// Registering the same rule again returns the same rule.
+    pub fn register(self) -> Rule {
+        RULES.with(|rules| {
+            let mut rules = rules.borrow_mut();
+            match rules.iter().position(|r| **r == self) {
+                Some(id) => Rule::Custom(id),
+                None => {
+                    rules.push(Rc::new(self));
+                    Rule::Custom(rules.len() - 1)
+                }
+            }
+        })
+    }
+
+    /// The last rule registered under `name`.
+    pub fn find(name: &str) -> Option<Rule> {
+        RULES.with(|rules| {
+            rules
+                .borrow()
+                .iter()
+                .rposition(|r| r.name == name)
+                .map(Rule::Custom)
+        })
+    }
+
+    /// The rule cited by [`Rule::Custom`]`(id)`, if there is one registered under `id`.
+    pub fn get(id: usize) -> Option<Rc<CustomRule>> {
+        RULES.with(|rules| rules.borrow().get(id).cloned())
+    }
+
+    /// Every registered rule along with the rule citing it, later ones shadowing earlier ones of
+    /// the same name.
+    pub fn all() -> Vec<(Rule, Rc<CustomRule>)> {
+        let rules = RULES.with(|rules| rules.borrow().clone());
+        rules
+            .into_iter()
+            .enumerate()
+            .map(|(id, rule)| (Rule::Custom(id), rule))
+            .filter(|(cited, rule)| CustomRule::find(&rule.name) == Some(*cited))
+            .collect()
+    }
+
+    /// Whether the premises settle the conclusion, that is every metavariable of the conclusion
+    /// appears in some premise.
+    pub fn determines_conclusion(&self) -> bool {
+        let premises: Vec<String> = self
+            .premises
+            .iter()
+            .flat_map(|premise| match premise {
+                Premise::Row(p) => metavariables(p),
+                Premise::Subproof(assumption, result) => {
+                    let mut res = metavariables(assumption);
+                    res.extend(metavariables(result));
+                    res
+                }
+            })
+            .collect();
+        metavariables(&self.conclusion)
+            .iter()
+            .all(|m| premises.contains(m))
+    }
+
+    /// An instance showing the rule is unsound, see [`counter_instance`].
+    pub fn counter_instance(&self) -> Option<CounterInstance> {
+        counter_instance(&self.premises, &self.conclusion)
+    }
+
+    /// Checks that `cited` are instances of the premises, in order, under a single substitution
+    /// and returns the matching instance of the conclusion. `conclusion` is the instance the user
+    /// chose, needed when the premises don't settle it.
+    pub fn instantiate(
+        &self,
+        cited: &[Premise],
+        conclusion: Option<&Rc<Proposition>>,
+    ) -> Result<Rc<Proposition>, &'static str> {
+        if cited.len() != self.premises.len() {
+            return Err("Cite one row per premise of the rule");
+        }
+        let mut substitution = Substitution::new();
+        for (premise, cited) in self.premises.iter().zip(cited) {
+            let matched = match (premise, cited) {
+                (Premise::Row(schema), Premise::Row(row)) => {
+                    matches(schema, row, &mut substitution)
+                }
+                (Premise::Subproof(l0, l1), Premise::Subproof(r0, r1)) => {
+                    matches(l0, r0, &mut substitution) && matches(l1, r1, &mut substitution)
+                }
+                _ => false,
+            };
+            if !matched {
+                return Err("Cited rows are not an instance of the rule's premises");
+            }
+        }
+        match conclusion {
+            Some(prop) if matches(&self.conclusion, prop, &mut substitution) => Ok(prop.clone()),
+            Some(_) => Err("Expression is not an instance of the rule's conclusion"),
+            None => {
+                substitute(&self.conclusion, &substitution).ok_or("Missing expression to introduce")
+            }
+        }
+    }
+}
+
+impl Display for Premise {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Premise::Row(p) => write!(f, "{}", p),
+            Premise::Subproof(assumption, result) => write!(f, "[{} |- {}]", assumption, result),
+        }
+    }
+}
+
+impl Display for CustomRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = if self.introduces { "intro" } else { "elim" };
+        write!(f, "{} {}: ", kind, self.name)?;
+        write_sequent(f, &self.premises, &self.conclusion)
+    }
+}
+
+/// Reads a rules file, one `intro|elim name: premise, ... |- conclusion` per line. `⊢` can be
+/// used in place of `|-`, blank lines and lines starting with `#` are skipped.
+pub fn parse_rules(text: &str) -> Result<Vec<CustomRule>, String> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(n, line)| parse_rule(line).map_err(|e| format!("line {}: {}", n + 1, e)))
+        .collect()
+}
+
+fn parse_formula(text: &str) -> Option<(Rc<Proposition>, &str)> {
+    match parse_expression(text) {
+        parser::Result::Success(prop, rest) => Some((prop, rest)),
+        parser::Result::Failure => None,
+    }
+}
+
+fn parse_premise(text: &str) -> Result<(Premise, &str), &'static str> {
+    let text = text.trim_start();
+    let Some(subproof) = text.strip_prefix('[') else {
+        let (prop, rest) = parse_formula(text).ok_or("invalid premise")?;
+        return Ok((Premise::Row(prop), rest));
+    };
+    let (assumption, rest) = parse_formula(subproof).ok_or("invalid subproof assumption")?;
+    let rest = strip_turnstile(rest).ok_or("missing `|-` inside the subproof")?;
+    let (result, rest) = parse_formula(rest).ok_or("invalid subproof result")?;
+    let rest = rest
+        .trim_start()
+        .strip_prefix(']')
+        .ok_or("missing `]` after the subproof")?;
+    Ok((Premise::Subproof(assumption, result), rest))
+}
+
+fn parse_rule(line: &str) -> Result<CustomRule, &'static str> {
+    let (head, schema) = line.split_once(':').ok_or("missing `:` after the name")?;
+    let (introduces, name) = match head.split_whitespace().collect::<Vec<&str>>()[..] {
+        ["intro", name] => (true, name),
+        ["elim", name] => (false, name),
+        _ => return Err("expected `intro` or `elim` and the rule name"),
+    };
+    if !valid_name(name) {
+        return Err("invalid rule name");
+    }
+    let (premises, conclusion) = parse_schema(schema)?;
+    Ok(CustomRule {
+        name: name.to_string(),
+        introduces,
+        premises,
+        conclusion,
+    })
+}
+
+/// Parses the premises and conclusion of a rule written as in a rules file, without the kind
+/// and the name, e.g. `[φ |- ⊥] |- (¬φ)`.
+pub fn parse_schema(text: &str) -> Result<(Vec<Premise>, Rc<Proposition>), &'static str> {
+    parse_sequent(text, parse_premise)
+}
+
+/// An instance of a rule whose premises hold and whose conclusion does not, showing the rule is
+/// unsound. A subproof premise holds when its assumption implies its result.
+#[derive(PartialEq, Debug)]
+pub struct CounterInstance {
+    /// `⊤` or `⊥` for each metavariable, in order of appearance.
+    pub substitution: Vec<(String, Rc<Proposition>)>,
+    /// The truth values of the atoms of the rule, in order of appearance.
+    pub valuation: Vec<(String, bool)>,
+    pub premises: Vec<Premise>,
+    pub conclusion: Rc<Proposition>,
+}
+
+/// Looks for a counter-instance of the rule `premises ⊢ conclusion`, where each metavariable is
+/// replaced by `⊤` or `⊥` and each atom is true or false. The rule is sound exactly when there
+/// is none, since a formula is valid exactly when its instances over `⊤` and `⊥` are. The
+/// search is a [`sat::counterexample`] query with the metavariables taken as atoms.
+pub fn counter_instance(
+    premises: &[Premise],
+    conclusion: &Rc<Proposition>,
+) -> Option<CounterInstance> {
+    let formulas: Vec<Rc<Proposition>> = premises
+        .iter()
+        .map(|premise| match premise {
+            Premise::Row(p) => p.clone(),
+            Premise::Subproof(assumption, result) => Proposition::new_implies(assumption, result),
+        })
+        .collect();
+    let model = sat::counterexample(&formulas, conclusion)?;
+    let (mut substitution, mut valuation) = (Vec::new(), Vec::new());
+    for (variable, value) in model.0 {
+        match variable.as_ref() {
+            Proposition::Meta(name) => {
+                let prop = match value {
+                    true => Proposition::new_verum(),
+                    false => Proposition::new_absurdum(),
+                };
+                substitution.push((name.clone(), prop));
+            }
+            Proposition::Term(name) => valuation.push((name.clone(), value)),
+            _ => unreachable!(),
+        }
+    }
+
+    let map: Substitution = substitution.iter().cloned().collect();
+    let instance = |p: &Rc<Proposition>| substitute(p, &map).unwrap();
+    Some(CounterInstance {
+        premises: premises
+            .iter()
+            .map(|premise| match premise {
+                Premise::Row(p) => Premise::Row(instance(p)),
+                Premise::Subproof(a, r) => Premise::Subproof(instance(a), instance(r)),
+            })
+            .collect(),
+        conclusion: instance(conclusion),
+        substitution,
+        valuation,
+    })
+}
+
+impl Display for CounterInstance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let substitution: Vec<String> = self
+            .substitution
+            .iter()
+            .map(|(meta, prop)| format!("{} := {}", Proposition::new_meta(meta), prop))
+            .collect();
+        if !substitution.is_empty() {
+            write!(f, "{} gives ", substitution.join(", "))?;
+        }
+        write_sequent(f, &self.premises, &self.conclusion)?;
+        let valuation: Vec<String> = self
+            .valuation
+            .iter()
+            .map(|(atom, value)| format!("{} {}", atom, if *value { "true" } else { "false" }))
+            .collect();
+        if !valuation.is_empty() {
+            write!(f, " with {}", valuation.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{counter_instance, parse_rules, parse_schema, CustomRule, Premise};
+    use crate::{
+        fitch::{Fitch, Proposition, RowStatus, SymbolStyle},
+        parser::{expr, parse_justification},
+    };
+
+    const RULES: &str = "# course rules
+intro contradiction: [φ |- ψ], [φ ⊢ (¬ψ)] ⊢ (¬φ)
+
+elim disjunctive-syllogism: (φ | ψ), (~φ) |- ψ
+elim explosion: # |- ?p
+";
+
+    #[test]
+    fn parse() {
+        let rules = parse_rules(RULES).unwrap();
+        assert_eq!(rules.len(), 3);
+        assert!(rules[0].introduces);
+        assert_eq!(
+            rules[0].premises[0],
+            Premise::Subproof(expr("φ"), expr("ψ"))
+        );
+        assert!(rules[0].determines_conclusion());
+        assert!(!rules[2].determines_conclusion());
+        assert_eq!(
+            rules[1].to_string(),
+            "elim disjunctive-syllogism: (φ | ψ), (~φ) |- ψ"
+        );
+        assert_eq!(rules[2].to_string(), "elim explosion: ⊥ |- ?p");
+        assert_eq!(parse_rules(&rules[0].to_string()).unwrap()[0], rules[0]);
+
+        assert!(parse_rules("contradiction: φ |- φ").is_err());
+        assert!(parse_rules("intro c: [φ |- ψ |- φ").is_err());
+        assert!(parse_rules("intro c: [φ] |- φ").is_err());
+        assert!(parse_rules("elim c: φ ψ |- φ").is_err());
+    }
+
+    #[test]
+    fn instantiate() {
+        let mut rules = parse_rules(RULES)
+            .unwrap()
+            .into_iter()
+            .map(CustomRule::register);
+        let (contradiction, syllogism, explosion) = (
+            rules.next().unwrap(),
+            rules.next().unwrap(),
+            rules.next().unwrap(),
+        );
+        assert_eq!(CustomRule::find("explosion"), Some(explosion));
+
+        let mut proof = Fitch::new();
+        proof.add_assumption(&expr("(A | (B & C))"));
+        proof.add_assumption(&expr("(~A)"));
+        assert!(proof.apply_custom(syllogism, &[0, 1], None));
+        assert_eq!(proof.proposition(2), Some(&expr("(B & C)")));
+        assert!(!proof.apply_custom(syllogism, &[1, 0], None));
+
+        proof.add_subproof(&expr("D"));
+        proof.reiterate(1);
+        proof.end_subproof();
+        proof.add_subproof(&expr("D"));
+        proof.add_unjustified(&expr("(~(~A))"));
+        proof.end_subproof();
+        assert!(proof.apply_custom(contradiction, &[3, 5], None));
+        assert_eq!(proof.proposition(7), Some(&expr("(~D)")));
+        assert!(!proof.apply_custom(contradiction, &[3, 1], None));
+        assert_eq!(
+            proof.layout(SymbolStyle::Ascii)[7].justification,
+            "contradiction [3, 5]"
+        );
+        assert_eq!(
+            parse_justification("contradiction 3, 5"),
+            Some((contradiction, vec![3, 5]))
+        );
+
+        proof.add_tentative(&expr("(A => A)"), explosion, vec![1]);
+        assert!(matches!(proof.row_status(8), RowStatus::Invalid(_)));
+        proof.add_subproof(&expr("⊥"));
+        assert!(!proof.apply_custom(explosion, &[9], None));
+        assert!(proof.apply_custom(explosion, &[9], Some(&expr("(A => A)"))));
+    }
+
+    #[test]
+    fn soundness() {
+        for rule in parse_rules(RULES).unwrap() {
+            assert_eq!(rule.counter_instance(), None, "{}", rule);
+        }
+        let check = |text| {
+            let (premises, conclusion) = parse_schema(text).unwrap();
+            counter_instance(&premises, &conclusion)
+        };
+        assert_eq!(check("[φ |- ψ] |- (φ → ψ)"), None);
+        assert_eq!(check("|- (A | (~A))"), None);
+
+        let affirming = check("(φ → ψ), ψ |- φ").unwrap();
+        assert_eq!(affirming.conclusion, Proposition::new_absurdum());
+        assert_eq!(
+            affirming.to_string(),
+            "φ := ⊥, ψ := ⊤ gives (⊥ => ⊤), ⊤ |- ⊥"
+        );
+        let subproof = check("[φ |- ψ] |- ψ").unwrap();
+        assert_eq!(subproof.to_string(), "φ := ⊥, ψ := ⊥ gives [⊥ |- ⊥] |- ⊥");
+        let atoms = check("(φ | A) |- φ").unwrap();
+        assert_eq!(atoms.to_string(), "φ := ⊥ gives (⊥ | A) |- ⊥ with A true");
+        assert!(check("|- A").is_some());
+    }
+}
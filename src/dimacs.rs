@@ -0,0 +1,286 @@
+//! DIMACS CNF, the input format of most SAT solvers, for cross-checking fitch against them. The
+//! comment header maps each variable to the atom it stands for, and to the subformula it names
+//! when it was introduced by the Tseitin encoding:
+//!
+//! ```text
+//! c (A | (~(A | B)))
+//! c 1 A
+//! c 2 B
+//! c 3 t1 := (A | B)
+//! c 4 t2 := (A | (~(A | B)))
+//! p cnf 4 7
+//! 3 -1 0
+//! ...
+//! ```
+//!
+//! Formulas already in CNF are written clause for clause, others go through [`tseitin`].
+//! [`parse`] reads the header back, naming unmapped variables `x1`, `x2` and so on. So does a
+//! variable given a name that another variable already has.
+
+use crate::{
+    fitch::{Fitch, Proposition},
+    normal::{tseitin, Clauses, Tseitin},
+    parser::{self, parse_expression},
+};
+use std::{collections::HashSet, fmt::Write, rc::Rc};
+
+/// `prop` as a DIMACS CNF, satisfiable exactly when `prop` is.
+pub fn export(prop: &Rc<Proposition>) -> String {
+    let encoded = match Clauses::from_cnf(prop) {
+        Some(clauses) => Tseitin {
+            clauses,
+            definitions: Vec::new(),
+        },
+        None => tseitin(prop),
+    };
+    let mut res = format!("c {}\n", prop);
+    let clauses = &encoded.clauses;
+    for (i, variable) in clauses.variables.iter().enumerate() {
+        let definition = encoded
+            .definitions
+            .iter()
+            .find(|(atom, _)| atom == variable);
+        match definition {
+            Some((_, subformula)) => writeln!(res, "c {} {} := {}", i + 1, variable, subformula),
+            None => writeln!(res, "c {} {}", i + 1, variable),
+        }
+        .unwrap();
+    }
+    writeln!(
+        res,
+        "p cnf {} {}",
+        clauses.variables.len(),
+        clauses.clauses.len()
+    )
+    .unwrap();
+    for clause in &clauses.clauses {
+        clause.iter().for_each(|l| write!(res, "{} ", l).unwrap());
+        res.push_str("0\n");
+    }
+    res
+}
+
+/// The premises of `proof` together with its negated conclusion, the last row, as a DIMACS CNF.
+/// It is unsatisfiable exactly when the premises entail the conclusion.
+pub fn export_proof(proof: &Fitch) -> Result<String, String> {
+    let (level, last) = proof.statements().last().ok_or("The proof is empty")?;
+    if *level != 0 {
+        return Err("The proof ends inside a subproof".to_string());
+    }
+    if proof.len() == proof.start_of_deductions() {
+        return Err("The proof has no conclusion".to_string());
+    }
+    let mut conjuncts: Vec<Rc<Proposition>> = proof.statements()[..proof.start_of_deductions()]
+        .iter()
+        .map(|(_, comp)| comp.unwrap().clone())
+        .collect();
+    conjuncts.push(Proposition::new_not(last.unwrap()));
+    Ok(match &conjuncts[..] {
+        [prop] => export(prop),
+        _ => export(&Proposition::new_conjunction(&conjuncts)),
+    })
+}
+
+/// Reads a DIMACS CNF. Everything after a `%` line is ignored, as SATLIB files end with one.
+pub fn parse(text: &str) -> Result<Clauses, String> {
+    let mut names: Vec<(usize, Rc<Proposition>)> = Vec::new();
+    let mut header: Option<(usize, usize)> = None;
+    let mut clauses: Vec<Vec<i32>> = Vec::new();
+    let mut clause: Vec<i32> = Vec::new();
+    for (n, line) in text.lines().enumerate() {
+        let error = |e: &str| format!("line {}: {}", n + 1, e);
+        let line = line.trim();
+        if line.starts_with('%') {
+            break;
+        }
+        if let Some(comment) = line.strip_prefix('c') {
+            if let Some(name) = parse_name(comment) {
+                names.push(name);
+            }
+            continue;
+        }
+        if let Some(problem) = line.strip_prefix('p') {
+            if header.is_some() {
+                return Err(error("repeated problem line"));
+            }
+            header = match problem.split_whitespace().collect::<Vec<&str>>()[..] {
+                ["cnf", variables, count] => variables.parse().ok().zip(count.parse().ok()),
+                _ => None,
+            };
+            if header.is_none() {
+                return Err(error("expected `p cnf <variables> <clauses>`"));
+            }
+            continue;
+        }
+        let Some((variables, _)) = header else {
+            if line.is_empty() {
+                continue;
+            }
+            return Err(error("clauses have to come after the problem line"));
+        };
+        for token in line.split_whitespace() {
+            let literal: i32 = token
+                .parse()
+                .map_err(|_| error(&format!("invalid literal `{}`", token)))?;
+            if literal.unsigned_abs() as usize > variables {
+                return Err(error(&format!(
+                    "variable {} out of range",
+                    literal.unsigned_abs()
+                )));
+            }
+            match literal {
+                0 => clauses.push(std::mem::take(&mut clause)),
+                _ => clause.push(literal),
+            }
+        }
+    }
+    // The last clause may go without its 0
+    if !clause.is_empty() {
+        clauses.push(clause);
+    }
+
+    let (variables, count) = header.ok_or("missing problem line")?;
+    if clauses.len() != count {
+        return Err(format!(
+            "the problem line declares {} clauses but there are {}",
+            count,
+            clauses.len()
+        ));
+    }
+    // The last name given to a variable counts. A name an earlier variable already has, like a
+    // missing one, falls back to a fresh `xN`, as merging the two would change the clauses.
+    let named: Vec<Option<Rc<Proposition>>> = (1..=variables)
+        .map(|v| {
+            names
+                .iter()
+                .rev()
+                .find(|(n, _)| *n == v)
+                .map(|(_, p)| p.clone())
+        })
+        .collect();
+    let mut taken: HashSet<Rc<Proposition>> = named.iter().flatten().cloned().collect();
+    let mut used: HashSet<Rc<Proposition>> = HashSet::new();
+    let mut res: Vec<Rc<Proposition>> = Vec::with_capacity(variables);
+    for (i, name) in named.into_iter().enumerate() {
+        let variable = match name {
+            Some(p) if used.insert(p.clone()) => p,
+            _ => (i + 1..)
+                .map(|n| Proposition::new_term(&format!("x{}", n)))
+                .find(|p| taken.insert(p.clone()))
+                .unwrap(),
+        };
+        used.insert(variable.clone());
+        res.push(variable);
+    }
+    Ok(Clauses {
+        variables: res,
+        clauses,
+    })
+}
+
+/// Reads a `c <variable> <atom>` comment, ignoring what follows the atom.
+fn parse_name(comment: &str) -> Option<(usize, Rc<Proposition>)> {
+    let mut words = comment.split_whitespace();
+    let variable: usize = words.next()?.parse().ok()?;
+    match parse_expression(words.next()?) {
+        parser::Result::Success(prop, rest)
+            if rest.is_empty()
+                && matches!(prop.as_ref(), Proposition::Term(_) | Proposition::Meta(_)) =>
+        {
+            Some((variable, prop))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{export, export_proof, parse};
+    use crate::{
+        parser::{expr, parse_proof},
+        sat,
+    };
+
+    #[test]
+    fn exporting() {
+        assert_eq!(
+            export(&expr("((A | (~B)) & B)")),
+            "c ((A | (~B)) & B)\nc 1 A\nc 2 B\np cnf 2 2\n1 -2 0\n2 0\n"
+        );
+        let encoded = export(&expr("(A | (~(A | B)))"));
+        assert!(encoded.contains("c 3 t1 := (A | B)\n"));
+        assert!(encoded.contains("p cnf 4 "));
+
+        let proof = parse_proof(
+            " 0 | (A | B)
+ 1 | (~A)
+----------------
+ 2 | B           I & [0]",
+        )
+        .unwrap();
+        let clauses = parse(&export_proof(&proof).unwrap()).unwrap();
+        assert_eq!(sat::solve(&clauses), None);
+        let empty = parse_proof(" 0 | A\n--------").unwrap();
+        assert!(export_proof(&empty).is_err());
+    }
+
+    #[test]
+    fn shared_names() {
+        let clauses = parse("c 1 A\nc 2 A\np cnf 2 2\n1 0\n-2 0").unwrap();
+        assert_eq!(clauses.variables, [expr("A"), expr("x2")]);
+        assert!(sat::solve(&clauses).is_some());
+        assert_eq!(clauses.to_proposition(), expr("(A & (~x2))"));
+
+        let clauses = parse("c 1 x3\nc 2 x4\np cnf 4 0").unwrap();
+        assert_eq!(
+            clauses.variables,
+            [expr("x3"), expr("x4"), expr("x5"), expr("x6")]
+        );
+    }
+
+    #[test]
+    fn parsing() {
+        let clauses = parse(
+            "c from a benchmark
+c 2 Rain
+p cnf 3 3
+ 1 -2 0 3
+ 0
+-3 0
+%
+0",
+        )
+        .unwrap();
+        assert_eq!(clauses.variables, [expr("x1"), expr("Rain"), expr("x3")]);
+        assert_eq!(clauses.clauses, [vec![1, -2], vec![3], vec![-3]]);
+        assert_eq!(
+            clauses.to_proposition(),
+            expr("((x1 | (~Rain)) & x3 & (~x3))")
+        );
+
+        let prop = expr("((A <=> (~B)) ^ (φ & C'))");
+        let read = parse(&export(&prop)).unwrap();
+        assert!(read.variables.contains(&expr("φ")));
+        assert!(read.variables.contains(&expr("C'")));
+        assert_eq!(
+            sat::solve(&read).is_some(),
+            sat::satisfiable(&prop).is_some()
+        );
+
+        assert_eq!(
+            parse("p cnf 1 1\n2 0").unwrap_err(),
+            "line 2: variable 2 out of range"
+        );
+        assert_eq!(
+            parse("p cnf 1 1\na 0").unwrap_err(),
+            "line 2: invalid literal `a`"
+        );
+        assert_eq!(
+            parse("p cnf 1 1\n-2147483648 0").unwrap_err(),
+            "line 2: variable 2147483648 out of range"
+        );
+        assert!(parse("1 0\np cnf 1 1").is_err());
+        assert!(parse("p cnf 1 2\n1 0").is_err());
+        assert!(parse("c 1 A").is_err());
+    }
+}
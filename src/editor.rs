@@ -0,0 +1,232 @@
+/// Single line text input used by the expression box. The cursor counts chars, not bytes, so
+/// symbols such as `⊥` can be typed anywhere in the line.
+pub struct LineEditor {
+    buffer: String,
+    cursor: usize,
+    history: Vec<String>,
+    history_index: Option<usize>,
+    draft: String,
+}
+
+impl LineEditor {
+    pub fn new() -> LineEditor {
+        LineEditor {
+            buffer: String::new(),
+            cursor: 0,
+            history: Vec::new(),
+            history_index: None,
+            draft: String::new(),
+        }
+    }
+
+    pub fn buffer(&self) -> &str {
+        &self.buffer
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Replaces the whole line, leaving the cursor at its end.
+    pub fn set(&mut self, text: &str) {
+        self.buffer = text.to_string();
+        self.end();
+    }
+
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+        self.cursor = 0;
+        self.history_index = None;
+    }
+
+    fn len(&self) -> usize {
+        self.buffer.chars().count()
+    }
+
+    fn byte_index(&self, char_index: usize) -> usize {
+        self.buffer
+            .char_indices()
+            .nth(char_index)
+            .map(|(i, _)| i)
+            .unwrap_or(self.buffer.len())
+    }
+
+    fn is_word(c: char) -> bool {
+        c.is_alphanumeric() || c == '_'
+    }
+
+    pub fn insert(&mut self, c: char) {
+        let at = self.byte_index(self.cursor);
+        self.buffer.insert(at, c);
+        self.cursor += 1;
+    }
+
+    /// Inserts pasted text at the cursor. Line breaks are dropped since the box holds one line.
+    pub fn insert_str(&mut self, text: &str) {
+        text.chars()
+            .filter(|c| *c != '\n' && *c != '\r')
+            .for_each(|c| self.insert(c));
+    }
+
+    pub fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            self.delete();
+        }
+    }
+
+    pub fn delete(&mut self) {
+        if self.cursor < self.len() {
+            let at = self.byte_index(self.cursor);
+            self.buffer.remove(at);
+        }
+    }
+
+    pub fn left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn right(&mut self) {
+        if self.cursor < self.len() {
+            self.cursor += 1;
+        }
+    }
+
+    pub fn home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn end(&mut self) {
+        self.cursor = self.len();
+    }
+
+    fn previous_word_start(&self) -> usize {
+        let chars: Vec<char> = self.buffer.chars().collect();
+        let mut i = self.cursor;
+        while i > 0 && !Self::is_word(chars[i - 1]) {
+            i -= 1;
+        }
+        while i > 0 && Self::is_word(chars[i - 1]) {
+            i -= 1;
+        }
+        i
+    }
+
+    fn next_word_end(&self) -> usize {
+        let chars: Vec<char> = self.buffer.chars().collect();
+        let mut i = self.cursor;
+        while i < chars.len() && !Self::is_word(chars[i]) {
+            i += 1;
+        }
+        while i < chars.len() && Self::is_word(chars[i]) {
+            i += 1;
+        }
+        i
+    }
+
+    pub fn word_left(&mut self) {
+        self.cursor = self.previous_word_start();
+    }
+
+    pub fn word_right(&mut self) {
+        self.cursor = self.next_word_end();
+    }
+
+    pub fn delete_word(&mut self) {
+        let start = self.previous_word_start();
+        let range = self.byte_index(start)..self.byte_index(self.cursor);
+        self.buffer.replace_range(range, "");
+        self.cursor = start;
+    }
+
+    /// Records the current line so it can be recalled with [`LineEditor::history_up`].
+    pub fn remember(&mut self) {
+        if !self.buffer.trim().is_empty() && self.history.last() != Some(&self.buffer) {
+            self.history.push(self.buffer.clone());
+        }
+        self.history_index = None;
+    }
+
+    pub fn history_up(&mut self) {
+        let index = match self.history_index {
+            None if self.history.is_empty() => return,
+            None => {
+                self.draft = self.buffer.clone();
+                self.history.len() - 1
+            }
+            Some(i) => i.saturating_sub(1),
+        };
+        self.history_index = Some(index);
+        self.buffer = self.history[index].clone();
+        self.end();
+    }
+
+    pub fn history_down(&mut self) {
+        match self.history_index {
+            None => return,
+            Some(i) if i + 1 < self.history.len() => {
+                self.history_index = Some(i + 1);
+                self.buffer = self.history[i + 1].clone();
+            }
+            Some(_) => {
+                self.history_index = None;
+                self.buffer = std::mem::take(&mut self.draft);
+            }
+        }
+        self.end();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LineEditor;
+
+    #[test]
+    fn non_ascii_editing() {
+        let mut editor = LineEditor::new();
+        editor.insert_str("(A & ⊥)");
+        editor.left();
+        editor.backspace();
+        editor.insert('B');
+        assert_eq!(editor.buffer(), "(A & B)");
+        assert_eq!(editor.cursor(), 6);
+        editor.home();
+        editor.delete();
+        assert_eq!(editor.buffer(), "A & B)");
+    }
+
+    #[test]
+    fn word_motions() {
+        let mut editor = LineEditor::new();
+        editor.insert_str("(AB => CD)");
+        editor.word_left();
+        assert_eq!(editor.cursor(), 7);
+        editor.word_left();
+        assert_eq!(editor.cursor(), 1);
+        editor.word_right();
+        assert_eq!(editor.cursor(), 3);
+        editor.end();
+        editor.delete_word();
+        assert_eq!(editor.buffer(), "(AB => ");
+    }
+
+    #[test]
+    fn history() {
+        let mut editor = LineEditor::new();
+        editor.insert_str("A");
+        editor.remember();
+        editor.clear();
+        editor.insert_str("B");
+        editor.remember();
+        editor.clear();
+        editor.insert_str("C");
+        editor.history_up();
+        assert_eq!(editor.buffer(), "B");
+        editor.history_up();
+        editor.history_up();
+        assert_eq!(editor.buffer(), "A");
+        editor.history_down();
+        editor.history_down();
+        assert_eq!(editor.buffer(), "C");
+    }
+}
@@ -0,0 +1,704 @@
+use crate::fitch::{numeric_suffix, Fitch, FitchComponent, Proposition, Rule, SymbolStyle};
+use std::rc::Rc;
+
+/// Formats a proof can be exported to. The LaTeX ones number rows from 1, the way the packages
+/// do, while the others follow the layout of the TUI.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Format {
+    /// `fitch.sty` by Johan Klüwer.
+    FitchSty,
+    Lplfitch,
+    Logicproof,
+    Html,
+    Svg,
+    Text,
+    /// The plain text in a code block.
+    Markdown,
+}
+
+impl Format {
+    pub const ALL: [Format; 7] = [
+        Format::FitchSty,
+        Format::Lplfitch,
+        Format::Logicproof,
+        Format::Html,
+        Format::Svg,
+        Format::Text,
+        Format::Markdown,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Format::FitchSty => "fitch.sty",
+            Format::Lplfitch => "lplfitch",
+            Format::Logicproof => "logicproof",
+            Format::Html => "html",
+            Format::Svg => "svg",
+            Format::Text => "text",
+            Format::Markdown => "markdown",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Format> {
+        Format::ALL.into_iter().find(|f| f.name() == name)
+    }
+}
+
+/// Exports `fitch`, writing expressions with `symbols` where the format doesn't have its own and
+/// drawing the bars of the text formats with `bars`.
+pub fn export(fitch: &Fitch, format: Format, symbols: SymbolStyle, bars: SymbolStyle) -> String {
+    match format {
+        Format::FitchSty => fitch_sty(fitch),
+        Format::Lplfitch => lplfitch(fitch),
+        Format::Logicproof => logicproof(fitch),
+        Format::Html => html(fitch, symbols),
+        Format::Svg => svg(fitch, symbols),
+        Format::Text => text(fitch, symbols, bars),
+        Format::Markdown => format!("```\n{}```\n", text(fitch, symbols, bars)),
+    }
+}
+
+/// The proof as the TUI shows it, minus the cursor and trailing whitespace.
+fn text(fitch: &Fitch, symbols: SymbolStyle, bars: SymbolStyle) -> String {
+    let mut lines = fitch.lines(symbols, bars);
+    lines.pop();
+    lines
+        .iter()
+        .map(|(_, line)| format!("{}\n", line.trim_end()))
+        .collect()
+}
+
+/// A proof as a flat sequence of rows and subproof boundaries.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Item {
+    Open,
+    Close,
+    Row(usize),
+}
+
+fn structure(fitch: &Fitch) -> Vec<Item> {
+    let mut items = Vec::new();
+    let mut depth = 0;
+    for (row, (level, _)) in fitch.statements().iter().enumerate() {
+        let opens = fitch.opens_subproof(row);
+        let target = if opens { level - 1 } else { *level };
+        while depth > target {
+            items.push(Item::Close);
+            depth -= 1;
+        }
+        if opens {
+            items.push(Item::Open);
+            depth += 1;
+        }
+        items.push(Item::Row(row));
+    }
+    (0..depth).for_each(|_| items.push(Item::Close));
+    items
+}
+
+fn latex_nary(operands: &[Rc<Proposition>], symbol: &str) -> String {
+    let operands: Vec<String> = operands.iter().map(|p| latex(p)).collect();
+    format!("({})", operands.join(&format!(" {} ", symbol)))
+}
+
+fn latex(prop: &Proposition) -> String {
+    match prop {
+        Proposition::Absurdum => "\\bot".to_string(),
+        Proposition::Term(name) => match numeric_suffix(name) {
+            (stem, "") => stem.replace('_', "\\_"),
+            (stem, digits) => format!("{}_{{{}}}", stem.replace('_', "\\_"), digits),
+        },
+        Proposition::And(conjuncts) => latex_nary(conjuncts, "\\land"),
+        Proposition::Or(disjuncts) => latex_nary(disjuncts, "\\lor"),
+        Proposition::Not(prop) => format!("(\\neg {})", latex(prop)),
+        Proposition::Implies(left, right) => format!("({} \\to {})", latex(left), latex(right)),
+        Proposition::Iff(left, right) => {
+            format!("({} \\leftrightarrow {})", latex(left), latex(right))
+        }
+        Proposition::Verum => "\\top".to_string(),
+        Proposition::Xor(left, right) => format!("({} \\oplus {})", latex(left), latex(right)),
+        Proposition::Nand(left, right) => {
+            format!("({} \\uparrow {})", latex(left), latex(right))
+        }
+        Proposition::Nor(left, right) => {
+            format!("({} \\downarrow {})", latex(left), latex(right))
+        }
+        Proposition::Meta(name) => {
+            let (stem, digits) = numeric_suffix(name);
+            let stem = match greek_macro(stem) {
+                Some(letter) => format!("\\{}", letter),
+                None if stem.starts_with(|c: char| c.is_ascii()) => {
+                    format!("?{}", stem.replace('_', "\\_"))
+                }
+                None => stem.replace('_', "\\_"),
+            };
+            match digits {
+                "" => stem,
+                digits => format!("{}_{{{}}}", stem, digits),
+            }
+        }
+    }
+}
+
+/// The LaTeX macro for a lowercase Greek letter, e.g. `varphi` for `φ`.
+fn greek_macro(letter: &str) -> Option<&'static str> {
+    const LETTERS: [(&str, &str); 23] = [
+        ("α", "alpha"),
+        ("β", "beta"),
+        ("γ", "gamma"),
+        ("δ", "delta"),
+        ("ε", "varepsilon"),
+        ("ζ", "zeta"),
+        ("η", "eta"),
+        ("θ", "theta"),
+        ("ι", "iota"),
+        ("κ", "kappa"),
+        ("λ", "lambda"),
+        ("μ", "mu"),
+        ("ν", "nu"),
+        ("ξ", "xi"),
+        ("π", "pi"),
+        ("ρ", "rho"),
+        ("σ", "sigma"),
+        ("τ", "tau"),
+        ("υ", "upsilon"),
+        ("φ", "varphi"),
+        ("χ", "chi"),
+        ("ψ", "psi"),
+        ("ω", "omega"),
+    ];
+    LETTERS
+        .iter()
+        .find(|(l, _)| *l == letter)
+        .map(|(_, name)| *name)
+}
+
+/// The rule as written in the margin, e.g. `$\land$I`.
+fn rule_name(rule: Rule) -> String {
+    let connective = match rule {
+        Rule::Reiter => return "R".to_string(),
+        Rule::Lemma(_) | Rule::Custom(_) => return rule.symbol().replace('_', "\\_"),
+        Rule::IntroAbs | Rule::ElimAbs => "\\bot",
+        Rule::IntroAnd | Rule::ElimAnd => "\\land",
+        Rule::IntroOr | Rule::ElimOr => "\\lor",
+        Rule::IntroNot | Rule::ElimNot => "\\neg",
+        Rule::IntroImpl | Rule::ElimImpl => "\\to",
+        Rule::IntroIff | Rule::ElimIff => "\\leftrightarrow",
+        Rule::IntroVerum => "\\top",
+        Rule::IntroXor | Rule::ElimXor => "\\oplus",
+        Rule::IntroNand | Rule::ElimNand => "\\uparrow",
+        Rule::IntroNor | Rule::ElimNor => "\\downarrow",
+    };
+    let side = if rule.name().starts_with("introduce") {
+        "I"
+    } else {
+        "E"
+    };
+    format!("${}${}", connective, side)
+}
+
+/// The lplfitch macro for `rule`, or its generic `\lrule` for the connectives lplfitch lacks.
+fn lplfitch_rule(rule: Rule) -> String {
+    let name = match rule {
+        Rule::Reiter => "\\lit",
+        Rule::IntroAbs => "\\lfalsei",
+        Rule::ElimAbs => "\\lfalsee",
+        Rule::IntroAnd => "\\landi",
+        Rule::ElimAnd => "\\lande",
+        Rule::IntroOr => "\\lori",
+        Rule::ElimOr => "\\lore",
+        Rule::IntroNot => "\\lnoti",
+        Rule::ElimNot => "\\lnote",
+        Rule::IntroImpl => "\\lcondi",
+        Rule::ElimImpl => "\\lconde",
+        Rule::IntroIff => "\\lbicondi",
+        Rule::ElimIff => "\\lbiconde",
+        _ => return format!("\\lrule{{{}}}", rule_name(rule)),
+    };
+    name.to_string()
+}
+
+/// The rows cited by `row`, with the subproofs it closes cited as ranges, e.g. `1, 3--5`.
+fn citations(fitch: &Fitch, row: usize, cited: &[usize]) -> String {
+    cited
+        .iter()
+        .map(|c| {
+            let end = fitch.subproof_end(*c);
+            if end != *c && row > end {
+                format!("{}--{}", c + 1, end + 1)
+            } else {
+                (c + 1).to_string()
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+fn fitch_sty(fitch: &Fitch) -> String {
+    let mut res = String::from("$\n\\begin{nd}\n");
+    let mut depth = 1;
+    for item in structure(fitch) {
+        let indent = "  ".repeat(depth);
+        match item {
+            Item::Open => {
+                res.push_str(&format!("{}\\open\n", indent));
+                depth += 1;
+            }
+            Item::Close => {
+                depth -= 1;
+                res.push_str(&format!("{}\\close\n", "  ".repeat(depth)));
+            }
+            Item::Row(row) => {
+                let (_, comp) = &fitch.statements()[row];
+                let prop = latex(comp.unwrap());
+                let line = match comp {
+                    FitchComponent::Assumption(_) => format!("\\hypo {{{}}} {{{}}}", row + 1, prop),
+                    FitchComponent::Unjustified(_) => {
+                        format!("\\have {{{}}} {{{}}}", row + 1, prop)
+                    }
+                    FitchComponent::Deduction(_, rule, cited) => format!(
+                        "\\have {{{}}} {{{}}} \\by{{{}}}{{{}}}",
+                        row + 1,
+                        prop,
+                        rule_name(*rule),
+                        citations(fitch, row, cited)
+                    ),
+                };
+                res.push_str(&format!("{}{}\n", indent, line));
+            }
+        }
+    }
+    res.push_str("\\end{nd}\n$\n");
+    res
+}
+
+fn lplfitch(fitch: &Fitch) -> String {
+    let mut res = String::from("\\fitchprf{");
+    let mut in_premises = true;
+    let mut separate = false;
+    let mut open_pending = false;
+    for item in structure(fitch) {
+        let premise = matches!(item, Item::Row(row) if row < fitch.start_of_deductions());
+        if in_premises && !premise {
+            res.push_str("}\n{\n");
+            in_premises = false;
+            separate = false;
+        }
+        if separate && item != Item::Close {
+            res.push_str("\\\\\n");
+        }
+        match item {
+            Item::Open => {
+                res.push_str("\\subproof{");
+                open_pending = true;
+                separate = false;
+            }
+            Item::Close => {
+                res.push_str("\n}");
+                separate = true;
+            }
+            Item::Row(row) => {
+                let (_, comp) = &fitch.statements()[row];
+                res.push_str(&format!(
+                    "\\pline[{}.]{{{}}}",
+                    row + 1,
+                    latex(comp.unwrap())
+                ));
+                if let FitchComponent::Deduction(_, rule, cited) = comp {
+                    res.push_str(&format!(
+                        "[{}{{{}}}]",
+                        lplfitch_rule(*rule),
+                        citations(fitch, row, cited)
+                    ));
+                }
+                separate = true;
+                if open_pending {
+                    res.push_str("}{\n");
+                    open_pending = false;
+                    separate = false;
+                }
+            }
+        }
+    }
+    if in_premises {
+        res.push_str("}\n{");
+    }
+    res.push_str("\n}\n");
+    res
+}
+
+fn logicproof(fitch: &Fitch) -> String {
+    let depth = fitch
+        .statements()
+        .iter()
+        .map(|(level, _)| *level)
+        .max()
+        .unwrap_or(0);
+    let mut res = format!("\\begin{{logicproof}}{{{}}}\n", depth);
+    let mut separate = false;
+    for item in structure(fitch) {
+        if separate && item != Item::Close {
+            res.push_str("\\\\\n");
+        } else if separate {
+            res.push('\n');
+        }
+        match item {
+            Item::Open => {
+                res.push_str("\\begin{subproof}\n");
+                separate = false;
+            }
+            Item::Close => {
+                res.push_str("\\end{subproof}\n");
+                separate = false;
+            }
+            Item::Row(row) => {
+                let (_, comp) = &fitch.statements()[row];
+                let justification = match comp {
+                    FitchComponent::Assumption(_) if row < fitch.start_of_deductions() => {
+                        "premise".to_string()
+                    }
+                    FitchComponent::Assumption(_) => "assumption".to_string(),
+                    FitchComponent::Unjustified(_) => String::new(),
+                    FitchComponent::Deduction(_, rule, cited) => {
+                        format!("{} {}", rule_name(*rule), citations(fitch, row, cited))
+                    }
+                };
+                res.push_str(&format!("{} & {}", latex(comp.unwrap()), justification));
+                separate = true;
+            }
+        }
+    }
+    if separate {
+        res.push('\n');
+    }
+    res.push_str("\\end{logicproof}\n");
+    res
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+const HTML_STYLE: &str = "
+.fitch { display: inline-block; font-family: monospace; font-size: 1.1em; }
+.row { display: flex; align-items: stretch; line-height: 1.6em; }
+.number { min-width: 2.5em; text-align: right; padding-right: 0.6em; color: #777; }
+.bar { width: 1.2em; border-left: 2px solid; flex-shrink: 0; }
+.bar.first { margin-top: 0.3em; }
+.formula { flex: 1; padding-right: 3em; white-space: pre; }
+.underlined { border-bottom: 2px solid; padding-right: 0.4em; }
+.rule { white-space: pre; }
+";
+
+/// A standalone page with the bars drawn by CSS: one per level on every row, the innermost one
+/// starting a bit lower on the first row of a subproof so that adjacent subproofs stay apart.
+fn html(fitch: &Fitch, symbols: SymbolStyle) -> String {
+    let mut res = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Proof</title>\n\
+         <style>{}</style>\n</head>\n<body>\n<div class=\"fitch\">\n",
+        HTML_STYLE
+    );
+    for (i, row) in fitch.layout(symbols).into_iter().enumerate() {
+        res.push_str(&format!(
+            "<div class=\"row\"><span class=\"number\">{}</span>",
+            i
+        ));
+        for level in 0..=row.level {
+            let first = row.opens_subproof && level == row.level;
+            res.push_str(if first {
+                "<span class=\"bar first\"></span>"
+            } else {
+                "<span class=\"bar\"></span>"
+            });
+        }
+        let expression = escape_xml(&row.expression);
+        let expression = if row.underlined {
+            format!("<span class=\"underlined\">{}</span>", expression)
+        } else {
+            expression
+        };
+        res.push_str(&format!(
+            "<span class=\"formula\">{}</span><span class=\"rule\">{}</span></div>\n",
+            expression,
+            escape_xml(&row.justification)
+        ));
+    }
+    res.push_str("</div>\n</body>\n</html>\n");
+    res
+}
+
+const SVG_FONT_SIZE: usize = 14;
+const SVG_CHAR_WIDTH: f32 = 8.4;
+const SVG_LINE_HEIGHT: usize = 22;
+const SVG_BAR_GAP: f32 = 16.0;
+
+/// An image of the proof in a monospace font, bars and hypothesis lines drawn as lines.
+fn svg(fitch: &Fitch, symbols: SymbolStyle) -> String {
+    let layout = fitch.layout(symbols);
+    let digits = layout.len().saturating_sub(1).to_string().len();
+    let first_bar = (digits + 1) as f32 * SVG_CHAR_WIDTH;
+    let formula_x = |level: usize| first_bar + level as f32 * SVG_BAR_GAP + 6.0;
+    let text_width = |text: &str| text.chars().count() as f32 * SVG_CHAR_WIDTH;
+    let rule_x = layout
+        .iter()
+        .map(|row| formula_x(row.level) + text_width(&row.expression))
+        .fold(0.0, f32::max)
+        + 3.0 * SVG_CHAR_WIDTH;
+    let width = layout
+        .iter()
+        .map(|row| rule_x + text_width(&row.justification))
+        .fold(rule_x, f32::max)
+        + SVG_CHAR_WIDTH;
+    let height = (layout.len() * SVG_LINE_HEIGHT + SVG_LINE_HEIGHT / 2).max(SVG_LINE_HEIGHT);
+
+    let mut res = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{:.0}\" height=\"{}\" \
+         font-family=\"monospace\" font-size=\"{}\">\n\
+         <rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n\
+         <g stroke=\"black\" stroke-width=\"1.5\">\n",
+        width, height, SVG_FONT_SIZE
+    );
+    for (i, row) in layout.iter().enumerate() {
+        let top = (i * SVG_LINE_HEIGHT) as f32 + 4.0;
+        let bottom = top + SVG_LINE_HEIGHT as f32;
+        for level in 0..=row.level {
+            let x = first_bar + level as f32 * SVG_BAR_GAP;
+            let y1 = if row.opens_subproof && level == row.level {
+                top + 4.0
+            } else {
+                top
+            };
+            res.push_str(&format!(
+                "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\"/>\n",
+                x, y1, x, bottom
+            ));
+        }
+        if row.underlined {
+            let x = first_bar + row.level as f32 * SVG_BAR_GAP;
+            res.push_str(&format!(
+                "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\"/>\n",
+                x,
+                bottom - 2.0,
+                formula_x(row.level) + text_width(&row.expression) + 4.0,
+                bottom - 2.0
+            ));
+        }
+    }
+    res.push_str("</g>\n");
+    for (i, row) in layout.iter().enumerate() {
+        let baseline = (i * SVG_LINE_HEIGHT) as f32 + 4.0 + SVG_LINE_HEIGHT as f32 * 0.7;
+        res.push_str(&format!(
+            "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\" fill=\"#777\">{}</text>\n",
+            first_bar - SVG_CHAR_WIDTH / 2.0,
+            baseline,
+            i
+        ));
+        res.push_str(&format!(
+            "<text x=\"{:.1}\" y=\"{:.1}\" xml:space=\"preserve\">{}</text>\n",
+            formula_x(row.level),
+            baseline,
+            escape_xml(&row.expression)
+        ));
+        if !row.justification.is_empty() {
+            res.push_str(&format!(
+                "<text x=\"{:.1}\" y=\"{:.1}\">{}</text>\n",
+                rule_x,
+                baseline,
+                escape_xml(&row.justification)
+            ));
+        }
+    }
+    res.push_str("</svg>\n");
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{export, Format};
+    use crate::{
+        fitch::{Fitch, Proposition, SymbolStyle},
+        parser::parse_proof,
+    };
+
+    fn proof() -> Fitch {
+        let a = Proposition::new_term("A");
+        let b = Proposition::new_term("B");
+        let mut fitch = Fitch::new();
+        fitch.add_assumption(&a);
+        fitch.add_subproof(&b);
+        fitch.introduce_and(&[0, 1]);
+        fitch.end_subproof();
+        fitch.introduce_implies(1);
+        fitch
+    }
+
+    #[test]
+    fn fitch_sty() {
+        assert_eq!(
+            export(
+                &proof(),
+                Format::FitchSty,
+                SymbolStyle::Ascii,
+                SymbolStyle::Ascii
+            ),
+            r"$
+\begin{nd}
+  \hypo {1} {A}
+  \open
+    \hypo {2} {B}
+    \have {3} {(A \land B)} \by{$\land$I}{1, 2}
+  \close
+  \have {4} {(B \to (A \land B))} \by{$\to$I}{2--3}
+\end{nd}
+$
+"
+        );
+    }
+
+    #[test]
+    fn lplfitch() {
+        assert_eq!(
+            export(
+                &proof(),
+                Format::Lplfitch,
+                SymbolStyle::Ascii,
+                SymbolStyle::Ascii
+            ),
+            r"\fitchprf{\pline[1.]{A}}
+{
+\subproof{\pline[2.]{B}}{
+\pline[3.]{(A \land B)}[\landi{1, 2}]
+}\\
+\pline[4.]{(B \to (A \land B))}[\lcondi{2--3}]
+}
+"
+        );
+        assert_eq!(
+            export(
+                &Fitch::new(),
+                Format::Lplfitch,
+                SymbolStyle::Ascii,
+                SymbolStyle::Ascii
+            ),
+            "\\fitchprf{}\n{\n}\n"
+        );
+    }
+
+    #[test]
+    fn logicproof() {
+        assert_eq!(
+            export(
+                &proof(),
+                Format::Logicproof,
+                SymbolStyle::Ascii,
+                SymbolStyle::Ascii
+            ),
+            r"\begin{logicproof}{1}
+A & premise\\
+\begin{subproof}
+B & assumption\\
+(A \land B) & $\land$I 1, 2
+\end{subproof}
+(B \to (A \land B)) & $\to$I 2--3
+\end{logicproof}
+"
+        );
+    }
+
+    #[test]
+    fn atom_names() {
+        let mut fitch = Fitch::new();
+        fitch.add_assumption(&Proposition::new_and(
+            &Proposition::new_term("p12"),
+            &Proposition::new_term("big_dog'"),
+        ));
+        let latex = export(
+            &fitch,
+            Format::Logicproof,
+            SymbolStyle::Ascii,
+            SymbolStyle::Ascii,
+        );
+        assert!(latex.contains(r"(p_{12} \land big\_dog')"));
+
+        fitch.add_assumption(&Proposition::new_implies(
+            &Proposition::new_meta("φ2"),
+            &Proposition::new_meta("q"),
+        ));
+        let latex = export(
+            &fitch,
+            Format::Logicproof,
+            SymbolStyle::Ascii,
+            SymbolStyle::Ascii,
+        );
+        assert!(latex.contains(r"(\varphi_{2} \to ?q)"));
+        let text = export(
+            &fitch,
+            Format::Text,
+            SymbolStyle::Unicode,
+            SymbolStyle::Ascii,
+        );
+        assert!(text.contains("(p₁₂ ∧ big_dog')"));
+    }
+
+    #[test]
+    fn html() {
+        let html = export(
+            &proof(),
+            Format::Html,
+            SymbolStyle::Ascii,
+            SymbolStyle::Ascii,
+        );
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert_eq!(html.matches("class=\"row\"").count(), 4);
+        assert_eq!(html.matches("<span class=\"bar").count(), 6);
+        assert_eq!(html.matches("class=\"bar first\"").count(), 1);
+        assert_eq!(html.matches("class=\"underlined\"").count(), 2);
+        assert!(html.contains("(A &amp; B)</span><span class=\"rule\">I &amp; [0, 1]</span>"));
+    }
+
+    #[test]
+    fn svg() {
+        let svg = export(
+            &proof(),
+            Format::Svg,
+            SymbolStyle::Unicode,
+            SymbolStyle::Ascii,
+        );
+        assert!(svg.starts_with("<svg "));
+        // One bar per level on each row, and the lines under row 0 and 1
+        assert_eq!(svg.matches("<line ").count(), 8);
+        assert!(svg.contains(">(B → (A ∧ B))</text>"));
+        assert!(svg.contains(">I → [1]</text>"));
+    }
+
+    #[test]
+    fn text() {
+        let text = export(
+            &proof(),
+            Format::Markdown,
+            SymbolStyle::Unicode,
+            SymbolStyle::Unicode,
+        );
+        assert_eq!(
+            text,
+            "```
+ 0 │ A
+──────────────────
+ 1 │ ─   B
+ 2 │     (A ∧ B)      I ∧ [0, 1]
+ 3 │ (B → (A ∧ B))    I → [1]
+```
+"
+        );
+        let plain = export(
+            &proof(),
+            Format::Text,
+            SymbolStyle::Ascii,
+            SymbolStyle::Ascii,
+        );
+        assert!(parse_proof(&plain).unwrap() == proof());
+        assert!(parse_proof(&text).unwrap() == proof());
+    }
+}
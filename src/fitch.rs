@@ -1,75 +1,425 @@
-use std::{borrow::Borrow, fmt::Display, rc::Rc};
-
+use crate::{
+    custom::{CustomRule, Premise},
+    lemma::Lemma,
+};
+use std::{
+    borrow::Borrow,
+    cell::RefCell,
+    collections::HashSet,
+    fmt::Display,
+    hash::{Hash, Hasher},
+    mem,
+    ops::Range,
+    rc::Rc,
+};
+
+/// A formula of propositional logic, built with the `new_*` constructors and passed around as
+/// `Rc<Proposition>`. The constructors hash-cons: equal formulas built on the same thread are
+/// the same `Rc`, which makes comparing and hashing them take constant time. Building the
+/// variants by hand breaks this.
+///
+/// Conjunctions and disjunctions are flat and take two or more operands, so `(A & B & C)` is a
+/// single conjunction while `((A & B) & C)` nests one inside another.
+///
+/// A `Meta` is a metavariable of a rule schema, standing for any formula, see
+/// [`crate::schema`]. It is named without the `?` it is written with in ASCII.
 #[derive(Debug)]
 pub enum Proposition {
     Absurdum,
     Term(String),
-    And(Rc<Proposition>, Rc<Proposition>),
-    Or(Rc<Proposition>, Rc<Proposition>),
+    And(Vec<Rc<Proposition>>),
+    Or(Vec<Rc<Proposition>>),
     Not(Rc<Proposition>),
     Implies(Rc<Proposition>, Rc<Proposition>),
     Iff(Rc<Proposition>, Rc<Proposition>),
+    Verum,
+    Xor(Rc<Proposition>, Rc<Proposition>),
+    Nand(Rc<Proposition>, Rc<Proposition>),
+    Nor(Rc<Proposition>, Rc<Proposition>),
+    Meta(String),
 }
 
-impl Display for Proposition {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+/// How connectives are written when showing expressions and rules. Both are accepted as input.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum SymbolStyle {
+    #[default]
+    Ascii,
+    Unicode,
+}
+
+impl SymbolStyle {
+    pub fn and(&self) -> &'static str {
+        match self {
+            SymbolStyle::Ascii => "&",
+            SymbolStyle::Unicode => "∧",
+        }
+    }
+
+    pub fn or(&self) -> &'static str {
+        match self {
+            SymbolStyle::Ascii => "|",
+            SymbolStyle::Unicode => "∨",
+        }
+    }
+
+    pub fn not(&self) -> &'static str {
+        match self {
+            SymbolStyle::Ascii => "~",
+            SymbolStyle::Unicode => "¬",
+        }
+    }
+
+    pub fn implies(&self) -> &'static str {
+        match self {
+            SymbolStyle::Ascii => "=>",
+            SymbolStyle::Unicode => "→",
+        }
+    }
+
+    /// The bar between the row numbers and the proof.
+    pub fn bar(&self) -> char {
+        match self {
+            SymbolStyle::Ascii => '|',
+            SymbolStyle::Unicode => '│',
+        }
+    }
+
+    /// Draws the line under the premises and marks subproof assumptions.
+    pub fn hypothesis(&self) -> char {
+        match self {
+            SymbolStyle::Ascii => '-',
+            SymbolStyle::Unicode => '─',
+        }
+    }
+
+    pub fn iff(&self) -> &'static str {
+        match self {
+            SymbolStyle::Ascii => "<=>",
+            SymbolStyle::Unicode => "↔",
+        }
+    }
+
+    pub fn xor(&self) -> &'static str {
+        match self {
+            SymbolStyle::Ascii => "^",
+            SymbolStyle::Unicode => "⊕",
+        }
+    }
+
+    pub fn nand(&self) -> &'static str {
+        match self {
+            SymbolStyle::Ascii => "~&",
+            SymbolStyle::Unicode => "↑",
+        }
+    }
+
+    pub fn nor(&self) -> &'static str {
         match self {
-            Self::Absurdum => write!(f, "⊥"),
-            Self::Term(name) => write!(f, "{}", name),
-            Self::And(left, right) => write!(f, "({} & {})", left, right),
-            Self::Or(left, right) => write!(f, "({} | {})", left, right),
-            Self::Not(t) => write!(f, "(~{})", t),
-            Self::Implies(left, right) => write!(f, "({} => {})", left, right),
-            Self::Iff(left, right) => write!(f, "({} <=> {})", left, right),
+            SymbolStyle::Ascii => "~|",
+            SymbolStyle::Unicode => "↓",
+        }
+    }
+}
+
+/// A [`Proposition`] shown with the given [`SymbolStyle`].
+pub struct Styled<'a>(pub &'a Proposition, pub SymbolStyle);
+
+impl Display for Styled<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = self.1;
+        match self.0 {
+            Proposition::Absurdum => write!(f, "⊥"),
+            Proposition::Term(name) => write_name(f, name, s),
+            Proposition::And(conjuncts) => write_nary(f, conjuncts, s.and(), s),
+            Proposition::Or(disjuncts) => write_nary(f, disjuncts, s.or(), s),
+            Proposition::Not(t) => write!(f, "({}{})", s.not(), Styled(t, s)),
+            Proposition::Implies(left, right) => write!(
+                f,
+                "({} {} {})",
+                Styled(left, s),
+                s.implies(),
+                Styled(right, s)
+            ),
+            Proposition::Iff(left, right) => {
+                write!(f, "({} {} {})", Styled(left, s), s.iff(), Styled(right, s))
+            }
+            Proposition::Verum => write!(f, "⊤"),
+            Proposition::Xor(left, right) => {
+                write!(f, "({} {} {})", Styled(left, s), s.xor(), Styled(right, s))
+            }
+            Proposition::Nand(left, right) => {
+                write!(f, "({} {} {})", Styled(left, s), s.nand(), Styled(right, s))
+            }
+            Proposition::Nor(left, right) => {
+                write!(f, "({} {} {})", Styled(left, s), s.nor(), Styled(right, s))
+            }
+            Proposition::Meta(name) if name.starts_with(|c: char| c.is_ascii()) => {
+                write!(f, "?")?;
+                write_name(f, name, s)
+            }
+            Proposition::Meta(name) => write_name(f, name, s),
+        }
+    }
+}
+
+fn write_name(f: &mut std::fmt::Formatter<'_>, name: &str, style: SymbolStyle) -> std::fmt::Result {
+    if style == SymbolStyle::Ascii {
+        return write!(f, "{}", name);
+    }
+    let (stem, digits) = numeric_suffix(name);
+    let subscript = digits.chars().map(|d| match d.to_digit(10) {
+        Some(d) => char::from_u32('₀' as u32 + d).unwrap(),
+        None => d,
+    });
+    write!(f, "{}{}", stem, subscript.collect::<String>())
+}
+
+/// Splits an atom name into its stem and the digits it ends with, which are shown as a
+/// subscript where the output allows it.
+pub(crate) fn numeric_suffix(name: &str) -> (&str, &str) {
+    let stem = name.trim_end_matches(|c: char| c.is_ascii_digit());
+    name.split_at(stem.len())
+}
+
+fn write_nary(
+    f: &mut std::fmt::Formatter<'_>,
+    operands: &[Rc<Proposition>],
+    symbol: &str,
+    style: SymbolStyle,
+) -> std::fmt::Result {
+    write!(f, "(")?;
+    for (i, operand) in operands.iter().enumerate() {
+        if i > 0 {
+            write!(f, " {} ", symbol)?;
         }
+        write!(f, "{}", Styled(operand, style))?;
     }
+    write!(f, ")")
 }
 
+impl Display for Proposition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", Styled(self, SymbolStyle::Ascii))
+    }
+}
+
+// Subformulas are interned, so they are equal exactly when they are the same `Rc`.
 impl PartialEq for Proposition {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
-            (Self::Term(l0), Self::Term(r0)) => l0 == r0,
-            (Self::And(l0, l1), Self::And(r0, r1)) => l0 == r0 && l1 == r1,
-            (Self::Or(l0, l1), Self::Or(r0, r1)) => l0 == r0 && l1 == r1,
-            (Self::Not(l0), Self::Not(r0)) => l0 == r0,
-            (Self::Implies(l0, l1), Self::Implies(r0, r1)) => l0 == r0 && l1 == r1,
-            (Self::Iff(l0, l1), Self::Iff(r0, r1)) => l0 == r0 && l1 == r1,
-            _ => core::mem::discriminant(self) == core::mem::discriminant(other),
+            (Self::Term(l0), Self::Term(r0)) | (Self::Meta(l0), Self::Meta(r0)) => l0 == r0,
+            (Self::And(l), Self::And(r)) | (Self::Or(l), Self::Or(r)) => {
+                l.len() == r.len() && l.iter().zip(r).all(|(l, r)| Rc::ptr_eq(l, r))
+            }
+            (Self::Not(l0), Self::Not(r0)) => Rc::ptr_eq(l0, r0),
+            (Self::Implies(l0, l1), Self::Implies(r0, r1)) => {
+                Rc::ptr_eq(l0, r0) && Rc::ptr_eq(l1, r1)
+            }
+            (Self::Iff(l0, l1), Self::Iff(r0, r1)) => Rc::ptr_eq(l0, r0) && Rc::ptr_eq(l1, r1),
+            (Self::Xor(l0, l1), Self::Xor(r0, r1)) => Rc::ptr_eq(l0, r0) && Rc::ptr_eq(l1, r1),
+            (Self::Nand(l0, l1), Self::Nand(r0, r1)) => Rc::ptr_eq(l0, r0) && Rc::ptr_eq(l1, r1),
+            (Self::Nor(l0, l1), Self::Nor(r0, r1)) => Rc::ptr_eq(l0, r0) && Rc::ptr_eq(l1, r1),
+            _ => mem::discriminant(self) == mem::discriminant(other),
+        }
+    }
+}
+
+impl Eq for Proposition {}
+
+impl Hash for Proposition {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+        match self {
+            Proposition::Absurdum | Proposition::Verum => (),
+            Proposition::Term(name) | Proposition::Meta(name) => name.hash(state),
+            Proposition::Not(t) => Rc::as_ptr(t).hash(state),
+            Proposition::And(operands) | Proposition::Or(operands) => {
+                operands.iter().for_each(|p| Rc::as_ptr(p).hash(state))
+            }
+            Proposition::Implies(left, right)
+            | Proposition::Iff(left, right)
+            | Proposition::Xor(left, right)
+            | Proposition::Nand(left, right)
+            | Proposition::Nor(left, right) => {
+                Rc::as_ptr(left).hash(state);
+                Rc::as_ptr(right).hash(state);
+            }
         }
     }
 }
 
+thread_local! {
+    static INTERNER: RefCell<Interner> = RefCell::new(Interner::default());
+}
+
+/// The formulas built so far on this thread.
+#[derive(Default)]
+struct Interner {
+    formulas: HashSet<Rc<Proposition>>,
+    /// How many formulas were left after the last sweep.
+    swept: usize,
+}
+
+impl Interner {
+    fn intern(&mut self, prop: Proposition) -> Rc<Proposition> {
+        if let Some(res) = self.formulas.get(&prop) {
+            return res.clone();
+        }
+        if self.formulas.len() >= 2 * self.swept.max(512) {
+            self.sweep();
+        }
+        let res = Rc::new(prop);
+        self.formulas.insert(res.clone());
+        res
+    }
+
+    /// Forgets the formulas nothing else holds anymore. Forgetting a formula can leave its
+    /// subformulas unused in turn, hence the loop.
+    fn sweep(&mut self) {
+        loop {
+            let before = self.formulas.len();
+            self.formulas.retain(|prop| Rc::strong_count(prop) > 1);
+            if self.formulas.len() == before {
+                break;
+            }
+        }
+        self.swept = self.formulas.len();
+    }
+}
+
+fn intern(prop: Proposition) -> Rc<Proposition> {
+    INTERNER.with(|interner| interner.borrow_mut().intern(prop))
+}
+
 impl Proposition {
     pub fn new_absurdum() -> Rc<Proposition> {
-        Rc::new(Proposition::Absurdum)
+        intern(Proposition::Absurdum)
     }
 
     pub fn new_term(name: &str) -> Rc<Proposition> {
-        Rc::new(Proposition::Term(name.to_string()))
+        intern(Proposition::Term(name.to_string()))
+    }
+
+    pub fn new_meta(name: &str) -> Rc<Proposition> {
+        intern(Proposition::Meta(name.to_string()))
     }
 
     pub fn new_and(left: &Rc<Proposition>, right: &Rc<Proposition>) -> Rc<Proposition> {
-        Rc::new(Proposition::And(left.clone(), right.clone()))
+        Proposition::new_conjunction(&[left.clone(), right.clone()])
     }
 
     pub fn new_or(left: &Rc<Proposition>, right: &Rc<Proposition>) -> Rc<Proposition> {
-        Rc::new(Proposition::Or(left.clone(), right.clone()))
+        Proposition::new_disjunction(&[left.clone(), right.clone()])
+    }
+
+    /// The conjunction of `conjuncts` as given, without flattening nested conjunctions.
+    /// Panics when there are fewer than two.
+    pub fn new_conjunction(conjuncts: &[Rc<Proposition>]) -> Rc<Proposition> {
+        assert!(
+            conjuncts.len() >= 2,
+            "a conjunction needs two or more operands"
+        );
+        intern(Proposition::And(conjuncts.to_vec()))
+    }
+
+    /// The disjunction of `disjuncts` as given, without flattening nested disjunctions.
+    /// Panics when there are fewer than two.
+    pub fn new_disjunction(disjuncts: &[Rc<Proposition>]) -> Rc<Proposition> {
+        assert!(
+            disjuncts.len() >= 2,
+            "a disjunction needs two or more operands"
+        );
+        intern(Proposition::Or(disjuncts.to_vec()))
     }
 
     pub fn new_not(prop: &Rc<Proposition>) -> Rc<Proposition> {
-        Rc::new(Proposition::Not(prop.clone()))
+        intern(Proposition::Not(prop.clone()))
     }
 
     pub fn new_implies(left: &Rc<Proposition>, right: &Rc<Proposition>) -> Rc<Proposition> {
-        Rc::new(Proposition::Implies(left.clone(), right.clone()))
+        intern(Proposition::Implies(left.clone(), right.clone()))
     }
 
     pub fn new_iff(left: &Rc<Proposition>, right: &Rc<Proposition>) -> Rc<Proposition> {
-        Rc::new(Proposition::Iff(left.clone(), right.clone()))
+        intern(Proposition::Iff(left.clone(), right.clone()))
+    }
+
+    pub fn new_verum() -> Rc<Proposition> {
+        intern(Proposition::Verum)
+    }
+
+    pub fn new_xor(left: &Rc<Proposition>, right: &Rc<Proposition>) -> Rc<Proposition> {
+        intern(Proposition::Xor(left.clone(), right.clone()))
+    }
+
+    pub fn new_nand(left: &Rc<Proposition>, right: &Rc<Proposition>) -> Rc<Proposition> {
+        intern(Proposition::Nand(left.clone(), right.clone()))
+    }
+
+    pub fn new_nor(left: &Rc<Proposition>, right: &Rc<Proposition>) -> Rc<Proposition> {
+        intern(Proposition::Nor(left.clone(), right.clone()))
+    }
+
+    /// The truth value of the formula when each atom has the value given by `value`.
+    /// Metavariables are valued like atoms of the same name.
+    pub fn evaluate(&self, value: &impl Fn(&str) -> bool) -> bool {
+        match self {
+            Proposition::Absurdum => false,
+            Proposition::Verum => true,
+            Proposition::Term(name) | Proposition::Meta(name) => value(name),
+            Proposition::Not(t) => !t.evaluate(value),
+            Proposition::And(conjuncts) => conjuncts.iter().all(|p| p.evaluate(value)),
+            Proposition::Or(disjuncts) => disjuncts.iter().any(|p| p.evaluate(value)),
+            Proposition::Implies(left, right) => !left.evaluate(value) || right.evaluate(value),
+            Proposition::Iff(left, right) => left.evaluate(value) == right.evaluate(value),
+            Proposition::Xor(left, right) => left.evaluate(value) != right.evaluate(value),
+            Proposition::Nand(left, right) => !(left.evaluate(value) && right.evaluate(value)),
+            Proposition::Nor(left, right) => !(left.evaluate(value) || right.evaluate(value)),
+        }
+    }
+
+    /// All the subformulas of `prop`, itself included, outermost first.
+    pub fn subformulas(prop: &Rc<Proposition>) -> Vec<Rc<Proposition>> {
+        let mut res = vec![prop.clone()];
+        match prop.borrow() {
+            Proposition::Absurdum
+            | Proposition::Verum
+            | Proposition::Term(_)
+            | Proposition::Meta(_) => (),
+            Proposition::Not(t) => res.extend(Proposition::subformulas(t)),
+            Proposition::And(operands) | Proposition::Or(operands) => {
+                operands
+                    .iter()
+                    .for_each(|p| res.extend(Proposition::subformulas(p)));
+            }
+            Proposition::Implies(left, right)
+            | Proposition::Iff(left, right)
+            | Proposition::Xor(left, right)
+            | Proposition::Nand(left, right)
+            | Proposition::Nor(left, right) => {
+                res.extend(Proposition::subformulas(left));
+                res.extend(Proposition::subformulas(right));
+            }
+        }
+        res
     }
 }
 
-enum Rule {
+/// The name of the rule system proofs are checked against, the only one there is for now.
+pub const RULE_SYSTEM: &str = "fitch";
+
+/// An inference rule of the Fitch system: reiteration and an introduction and elimination rule
+/// for each connective. ⊤ has no elimination rule, and XOR, NAND and NOR are introduced by
+/// folding their definition and eliminated by unfolding it: `(A ^ B)` is `(~(A <=> B))`,
+/// `(A ~& B)` is `(~(A & B))` and `(A ~| B)` is `(~(A | B))`.
+///
+/// As in LPL, `∧I` conjoins any number of rows, `∧E` extracts any conjunct, `∨I` puts the cited
+/// row in any position of the disjunction and `∨E` takes one subproof per disjunct.
+///
+/// `Lemma` cites a registered [`Lemma`] by its id and `Custom` a registered [`CustomRule`];
+/// neither is among [`Rule::ALL`].
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Rule {
     Reiter,
     IntroAbs,
     ElimAbs,
@@ -83,24 +433,217 @@ enum Rule {
     ElimImpl,
     IntroIff,
     ElimIff,
+    IntroVerum,
+    IntroXor,
+    ElimXor,
+    IntroNand,
+    ElimNand,
+    IntroNor,
+    ElimNor,
+    Lemma(usize),
+    Custom(usize),
 }
 
-enum FitchComponent {
+impl Rule {
+    pub const ALL: [Rule; 20] = [
+        Rule::Reiter,
+        Rule::IntroAbs,
+        Rule::ElimAbs,
+        Rule::IntroAnd,
+        Rule::ElimAnd,
+        Rule::IntroOr,
+        Rule::ElimOr,
+        Rule::IntroNot,
+        Rule::ElimNot,
+        Rule::IntroImpl,
+        Rule::ElimImpl,
+        Rule::IntroIff,
+        Rule::ElimIff,
+        Rule::IntroVerum,
+        Rule::IntroXor,
+        Rule::ElimXor,
+        Rule::IntroNand,
+        Rule::ElimNand,
+        Rule::IntroNor,
+        Rule::ElimNor,
+    ];
+
+    pub fn symbol(&self) -> String {
+        self.styled_symbol(SymbolStyle::Ascii)
+    }
+
+    pub fn styled_symbol(&self, style: SymbolStyle) -> String {
+        let connective = match self {
+            Rule::Reiter => return "Re".to_string(),
+            Rule::Lemma(id) => {
+                return Lemma::get(*id)
+                    .map_or("Lemma ?".to_string(), |l| format!("Lemma {}", l.name))
+            }
+            Rule::Custom(id) => {
+                return CustomRule::get(*id).map_or("?".to_string(), |r| r.name.clone())
+            }
+            Rule::IntroAbs | Rule::ElimAbs => "⊥",
+            Rule::IntroAnd | Rule::ElimAnd => style.and(),
+            Rule::IntroOr | Rule::ElimOr => style.or(),
+            Rule::IntroNot | Rule::ElimNot => style.not(),
+            Rule::IntroImpl | Rule::ElimImpl => style.implies(),
+            Rule::IntroIff | Rule::ElimIff => style.iff(),
+            Rule::IntroVerum => "⊤",
+            Rule::IntroXor | Rule::ElimXor => style.xor(),
+            Rule::IntroNand | Rule::ElimNand => style.nand(),
+            Rule::IntroNor | Rule::ElimNor => style.nor(),
+        };
+        match self.introduces() {
+            true => format!("I {}", connective),
+            false => format!("E {}", connective),
+        }
+    }
+
+    /// Whether the rule is listed among the introduction rules. Reiteration and lemmas are
+    /// neither, and count as eliminations.
+    pub fn introduces(&self) -> bool {
+        match self {
+            Rule::IntroAbs
+            | Rule::IntroAnd
+            | Rule::IntroOr
+            | Rule::IntroNot
+            | Rule::IntroImpl
+            | Rule::IntroIff
+            | Rule::IntroVerum
+            | Rule::IntroXor
+            | Rule::IntroNand
+            | Rule::IntroNor => true,
+            Rule::Custom(id) => CustomRule::get(*id).is_some_and(|r| r.introduces),
+            _ => false,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rule::Reiter => "reiterate",
+            Rule::IntroAbs => "introduce absurdum",
+            Rule::ElimAbs => "eliminate absurdum",
+            Rule::IntroAnd => "introduce and",
+            Rule::ElimAnd => "eliminate and",
+            Rule::IntroOr => "introduce or",
+            Rule::ElimOr => "eliminate or",
+            Rule::IntroNot => "introduce not",
+            Rule::ElimNot => "eliminate not",
+            Rule::IntroImpl => "introduce implies",
+            Rule::ElimImpl => "eliminate implies",
+            Rule::IntroIff => "introduce iff",
+            Rule::ElimIff => "eliminate iff",
+            Rule::IntroVerum => "introduce verum",
+            Rule::IntroXor => "introduce xor",
+            Rule::ElimXor => "eliminate xor",
+            Rule::IntroNand => "introduce nand",
+            Rule::ElimNand => "eliminate nand",
+            Rule::IntroNor => "introduce nor",
+            Rule::ElimNor => "eliminate nor",
+            Rule::Lemma(_) => "lemma",
+            Rule::Custom(_) => "custom rule",
+        }
+    }
+
+    /// The connective the rule introduces or eliminates, as named in the rule menus.
+    pub fn connective(&self) -> &'static str {
+        match self {
+            Rule::Reiter => "reiterate",
+            Rule::IntroAbs | Rule::ElimAbs => "absurdum",
+            Rule::IntroAnd | Rule::ElimAnd => "and",
+            Rule::IntroOr | Rule::ElimOr => "or",
+            Rule::IntroNot | Rule::ElimNot => "not",
+            Rule::IntroImpl | Rule::ElimImpl => "implies",
+            Rule::IntroIff | Rule::ElimIff => "iff",
+            Rule::IntroVerum => "verum",
+            Rule::IntroXor | Rule::ElimXor => "xor",
+            Rule::IntroNand | Rule::ElimNand => "nand",
+            Rule::IntroNor | Rule::ElimNor => "nor",
+            Rule::Lemma(_) => "lemma",
+            Rule::Custom(_) => "custom",
+        }
+    }
+}
+
+/// Outcome of re-checking a single row against the rows above it.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RowStatus {
+    Valid,
+    Invalid(&'static str),
+    Unjustified,
+}
+
+/// A row of a proof: an assumption, a deduction with its rule and cited rows, or a formula still
+/// waiting for a justification.
+#[derive(Clone, PartialEq)]
+pub enum FitchComponent {
     Assumption(Rc<Proposition>),
     Deduction(Rc<Proposition>, Rule, Vec<usize>),
+    Unjustified(Rc<Proposition>),
 }
 
 impl FitchComponent {
-    fn unwrap(&self) -> &Rc<Proposition> {
+    pub fn unwrap(&self) -> &Rc<Proposition> {
         match self {
             FitchComponent::Assumption(t) => t,
             FitchComponent::Deduction(t, _, _) => t,
+            FitchComponent::Unjustified(t) => t,
         }
     }
 }
 
 type Level = usize;
 
+/// A row of [`Fitch::layout`].
+pub struct RowLayout {
+    pub level: Level,
+    pub opens_subproof: bool,
+    /// Whether the hypothesis line goes under the row: the last premise and subproof assumptions.
+    pub underlined: bool,
+    pub expression: String,
+    /// The rule and its citations, `?` for unjustified rows and empty for assumptions.
+    pub justification: String,
+}
+
+/// A line of [`Fitch::proof_lines`]. The line under the premises and the cursor have no row and
+/// are all `bars`.
+pub struct ProofLine {
+    pub row: Option<usize>,
+    /// The row number, right-aligned.
+    pub number: String,
+    /// The subproof bars and the indentation up to the expression.
+    pub bars: String,
+    pub expression: String,
+    /// The justification, after the padding that aligns it with the others.
+    pub justification: String,
+}
+
+impl ProofLine {
+    fn bars(bars: String) -> ProofLine {
+        ProofLine {
+            row: None,
+            number: String::new(),
+            bars,
+            expression: String::new(),
+            justification: String::new(),
+        }
+    }
+}
+
+/// Rows copied from a proof with [`Fitch::copy`], levels relative to the first one.
+#[derive(Clone)]
+pub struct Fragment {
+    first: usize,
+    premise: bool,
+    rows: Vec<(Level, FitchComponent)>,
+    /// The rows above the fragment in the proof it was copied from, which citations leaving the
+    /// fragment point at.
+    context: Vec<(Level, FitchComponent)>,
+}
+
+/// A proof: the premises followed by the deductions, each row at the level of the subproof it is
+/// in. Rows are only ever added at the end.
+#[derive(Clone, PartialEq)]
 pub struct Fitch {
     statements: Vec<(Level, FitchComponent)>,
     start_of_deductions: usize,
@@ -110,6 +653,100 @@ pub struct Fitch {
 impl Display for Fitch {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut res = String::new();
+        self.lines(SymbolStyle::Ascii, SymbolStyle::Ascii)
+            .iter()
+            .for_each(|(_, line)| {
+                res.push_str(line);
+                res.push('\n');
+            });
+        write!(f, "{}", res)
+    }
+}
+
+impl Default for Fitch {
+    fn default() -> Fitch {
+        Fitch::new()
+    }
+}
+
+impl Fitch {
+    pub fn new() -> Fitch {
+        Fitch {
+            statements: Vec::new(),
+            start_of_deductions: 0,
+            current_level: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.statements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.statements.is_empty()
+    }
+
+    pub fn proposition(&self, row: usize) -> Option<&Rc<Proposition>> {
+        self.statements.get(row).map(|(_, comp)| comp.unwrap())
+    }
+
+    /// Every distinct subformula appearing in the proof, starting with the ones of `first`.
+    pub fn subformulas(&self, first: Option<usize>) -> Vec<Rc<Proposition>> {
+        let mut res: Vec<Rc<Proposition>> = Vec::new();
+        first
+            .and_then(|row| self.proposition(row))
+            .into_iter()
+            .chain(self.statements.iter().map(|(_, comp)| comp.unwrap()))
+            .flat_map(Proposition::subformulas)
+            .for_each(|prop| {
+                if !res.contains(&prop) {
+                    res.push(prop);
+                }
+            });
+        res
+    }
+
+    /// The rows as laid out on screen, with the expressions and rules written in `symbols`.
+    pub fn layout(&self, symbols: SymbolStyle) -> Vec<RowLayout> {
+        self.statements
+            .iter()
+            .enumerate()
+            .map(|(i, (level, comp))| RowLayout {
+                level: *level,
+                opens_subproof: self.opens_subproof(i),
+                underlined: self.opens_subproof(i) || i + 1 == self.start_of_deductions,
+                expression: Styled(comp.unwrap(), symbols).to_string(),
+                justification: match comp {
+                    FitchComponent::Assumption(_) => String::new(),
+                    FitchComponent::Unjustified(_) => "?".to_string(),
+                    FitchComponent::Deduction(_, r, ass) => format!(
+                        "{} [{}]",
+                        r.styled_symbol(symbols),
+                        ass.iter()
+                            .map(|v| v.to_string())
+                            .collect::<Vec<String>>()
+                            .join(", ")
+                    ),
+                },
+            })
+            .collect()
+    }
+
+    /// The proof as shown in the TUI, each line tagged with its row. The line under the premises
+    /// and the cursor have no row. `bars` picks the characters the bars are drawn with.
+    pub fn lines(&self, symbols: SymbolStyle, bars: SymbolStyle) -> Vec<(Option<usize>, String)> {
+        self.proof_lines(symbols, bars)
+            .into_iter()
+            .map(|line| {
+                let text = [line.number, line.bars, line.expression, line.justification].concat();
+                (line.row, text)
+            })
+            .collect()
+    }
+
+    /// [`Fitch::lines`] with each line split into its parts, for styling them apart.
+    pub fn proof_lines(&self, symbols: SymbolStyle, bars: SymbolStyle) -> Vec<ProofLine> {
+        let mut lines = Vec::new();
         let mut temp = self.statements.len() as i32 - 1;
         if temp == 0 {
             temp = 1;
@@ -121,88 +758,66 @@ impl Display for Fitch {
             max += 1;
         }
 
-        let longest_row = self
-            .statements
+        let layout = self.layout(symbols);
+        let longest_row = layout
             .iter()
-            .map(|(level, comp)| comp.unwrap().to_string().chars().count() + level * 4)
+            .map(|row| row.expression.chars().count() + row.level * 4)
             .max()
             .unwrap_or(0);
 
-        self.statements
-            .iter()
-            .enumerate()
-            .for_each(|(i, (level, expression))| {
-                if i == self.start_of_deductions {
-                    res.push_str("------------------\n");
-                }
+        layout.into_iter().enumerate().for_each(|(i, row)| {
+            if i == self.start_of_deductions {
+                lines.push(ProofLine::bars(bars.hypothesis().to_string().repeat(18)));
+            }
 
-                res.push(' ');
-                let mut temp = if i == 0 { 1 } else { i as i32 };
-                let mut white = 0;
-                while temp > 0 {
-                    temp /= 10;
-                    white += 1;
-                }
+            let mut number = String::from(" ");
+            let mut temp = if i == 0 { 1 } else { i as i32 };
+            let mut white = 0;
+            while temp > 0 {
+                temp /= 10;
+                white += 1;
+            }
 
-                let white = max - white;
-                for _ in 0..white {
-                    res.push(' ');
-                }
-                res.push_str(format!("{} | ", i).as_str());
+            let white = max - white;
+            for _ in 0..white {
+                number.push(' ');
+            }
+            number.push_str(format!("{} ", i).as_str());
 
-                let is_sub = matches!(expression, FitchComponent::Assumption(_));
+            let mut bar = format!("{} ", bars.bar());
+            let is_sub = matches!(self.statements[i].1, FitchComponent::Assumption(_));
 
-                if is_sub {
-                    res.push('-');
-                }
-                for _ in 0..*level {
-                    res.push_str("    ");
-                }
-                if is_sub {
-                    res.pop();
-                }
-
-                let exp_str = expression.unwrap().to_string();
-                res.push_str(exp_str.as_str());
+            if is_sub {
+                bar.push(bars.hypothesis());
+            }
+            for _ in 0..row.level {
+                bar.push_str("    ");
+            }
+            if is_sub {
+                bar.pop();
+            }
 
-                let to_add = longest_row - (exp_str.chars().count() + level * 4);
-                for _ in 0..to_add {
-                    res.push(' ');
-                }
+            let mut justification = String::new();
+            let to_add = longest_row - (row.expression.chars().count() + row.level * 4);
+            for _ in 0..to_add {
+                justification.push(' ');
+            }
 
-                match expression {
-                    FitchComponent::Assumption(_) => (),
-                    FitchComponent::Deduction(_, r, ass) => {
-                        let sym = match r {
-                            Rule::Reiter => "    Re ",
-                            Rule::IntroAbs => "    I ⊥ ",
-                            Rule::ElimAbs => "    E ⊥ ",
-                            Rule::IntroAnd => "    I & ",
-                            Rule::ElimAnd => "    E & ",
-                            Rule::IntroOr => "    I | ",
-                            Rule::ElimOr => "    E | ",
-                            Rule::IntroNot => "    I ~ ",
-                            Rule::ElimNot => "    E ~ ",
-                            Rule::IntroImpl => "    I => ",
-                            Rule::ElimImpl => "    E => ",
-                            Rule::IntroIff => "    I <=> ",
-                            Rule::ElimIff => "    E <=> ",
-                        };
-                        res.push_str(sym);
-                        let mut assums = ass
-                            .iter()
-                            .map(|v| v.to_string())
-                            .collect::<Vec<String>>()
-                            .join(", ");
-                        assums.insert(0, '[');
-                        assums.push(']');
-                        res.push_str(&assums);
-                    }
-                }
+            if !row.justification.is_empty() {
+                justification.push_str("    ");
+                justification.push_str(row.justification.as_str());
+            }
 
-                res.push('\n');
+            lines.push(ProofLine {
+                row: Some(i),
+                number,
+                bars: bar,
+                expression: row.expression,
+                justification,
             });
+        });
 
+        let mut res = String::new();
         for _ in 0..max + 4 {
             res.push(' ');
         }
@@ -211,20 +826,12 @@ impl Display for Fitch {
             res.push_str("    ");
         }
 
-        res.push_str("^\n");
-        write!(f, "{}", res)
-    }
-}
-
-impl Fitch {
-    pub fn new() -> Fitch {
-        Fitch {
-            statements: Vec::new(),
-            start_of_deductions: 0,
-            current_level: 0,
-        }
+        res.push('^');
+        lines.push(ProofLine::bars(res));
+        lines
     }
 
+    /// Adds a premise. Premises come before every other row.
     pub fn add_assumption(&mut self, prop: &Rc<Proposition>) -> bool {
         if self.statements.len() > self.start_of_deductions {
             return false;
@@ -236,6 +843,7 @@ impl Fitch {
         true
     }
 
+    /// Opens a subproof inside the current one, assuming `prop`.
     pub fn add_subproof(&mut self, prop: &Rc<Proposition>) {
         self.current_level += 1;
         self.statements
@@ -260,97 +868,454 @@ impl Fitch {
         }
     }
 
-    pub fn introduce_and(&mut self, left: usize, right: usize) -> bool {
-        let left_x = match self.statements.get(left) {
-            Some(v) => v,
-            None => return false,
-        };
-        let right_x = match self.statements.get(right) {
-            Some(v) => v,
-            None => return false,
-        };
+    /// Adds a row with no justification yet, to be filled in later with [`Fitch::justify`].
+    pub fn add_unjustified(&mut self, prop: &Rc<Proposition>) {
+        self.statements.push((
+            self.current_level,
+            FitchComponent::Unjustified(prop.clone()),
+        ));
+    }
 
-        let ris = Proposition::new_and(left_x.1.unwrap(), right_x.1.unwrap());
+    /// Adds a row with the given justification without requiring it to be correct. Use
+    /// [`Fitch::row_status`] to find out whether it holds.
+    pub fn add_tentative(&mut self, prop: &Rc<Proposition>, rule: Rule, citations: Vec<usize>) {
         self.statements.push((
             self.current_level,
-            FitchComponent::Deduction(ris, Rule::IntroAnd, vec![left, right]),
+            FitchComponent::Deduction(prop.clone(), rule, citations),
         ));
-        true
     }
 
-    pub fn eliminate_and(&mut self, assum: usize, new_exp: &Rc<Proposition>) -> bool {
-        let assum_x = match self.statements.get(assum) {
-            Some(v) => v.1.unwrap(),
-            None => return false,
+    /// Replaces the justification of a deduction row. Assumptions can't be justified.
+    pub fn justify(&mut self, row: usize, rule: Rule, citations: Vec<usize>) -> bool {
+        match self.statements.get_mut(row) {
+            Some((_, FitchComponent::Assumption(_))) | None => false,
+            Some((_, comp)) => {
+                *comp = FitchComponent::Deduction(comp.unwrap().clone(), rule, citations);
+                true
+            }
+        }
+    }
+
+    pub fn statements(&self) -> &[(Level, FitchComponent)] {
+        &self.statements
+    }
+
+    /// The number of premises, which come before every other row.
+    pub fn start_of_deductions(&self) -> usize {
+        self.start_of_deductions
+    }
+
+    /// Whether `row` is the assumption of a subproof.
+    pub fn opens_subproof(&self, row: usize) -> bool {
+        matches!(
+            self.statements.get(row),
+            Some((level, FitchComponent::Assumption(_))) if *level > 0
+        )
+    }
+
+    /// The last row of the subproof opened by `row`, or `row` itself if it doesn't open one.
+    pub fn subproof_end(&self, row: usize) -> usize {
+        if !self.opens_subproof(row) {
+            return row;
+        }
+        let level = self.statements[row].0;
+        let mut end = row + 1;
+        while let Some((l, comp)) = self.statements.get(end) {
+            if *l < level || (*l == level && matches!(comp, FitchComponent::Assumption(_))) {
+                break;
+            }
+            end += 1;
+        }
+        end - 1
+    }
+
+    /// Copies `row`, along with the rest of its subproof when `row` opens one. The levels are
+    /// kept relative to the level the row is in, so a copied subproof stays one level deeper.
+    pub fn copy(&self, row: usize) -> Option<Fragment> {
+        let (level, _) = self.statements.get(row)?;
+        let level = match self.opens_subproof(row) {
+            true => level - 1,
+            false => *level,
         };
+        let end = self.subproof_end(row) + 1;
+        Some(Fragment {
+            first: row,
+            premise: row < self.start_of_deductions,
+            rows: self.statements[row..end]
+                .iter()
+                .map(|(l, comp)| (l - level, comp.clone()))
+                .collect(),
+            context: self.statements[..row].to_vec(),
+        })
+    }
 
-        match assum_x.borrow() {
-            Proposition::And(left, _) if left == new_exp => {
-                self.statements.push((
-                    self.current_level,
-                    FitchComponent::Deduction(left.clone(), Rule::ElimAnd, vec![assum]),
-                ));
-                true
+    /// Appends copied rows at the current level, a pasted subproof opening inside the current one
+    /// and left closed. Citations of rows inside the fragment follow them. A row citing a row
+    /// outside of it keeps its citations only when that row is the same here as where it was
+    /// copied from, otherwise it is left unjustified. The rows are checked again by
+    /// [`Fitch::row_status`]. A premise can only be pasted as long as there are no deductions.
+    /// Returns the pasted rows.
+    pub fn paste(&mut self, fragment: &Fragment) -> Option<Range<usize>> {
+        let start = self.statements.len();
+        if fragment.premise {
+            return self
+                .add_assumption(fragment.rows[0].1.unwrap())
+                .then_some(start..start + 1);
+        }
+        let copied = fragment.first..fragment.first + fragment.rows.len();
+        for (level, comp) in &fragment.rows {
+            let comp = match comp {
+                FitchComponent::Deduction(prop, _, citations)
+                    if citations.iter().any(|c| {
+                        !copied.contains(c) && self.statements.get(*c) != fragment.context.get(*c)
+                    }) =>
+                {
+                    FitchComponent::Unjustified(prop.clone())
+                }
+                FitchComponent::Deduction(prop, rule, citations) => FitchComponent::Deduction(
+                    prop.clone(),
+                    *rule,
+                    citations
+                        .iter()
+                        .map(|c| match copied.contains(c) {
+                            true => c - fragment.first + start,
+                            false => *c,
+                        })
+                        .collect(),
+                ),
+                comp => comp.clone(),
+            };
+            self.statements.push((self.current_level + level, comp));
+        }
+        Some(start..self.statements.len())
+    }
+
+    /// Checks whether `row` follows from the rows it cites, which have to come before it. Rules
+    /// that discharge a subproof cite its first row.
+    pub fn row_status(&self, row: usize) -> RowStatus {
+        match self.statements.get(row) {
+            None => RowStatus::Invalid("Row does not exist"),
+            Some((0, FitchComponent::Assumption(_))) if row >= self.start_of_deductions => {
+                RowStatus::Invalid("An assumption after the premises has to open a subproof")
+            }
+            Some((_, FitchComponent::Assumption(_))) => RowStatus::Valid,
+            Some((_, FitchComponent::Unjustified(_))) => RowStatus::Unjustified,
+            Some((level, FitchComponent::Deduction(prop, rule, citations))) => {
+                match self.check(row, *level, *rule, citations, Some(prop)) {
+                    Ok(derived) if &derived == prop => RowStatus::Valid,
+                    Ok(_) => RowStatus::Invalid("The rule derives a different expression"),
+                    Err(reason) => RowStatus::Invalid(reason),
+                }
             }
-            Proposition::And(_, right) if right == new_exp => {
+        }
+    }
+
+    fn deduce(
+        &mut self,
+        rule: Rule,
+        citations: Vec<usize>,
+        prop: Option<&Rc<Proposition>>,
+    ) -> bool {
+        match self.check(
+            self.statements.len(),
+            self.current_level,
+            rule,
+            &citations,
+            prop,
+        ) {
+            Err(_) => false,
+            Ok(ris) => {
                 self.statements.push((
                     self.current_level,
-                    FitchComponent::Deduction(right.clone(), Rule::ElimAnd, vec![assum]),
+                    FitchComponent::Deduction(ris, rule, citations),
                 ));
                 true
             }
-            _ => false,
         }
     }
 
-    pub fn reiterate(&mut self, row: usize) -> bool {
-        if row >= self.statements.len() {
-            return false;
+    fn cited(&self, upto: usize, row: usize) -> Result<&(Level, FitchComponent), &'static str> {
+        if row >= upto {
+            return Err("Cited row does not exist");
         }
-
-        let a = self.statements.get(row).unwrap();
-        if a.0 > self.current_level {
-            return false;
-        }
-
-        self.statements.push((
-            self.current_level,
-            FitchComponent::Deduction(a.1.unwrap().clone(), Rule::Reiter, vec![row]),
-        ));
-        true
+        Ok(self.statements.get(row).unwrap())
     }
 
-    pub fn introduce_or(&mut self, assum: usize, prop: &Rc<Proposition>) -> bool {
-        let assum_x = match self.statements.get(assum) {
-            Some(v) => v.1.unwrap(),
-            None => return false,
-        };
+    /// Checks an application of `rule` as if it was added after the first `upto` rows at the given
+    /// level, returning the derived expression. `prop` is the expression chosen by the user for
+    /// the rules that need one.
+    fn check(
+        &self,
+        upto: usize,
+        level: Level,
+        rule: Rule,
+        citations: &[usize],
+        prop: Option<&Rc<Proposition>>,
+    ) -> Result<Rc<Proposition>, &'static str> {
+        match (rule, citations) {
+            (Rule::Reiter, [row]) => {
+                let (l, a) = self.cited(upto, *row)?;
+                if *l > level {
+                    return Err("Cannot reiterate from a deeper subproof");
+                }
+                Ok(a.unwrap().clone())
+            }
+            (Rule::IntroAbs, [ass1, ass2]) => {
+                let a1 = self.cited(upto, *ass1)?.1.unwrap();
+                let a2 = self.cited(upto, *ass2)?.1.unwrap();
+                let negates =
+                    |not: &Proposition, prop| matches!(not, Proposition::Not(t) if t == prop);
+                if !(negates(a1, a2) || negates(a2, a1)) {
+                    return Err("Cited rows are not an expression and its negation");
+                }
+                Ok(Proposition::new_absurdum())
+            }
+            (Rule::ElimAbs, [absurdum]) => {
+                let (l, m) = self.cited(upto, *absurdum)?;
+                if !matches!(**m.unwrap(), Proposition::Absurdum) || *l > level {
+                    return Err("Cited row is not an absurdum");
+                }
+                prop.cloned().ok_or("Missing expression to introduce")
+            }
+            (Rule::IntroAnd, [_, _, ..]) => {
+                let conjuncts = citations
+                    .iter()
+                    .map(|row| Ok(self.cited(upto, *row)?.1.unwrap().clone()))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(Proposition::new_conjunction(&conjuncts))
+            }
+            (Rule::ElimAnd, [assum]) => {
+                let assum_x = self.cited(upto, *assum)?.1.unwrap();
+                let new_exp = prop.ok_or("Missing expression to extract")?;
+                match assum_x.borrow() {
+                    Proposition::And(conjuncts) => conjuncts
+                        .iter()
+                        .find(|c| *c == new_exp)
+                        .cloned()
+                        .ok_or("Expression is not a conjunct of the conjunction"),
+                    _ => Err("Cited row is not a conjunction"),
+                }
+            }
+            (Rule::IntroOr, [assum]) => {
+                let assum_x = self.cited(upto, *assum)?.1.unwrap();
+                let prop = prop.ok_or("Missing expression to introduce")?;
+                match prop.borrow() {
+                    Proposition::Or(disjuncts) if disjuncts.contains(assum_x) => Ok(prop.clone()),
+                    Proposition::Or(_) => Err("Cited row is not a disjunct of the disjunction"),
+                    _ => Err("Expression is not a disjunction"),
+                }
+            }
+            (Rule::ElimOr, [assum, subproofs @ ..]) => {
+                if subproofs.iter().any(|sub| assum >= sub) {
+                    return Err("The disjunction must come before the subproofs");
+                }
+                let disjuncts = match self.cited(upto, *assum)?.1.unwrap().borrow() {
+                    Proposition::Or(disjuncts) => disjuncts,
+                    _ => return Err("Cited row is not a disjunction"),
+                };
+                if disjuncts.len() != subproofs.len() {
+                    return Err("Cite one subproof per disjunct");
+                }
+                for (disjunct, sub) in disjuncts.iter().zip(subproofs) {
+                    if self.cited(upto, *sub)?.1.unwrap() != disjunct {
+                        return Err("Subproofs don't assume the disjuncts in order");
+                    }
+                }
 
-        match prop.borrow() {
-            Proposition::Or(left, right) => {
-                if assum_x == left || assum_x == right {
-                    self.statements.push((
-                        self.current_level,
-                        FitchComponent::Deduction(prop.clone(), Rule::IntroOr, vec![assum]),
-                    ));
-                    true
-                } else {
-                    false
+                let mut results = subproofs
+                    .iter()
+                    .map(|sub| self.get_subproof_result(*sub, upto));
+                match results.next().flatten() {
+                    Some(first) if results.all(|r| r == Some(first)) => Ok(first.clone()),
+                    _ => Err("Subproofs don't reach the same conclusion"),
                 }
             }
-            _ => false,
+            (Rule::IntroNot, [sub_proof]) => {
+                match self
+                    .get_subproof_result(*sub_proof, upto)
+                    .map(|v| v.borrow())
+                {
+                    Some(Proposition::Absurdum) => (),
+                    _ => return Err("Subproof does not end with an absurdum"),
+                }
+                let (l, cur) = self.cited(upto, *sub_proof)?;
+                if *l != level + 1 {
+                    return Err("Subproof is not directly inside the current level");
+                }
+                Ok(Proposition::new_not(cur.unwrap()))
+            }
+            (Rule::ElimNot, [row]) => {
+                let cur = self.cited(upto, *row)?.1.unwrap();
+                match cur.borrow() {
+                    Proposition::Not(v) => match v.borrow() {
+                        Proposition::Not(b) => Ok(b.clone()),
+                        _ => Err("Cited row is not a double negation"),
+                    },
+                    _ => Err("Cited row is not a double negation"),
+                }
+            }
+            (Rule::IntroImpl, [sub_proof]) => {
+                let (l, start) = self.cited(upto, *sub_proof)?;
+                let end = self
+                    .get_subproof_result(*sub_proof, upto)
+                    .ok_or("Cited row is not a subproof")?;
+                if *l != level + 1 {
+                    return Err("Subproof is not directly inside the current level");
+                }
+                Ok(Proposition::new_implies(start.unwrap(), end))
+            }
+            (Rule::ElimImpl, [assum, left]) => {
+                let assum_x = self.cited(upto, *assum)?.1.unwrap();
+                let left_x = self.cited(upto, *left)?.1.unwrap();
+                match assum_x.borrow() {
+                    Proposition::Implies(l, r) if left_x == l => Ok(r.clone()),
+                    Proposition::Implies(_, _) => Err("Second row is not the antecedent"),
+                    _ => Err("Cited row is not an implication"),
+                }
+            }
+            (Rule::IntroIff, [left_sub, right_sub]) => {
+                let (left_l, left_start) = self.cited(upto, *left_sub)?;
+                let left_end = self
+                    .get_subproof_result(*left_sub, upto)
+                    .ok_or("Cited row is not a subproof")?;
+                let (right_l, right_start) = self.cited(upto, *right_sub)?;
+                let right_end = self
+                    .get_subproof_result(*right_sub, upto)
+                    .ok_or("Cited row is not a subproof")?;
+
+                if *left_l != level + 1 || *right_l != level + 1 {
+                    return Err("Subproofs are not directly inside the current level");
+                }
+
+                let (left_start, right_start) = (left_start.unwrap(), right_start.unwrap());
+                if !(left_start == right_end && left_end == right_start) {
+                    return Err("Subproofs don't prove each other's assumption");
+                }
+                Ok(Proposition::new_iff(left_start, right_start))
+            }
+            (Rule::ElimIff, [assum, truth]) => {
+                let imp = self.cited(upto, *assum)?.1.unwrap();
+                let truth_x = self.cited(upto, *truth)?.1.unwrap();
+                match imp.borrow() {
+                    Proposition::Iff(left, right) if left == truth_x => Ok(right.clone()),
+                    Proposition::Iff(left, right) if right == truth_x => Ok(left.clone()),
+                    Proposition::Iff(_, _) => Err("Second row is neither side of the equivalence"),
+                    _ => Err("Cited row is not a double implication"),
+                }
+            }
+            (Rule::IntroVerum, []) => Ok(Proposition::new_verum()),
+            (Rule::IntroXor, [row]) => match self.cited(upto, *row)?.1.unwrap().borrow() {
+                Proposition::Not(t) => match t.borrow() {
+                    Proposition::Iff(left, right) => Ok(Proposition::new_xor(left, right)),
+                    _ => Err("Cited row is not a negated double implication"),
+                },
+                _ => Err("Cited row is not a negated double implication"),
+            },
+            (Rule::ElimXor, [row]) => match self.cited(upto, *row)?.1.unwrap().borrow() {
+                Proposition::Xor(left, right) => {
+                    Ok(Proposition::new_not(&Proposition::new_iff(left, right)))
+                }
+                _ => Err("Cited row is not an exclusive or"),
+            },
+            (Rule::IntroNand, [row]) => match self.cited(upto, *row)?.1.unwrap().borrow() {
+                Proposition::Not(t) => match t.borrow() {
+                    Proposition::And(conjuncts) if conjuncts.len() == 2 => {
+                        Ok(Proposition::new_nand(&conjuncts[0], &conjuncts[1]))
+                    }
+                    _ => Err("Cited row is not a negated conjunction"),
+                },
+                _ => Err("Cited row is not a negated conjunction"),
+            },
+            (Rule::ElimNand, [row]) => match self.cited(upto, *row)?.1.unwrap().borrow() {
+                Proposition::Nand(left, right) => {
+                    Ok(Proposition::new_not(&Proposition::new_and(left, right)))
+                }
+                _ => Err("Cited row is not a NAND"),
+            },
+            (Rule::IntroNor, [row]) => match self.cited(upto, *row)?.1.unwrap().borrow() {
+                Proposition::Not(t) => match t.borrow() {
+                    Proposition::Or(disjuncts) if disjuncts.len() == 2 => {
+                        Ok(Proposition::new_nor(&disjuncts[0], &disjuncts[1]))
+                    }
+                    _ => Err("Cited row is not a negated disjunction"),
+                },
+                _ => Err("Cited row is not a negated disjunction"),
+            },
+            (Rule::ElimNor, [row]) => match self.cited(upto, *row)?.1.unwrap().borrow() {
+                Proposition::Nor(left, right) => {
+                    Ok(Proposition::new_not(&Proposition::new_or(left, right)))
+                }
+                _ => Err("Cited row is not a NOR"),
+            },
+            (Rule::Lemma(id), rows) => {
+                let cited = rows
+                    .iter()
+                    .map(|row| Ok(self.cited(upto, *row)?.1.unwrap()))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let lemma = Lemma::get(id).ok_or("Unknown lemma")?;
+                lemma.instantiate(&cited, prop)
+            }
+            (Rule::Custom(id), rows) => {
+                let rule = CustomRule::get(id).ok_or("Unknown rule")?;
+                if rows.len() != rule.premises.len() {
+                    return Err("Cite one row per premise of the rule");
+                }
+                let cited = rule
+                    .premises
+                    .iter()
+                    .zip(rows)
+                    .map(|(premise, row)| {
+                        let (l, start) = self.cited(upto, *row)?;
+                        match premise {
+                            Premise::Row(_) => Ok(Premise::Row(start.unwrap().clone())),
+                            Premise::Subproof(_, _) => {
+                                let end = self
+                                    .get_subproof_result(*row, upto)
+                                    .ok_or("Cited row is not a subproof")?;
+                                if *l != level + 1 {
+                                    return Err(
+                                        "Subproof is not directly inside the current level",
+                                    );
+                                }
+                                Ok(Premise::Subproof(start.unwrap().clone(), end.clone()))
+                            }
+                        }
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                rule.instantiate(&cited, prop)
+            }
+            _ => Err("Wrong number of cited rows for this rule"),
         }
     }
 
+    // The rules below add a deduction at the current level only if it holds, and return whether
+    // it did.
+
+    pub fn introduce_and(&mut self, conjuncts: &[usize]) -> bool {
+        self.deduce(Rule::IntroAnd, conjuncts.to_vec(), None)
+    }
+
+    pub fn eliminate_and(&mut self, assum: usize, new_exp: &Rc<Proposition>) -> bool {
+        self.deduce(Rule::ElimAnd, vec![assum], Some(new_exp))
+    }
+
+    pub fn reiterate(&mut self, row: usize) -> bool {
+        self.deduce(Rule::Reiter, vec![row], None)
+    }
+
+    pub fn introduce_or(&mut self, assum: usize, prop: &Rc<Proposition>) -> bool {
+        self.deduce(Rule::IntroOr, vec![assum], Some(prop))
+    }
+
     // This prolly has some bugs
-    fn get_subproof_result(&self, n: usize) -> Option<&Rc<Proposition>> {
+    fn get_subproof_result(&self, n: usize, upto: usize) -> Option<&Rc<Proposition>> {
         let start = match self.statements.get(n) {
-            Some((level, FitchComponent::Assumption(_))) => level,
+            Some((level, FitchComponent::Assumption(_))) if n < upto => level,
             _ => return None,
         };
 
-        for x in n + 1..self.statements.len() {
-            if x + 1 == self.statements.len() {
+        for x in n + 1..upto {
+            if x + 1 == upto {
                 return Some(self.statements.get(x).unwrap().1.unwrap());
             }
 
@@ -364,245 +1329,110 @@ impl Fitch {
         None
     }
 
-    pub fn eliminate_or(&mut self, assum: usize, left: usize, right: usize) -> bool {
-        if assum >= left || assum >= right {
-            return false;
-        }
-
-        let assum_x = match self.statements.get(assum) {
-            None => return false,
-            Some((_, v)) => v.unwrap(),
-        };
-
-        let left_a = match self.statements.get(left) {
-            None => return false,
-            Some((_, v)) => v.unwrap(),
-        };
-
-        let right_a = match self.statements.get(right) {
-            None => return false,
-            Some((_, v)) => v.unwrap(),
-        };
-
-        match assum_x.borrow() {
-            Proposition::Or(ll, rr) => {
-                if !(ll == left_a && rr == right_a) {
-                    return false;
-                }
-            }
-            _ => return false,
-        }
-
-        let left_sub = self.get_subproof_result(left);
-        let right_sub = self.get_subproof_result(right);
-        if left_sub.is_none() || left_sub != right_sub {
-            return false;
-        }
-        self.statements.push((
-            self.current_level,
-            FitchComponent::Deduction(
-                left_sub.unwrap().clone(),
-                Rule::ElimOr,
-                vec![assum, left, right],
-            ),
-        ));
-        true
+    /// Eliminates the disjunction in `assum` with one subproof for each of its disjuncts, in
+    /// order.
+    pub fn eliminate_or(&mut self, assum: usize, subproofs: &[usize]) -> bool {
+        let mut citations = vec![assum];
+        citations.extend(subproofs);
+        self.deduce(Rule::ElimOr, citations, None)
     }
 
     pub fn introduce_absurdum(&mut self, ass1: usize, ass2: usize) -> bool {
-        let a1 = match self.statements.get(ass1) {
-            None => return false,
-            Some(v) => v.1.unwrap(),
-        };
-        let a2 = match self.statements.get(ass2) {
-            None => return false,
-            Some(v) => v.1.unwrap(),
-        };
-
-        let n1 = Proposition::new_not(a1);
-        let n2 = Proposition::new_not(a2);
-        if !(&n1 == a2 || &n2 == a1) {
-            return false;
-        }
-
-        self.statements.push((
-            self.current_level,
-            FitchComponent::Deduction(
-                Proposition::new_absurdum(),
-                Rule::IntroAbs,
-                vec![ass1, ass2],
-            ),
-        ));
-        true
+        self.deduce(Rule::IntroAbs, vec![ass1, ass2], None)
     }
 
     pub fn eliminate_absurdum(&mut self, absurdum: usize, introduce: &Rc<Proposition>) -> bool {
-        match self.statements.get(absurdum) {
-            Some((level, m))
-                if m.unwrap() == &Proposition::new_absurdum() && *level <= self.current_level =>
-            {
-                self.statements.push((
-                    self.current_level,
-                    FitchComponent::Deduction(introduce.clone(), Rule::ElimAbs, vec![absurdum]),
-                ));
-                true
-            }
-            _ => false,
-        }
+        self.deduce(Rule::ElimAbs, vec![absurdum], Some(introduce))
     }
 
     pub fn introduce_not(&mut self, sub_proof: usize) -> bool {
-        match self.get_subproof_result(sub_proof) {
-            None => return false,
-            Some(v) => match v.borrow() {
-                Proposition::Absurdum => (),
-                _ => return false,
-            },
-        }
-
-        if self.current_level != self.statements.get(sub_proof).unwrap().0 - 1 {
-            return false;
-        }
-
-        let cur = self.statements.get(sub_proof).unwrap().1.unwrap();
-        self.statements.push((
-            self.current_level,
-            FitchComponent::Deduction(Proposition::new_not(cur), Rule::IntroNot, vec![sub_proof]),
-        ));
-        true
+        self.deduce(Rule::IntroNot, vec![sub_proof], None)
     }
 
     pub fn eliminate_not(&mut self, row: usize) -> bool {
-        let cur = match self.statements.get(row) {
-            None => return false,
-            Some((_, v)) => v.unwrap(),
-        };
+        self.deduce(Rule::ElimNot, vec![row], None)
+    }
 
-        let cur = match cur.borrow() {
-            Proposition::Not(v) => match v.borrow() {
-                Proposition::Not(b) => b,
-                _ => return false,
-            },
-            _ => return false,
-        };
+    pub fn introduce_implies(&mut self, sub_proof: usize) -> bool {
+        self.deduce(Rule::IntroImpl, vec![sub_proof], None)
+    }
 
-        self.statements.push((
-            self.current_level,
-            FitchComponent::Deduction(cur.clone(), Rule::ElimNot, vec![row]),
-        ));
-        true
+    pub fn eliminate_implies(&mut self, assum: usize, left: usize) -> bool {
+        self.deduce(Rule::ElimImpl, vec![assum, left], None)
     }
 
-    pub fn introduce_implies(&mut self, sub_proof: usize) -> bool {
-        let start = match self.statements.get(sub_proof) {
-            None => return false,
-            Some((_, v)) => v.unwrap(),
-        };
-        let end = match self.get_subproof_result(sub_proof) {
-            None => return false,
-            Some(v) => v,
-        };
+    pub fn introduce_iff(&mut self, left_sub: usize, right_sub: usize) -> bool {
+        self.deduce(Rule::IntroIff, vec![left_sub, right_sub], None)
+    }
 
-        if self.current_level != self.statements.get(sub_proof).unwrap().0 - 1 {
-            return false;
-        }
+    pub fn eliminate_iff(&mut self, assum: usize, truth: usize) -> bool {
+        self.deduce(Rule::ElimIff, vec![assum, truth], None)
+    }
 
-        self.statements.push((
-            self.current_level,
-            FitchComponent::Deduction(
-                Proposition::new_implies(start, end),
-                Rule::IntroImpl,
-                vec![sub_proof],
-            ),
-        ));
-        true
+    pub fn introduce_verum(&mut self) -> bool {
+        self.deduce(Rule::IntroVerum, Vec::new(), None)
     }
 
-    pub fn eliminate_implies(&mut self, assum: usize, left: usize) -> bool {
-        let assum_x = match self.statements.get(assum) {
-            None => return false,
-            Some((_, v)) => v.unwrap(),
-        };
-        let left_x = match self.statements.get(left) {
-            None => return false,
-            Some((_, v)) => v.unwrap(),
-        };
+    pub fn introduce_xor(&mut self, row: usize) -> bool {
+        self.deduce(Rule::IntroXor, vec![row], None)
+    }
 
-        match assum_x.borrow() {
-            Proposition::Implies(l, r) if left_x == l => self.statements.push((
-                self.current_level,
-                FitchComponent::Deduction(r.clone(), Rule::ElimImpl, vec![assum, left]),
-            )),
-            _ => return false,
-        }
-        true
+    pub fn eliminate_xor(&mut self, row: usize) -> bool {
+        self.deduce(Rule::ElimXor, vec![row], None)
     }
 
-    pub fn introduce_iff(&mut self, left_sub: usize, right_sub: usize) -> bool {
-        let left_start = match self.statements.get(left_sub) {
-            None => return false,
-            Some((_, v)) => v.unwrap(),
-        };
-        let left_end = match self.get_subproof_result(left_sub) {
-            None => return false,
-            Some(v) => v,
-        };
-        let right_start = match self.statements.get(right_sub) {
-            None => return false,
-            Some((_, v)) => v.unwrap(),
-        };
-        let right_end = match self.get_subproof_result(right_sub) {
-            None => return false,
-            Some(v) => v,
-        };
+    pub fn introduce_nand(&mut self, row: usize) -> bool {
+        self.deduce(Rule::IntroNand, vec![row], None)
+    }
 
-        if self.current_level != self.statements.get(left_sub).unwrap().0 - 1
-            || self.current_level != self.statements.get(right_sub).unwrap().0 - 1
-        {
-            return false;
-        }
+    pub fn eliminate_nand(&mut self, row: usize) -> bool {
+        self.deduce(Rule::ElimNand, vec![row], None)
+    }
 
-        if !(left_start == right_end && left_end == right_start) {
-            return false;
-        }
-        self.statements.push((
-            self.current_level,
-            FitchComponent::Deduction(
-                Proposition::new_iff(left_start, right_start),
-                Rule::IntroIff,
-                vec![left_sub, right_sub],
-            ),
-        ));
-        true
+    pub fn introduce_nor(&mut self, row: usize) -> bool {
+        self.deduce(Rule::IntroNor, vec![row], None)
     }
 
-    pub fn eliminate_iff(&mut self, assum: usize, truth: usize) -> bool {
-        let imp = match self.statements.get(assum) {
-            None => return false,
-            Some((_, v)) => v.unwrap(),
-        };
-        let truth_x = match self.statements.get(truth) {
-            None => return false,
-            Some((_, v)) => v.unwrap(),
-        };
+    pub fn eliminate_nor(&mut self, row: usize) -> bool {
+        self.deduce(Rule::ElimNor, vec![row], None)
+    }
 
-        let ris = match imp.borrow() {
-            Proposition::Iff(left, right) if left == truth_x => right,
-            Proposition::Iff(left, right) if right == truth_x => left,
-            _ => return false,
-        };
+    /// Derives the instance of the lemma cited by `lemma` whose premises are `rows`, in order.
+    /// `conclusion` is needed when the premises don't settle the instance.
+    pub fn apply_lemma(
+        &mut self,
+        lemma: Rule,
+        rows: &[usize],
+        conclusion: Option<&Rc<Proposition>>,
+    ) -> bool {
+        self.deduce(lemma, rows.to_vec(), conclusion)
+    }
 
-        self.statements.push((
-            self.current_level,
-            FitchComponent::Deduction(ris.clone(), Rule::ElimIff, vec![assum, truth]),
-        ));
-        true
+    /// Derives the instance of the custom rule cited by `rule` whose premises are `rows`, in
+    /// order, citing the first row of the subproof premises. `conclusion` is needed when the
+    /// premises don't settle the instance.
+    pub fn apply_custom(
+        &mut self,
+        rule: Rule,
+        rows: &[usize],
+        conclusion: Option<&Rc<Proposition>>,
+    ) -> bool {
+        self.deduce(rule, rows.to_vec(), conclusion)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Fitch, Proposition};
+    use super::{
+        Fitch, FitchComponent, Proposition, RowStatus, Rule, Styled, SymbolStyle, INTERNER,
+    };
+    use std::{collections::HashSet, rc::Rc};
+
+    #[test]
+    #[should_panic(expected = "two or more operands")]
+    fn single_conjunct() {
+        Proposition::new_conjunction(&[Proposition::new_term("A")]);
+    }
 
     #[test]
     fn introduce_and() {
@@ -611,7 +1441,7 @@ mod tests {
         let t1 = Proposition::new_term("B");
         fitch.add_assumption(&t0);
         fitch.add_assumption(&t1);
-        let ris = fitch.introduce_and(0, 1);
+        let ris = fitch.introduce_and(&[0, 1]);
         assert!(ris);
         assert_eq!(
             *fitch.statements.get(2).unwrap().1.unwrap(),
@@ -669,11 +1499,40 @@ mod tests {
         fitch.add_subproof(&t2);
         fitch.reiterate(0);
         fitch.end_subproof();
-        let ris = fitch.eliminate_or(1, 2, 4);
+        let ris = fitch.eliminate_or(1, &[2, 4]);
         assert!(ris);
         assert_eq!(fitch.statements.last().unwrap().1.unwrap(), &t0);
     }
 
+    #[test]
+    fn nary() {
+        let mut fitch = Fitch::new();
+        let a = Proposition::new_term("A");
+        let b = Proposition::new_term("B");
+        let c = Proposition::new_term("C");
+        let abc = [a.clone(), b.clone(), c.clone()];
+        abc.iter().for_each(|p| assert!(fitch.add_assumption(p)));
+        assert!(fitch.introduce_and(&[0, 1, 2]));
+        assert_eq!(
+            fitch.proposition(3),
+            Some(&Proposition::new_conjunction(&abc))
+        );
+        assert!(fitch.eliminate_and(3, &b));
+        assert!(!fitch.eliminate_and(3, &Proposition::new_and(&a, &b)));
+        assert!(fitch.introduce_or(4, &Proposition::new_disjunction(&abc)));
+        assert!(!fitch.introduce_nand(3));
+
+        for p in &abc {
+            fitch.add_subproof(p);
+            fitch.reiterate(1);
+            fitch.end_subproof();
+        }
+        assert!(!fitch.eliminate_or(5, &[6, 8]));
+        assert!(!fitch.eliminate_or(5, &[8, 6, 10]));
+        assert!(fitch.eliminate_or(5, &[6, 8, 10]));
+        assert_eq!(fitch.proposition(12), Some(&b));
+    }
+
     #[test]
     fn introduce_absurdum() {
         let mut fitch = Fitch::new();
@@ -728,6 +1587,50 @@ mod tests {
         assert_eq!(fitch.statements.last().unwrap().1.unwrap(), &t0);
     }
 
+    #[test]
+    fn subscripts() {
+        let p = Proposition::new_term("p12'3");
+        assert_eq!(Styled(&p, SymbolStyle::Unicode).to_string(), "p12'₃");
+        assert_eq!(Styled(&p, SymbolStyle::Ascii).to_string(), "p12'3");
+        let rain = Proposition::new_term("Rain");
+        assert_eq!(Styled(&rain, SymbolStyle::Unicode).to_string(), "Rain");
+    }
+
+    #[test]
+    fn gates() {
+        let mut fitch = Fitch::new();
+        let a = Proposition::new_term("A");
+        let b = Proposition::new_term("B");
+        fitch.add_assumption(&Proposition::new_not(&Proposition::new_iff(&a, &b)));
+        fitch.add_assumption(&Proposition::new_nand(&a, &b));
+        fitch.add_assumption(&Proposition::new_not(&Proposition::new_or(&a, &b)));
+        assert!(fitch.introduce_verum());
+        assert!(fitch.introduce_xor(0));
+        assert!(fitch.eliminate_xor(4));
+        assert_eq!(fitch.proposition(5), fitch.proposition(0));
+        assert!(fitch.eliminate_nand(1));
+        assert!(fitch.introduce_nand(6));
+        assert_eq!(fitch.proposition(7), fitch.proposition(1));
+        assert!(fitch.introduce_nor(2));
+        assert!(fitch.eliminate_nor(8));
+        assert_eq!(fitch.proposition(3), Some(&Proposition::new_verum()));
+        assert!(!fitch.introduce_nor(0));
+        assert!(!fitch.eliminate_xor(1));
+    }
+
+    #[test]
+    fn evaluate() {
+        let a = Proposition::new_term("A");
+        let b = Proposition::new_term("B");
+        let value = |name: &str| name == "A";
+        assert!(Proposition::new_xor(&a, &b).evaluate(&value));
+        assert!(Proposition::new_nand(&a, &b).evaluate(&value));
+        assert!(!Proposition::new_nor(&a, &b).evaluate(&value));
+        assert!(!Proposition::new_implies(&a, &b).evaluate(&value));
+        assert!(Proposition::new_iff(&b, &Proposition::new_absurdum()).evaluate(&value));
+        assert!(Proposition::new_or(&b, &Proposition::new_verum()).evaluate(&value));
+    }
+
     #[test]
     fn introduce_implies() {
         let mut fitch = Fitch::new();
@@ -791,4 +1694,129 @@ mod tests {
         assert!(ris);
         assert_eq!(fitch.statements.last().unwrap().1.unwrap(), &t1);
     }
+
+    #[test]
+    fn tentative_rows() {
+        let mut fitch = Fitch::new();
+        let t0 = Proposition::new_term("A");
+        let t1 = Proposition::new_term("B");
+        let and = Proposition::new_and(&t0, &t1);
+        fitch.add_assumption(&and);
+        fitch.add_tentative(&t0, Rule::ElimAnd, vec![0]);
+        fitch.add_tentative(&t1, Rule::ElimNot, vec![0]);
+        fitch.add_unjustified(&and);
+        fitch.add_tentative(&and, Rule::IntroAnd, vec![1, 4]);
+        assert_eq!(fitch.row_status(0), RowStatus::Valid);
+        assert_eq!(fitch.row_status(1), RowStatus::Valid);
+        assert!(matches!(fitch.row_status(2), RowStatus::Invalid(_)));
+        assert_eq!(fitch.row_status(3), RowStatus::Unjustified);
+        assert!(matches!(fitch.row_status(4), RowStatus::Invalid(_)));
+
+        assert!(fitch.justify(3, Rule::IntroAnd, vec![1, 2]));
+        assert_eq!(fitch.row_status(3), RowStatus::Valid);
+        assert!(!fitch.justify(0, Rule::Reiter, vec![0]));
+    }
+
+    #[test]
+    fn subformulas() {
+        let mut fitch = Fitch::new();
+        let t0 = Proposition::new_term("A");
+        let t1 = Proposition::new_term("B");
+        let imp = Proposition::new_implies(&t0, &t1);
+        let and = Proposition::new_and(&t1, &imp);
+        fitch.add_assumption(&t0);
+        fitch.add_assumption(&and);
+        assert_eq!(
+            fitch.subformulas(None),
+            vec![t0.clone(), and.clone(), t1.clone(), imp.clone()]
+        );
+        assert_eq!(fitch.subformulas(Some(1)), vec![and, t1, imp, t0]);
+    }
+
+    #[test]
+    fn copy_and_paste() {
+        let mut fitch = Fitch::new();
+        let t0 = Proposition::new_term("A");
+        let t1 = Proposition::new_term("B");
+        let and = Proposition::new_and(&t0, &t1);
+        fitch.add_assumption(&t1);
+        fitch.add_subproof(&t0);
+        fitch.reiterate(0);
+        fitch.introduce_and(&[1, 2]);
+        fitch.end_subproof();
+        fitch.introduce_implies(1);
+
+        let subproof = fitch.copy(1).unwrap();
+        assert!(fitch.copy(5).is_none());
+
+        let mut other = Fitch::new();
+        assert_eq!(other.paste(&fitch.copy(0).unwrap()), Some(0..1));
+        assert_eq!(other.paste(&subproof), Some(1..4));
+        assert_eq!(other.paste(&fitch.copy(2).unwrap()), Some(4..5));
+        assert_eq!(other.paste(&fitch.copy(4).unwrap()), Some(5..6));
+        assert_eq!(other.proposition(3), Some(&and));
+        let levels: Vec<usize> = other.statements().iter().map(|(l, _)| *l).collect();
+        assert_eq!(levels, [0, 1, 1, 1, 0, 0]);
+        assert!(other.opens_subproof(1));
+        assert!((0..6).all(|row| other.row_status(row) == RowStatus::Valid));
+        // The pasted implication discharges the pasted subproof
+        assert!(matches!(
+            &other.statements()[5],
+            (0, FitchComponent::Deduction(_, Rule::IntroImpl, citations)) if citations == &[1]
+        ));
+        assert_eq!(other.paste(&fitch.copy(0).unwrap()), None);
+
+        let mut flat = Fitch::new();
+        flat.add_assumption(&t1);
+        flat.paste(&fitch.copy(2).unwrap());
+        flat.statements
+            .push((0, FitchComponent::Assumption(t0.clone())));
+        assert!(matches!(flat.row_status(2), RowStatus::Invalid(_)));
+
+        // The reiterated row cites a premise that is different in the target proof.
+        let mut other = Fitch::new();
+        other.add_assumption(&t0);
+        other.paste(&subproof);
+        assert_eq!(other.row_status(2), RowStatus::Unjustified);
+        assert_eq!(other.row_status(3), RowStatus::Valid);
+
+        // Pasting into another tab: row 1 holds the B the reiteration needs, but it is not the
+        // premise the copy cited.
+        let mut source = Fitch::new();
+        source.add_assumption(&t0);
+        source.add_assumption(&t1);
+        source.reiterate(1);
+        let reiterated = source.copy(2).unwrap();
+        let mut target = Fitch::new();
+        target.add_assumption(&and);
+        target.eliminate_and(0, &t1);
+        assert_eq!(target.paste(&reiterated), Some(2..3));
+        assert_eq!(target.row_status(2), RowStatus::Unjustified);
+        let mut target = Fitch::new();
+        target.add_assumption(&t0);
+        target.add_assumption(&t1);
+        target.paste(&reiterated);
+        assert_eq!(target.row_status(2), RowStatus::Valid);
+    }
+
+    #[test]
+    fn interning() {
+        let a = Proposition::new_term("A");
+        let left = Proposition::new_and(&a, &Proposition::new_not(&a));
+        let right = Proposition::new_and(&Proposition::new_term("A"), &Proposition::new_not(&a));
+        assert!(Rc::ptr_eq(&left, &right));
+        assert_ne!(left, Proposition::new_or(&a, &Proposition::new_not(&a)));
+        let set: HashSet<Rc<Proposition>> = [left, right, a].into_iter().collect();
+        assert_eq!(set.len(), 2);
+
+        let interned = || INTERNER.with(|interner| interner.borrow().formulas.len());
+        let mut prop = Proposition::new_absurdum();
+        for i in 0..2000 {
+            prop = Proposition::new_implies(&Proposition::new_term(&i.to_string()), &prop);
+        }
+        assert!(interned() > 4000);
+        drop(prop);
+        INTERNER.with(|interner| interner.borrow_mut().sweep());
+        assert!(interned() < 10);
+    }
 }
@@ -0,0 +1,167 @@
+use crate::action::{key_label, Action, Key};
+use fitch::{custom::CustomRule, Rule};
+
+struct RuleHelp {
+    premises: &'static str,
+    select: &'static str,
+    example: &'static str,
+}
+
+fn rule_help(rule: Rule) -> RuleHelp {
+    let (premises, select, example) = match rule {
+        Rule::Lemma(_) => (
+            "instances of the lemma's premises",
+            "run `apply lemma`, type its name, then the rows matching its premises in order",
+            "(P => Q)  ⊢  ((~Q) => (~P))  with a lemma (A => B)  ⊢  ((~B) => (~A))",
+        ),
+        Rule::Custom(_) => (
+            "instances of the rule's premises, rows or subproofs",
+            "the rows matching its premises in order, the first row for a subproof",
+            "(P | Q), (~P)  ⊢  Q  with a rule (φ | ψ), (~φ)  ⊢  ψ",
+        ),
+        Rule::Reiter => (
+            "any row of the current or an enclosing subproof",
+            "the row to copy",
+            "A  ⊢  A",
+        ),
+        Rule::IntroAbs => (
+            "an expression and its negation",
+            "the two rows, in any order",
+            "A, (~A)  ⊢  ⊥",
+        ),
+        Rule::ElimAbs => (
+            "an absurdum",
+            "the absurdum, then type the expression to introduce",
+            "⊥  ⊢  B",
+        ),
+        Rule::IntroAnd => (
+            "every conjunct",
+            "the conjuncts in order, e.g. 0, 1, 2",
+            "A, B, C  ⊢  (A & B & C)",
+        ),
+        Rule::ElimAnd => (
+            "a conjunction",
+            "the conjunction, then the position of the conjunct",
+            "(A & B & C)  ⊢  B",
+        ),
+        Rule::IntroOr => (
+            "one disjunct of the disjunction",
+            "the disjunct, then type the whole disjunction",
+            "B  ⊢  (A | B | C)",
+        ),
+        Rule::ElimOr => (
+            "a disjunction and a subproof from each disjunct to the same conclusion",
+            "the disjunction, then the first row of each subproof in order",
+            "(A | B), [A ... C], [B ... C]  ⊢  C",
+        ),
+        Rule::IntroNot => (
+            "a subproof ending in an absurdum",
+            "the first row of the subproof",
+            "[A ... ⊥]  ⊢  (~A)",
+        ),
+        Rule::ElimNot => ("a double negation", "the double negation", "(~(~A))  ⊢  A"),
+        Rule::IntroImpl => (
+            "a subproof from the antecedent to the consequent",
+            "the first row of the subproof",
+            "[A ... B]  ⊢  (A => B)",
+        ),
+        Rule::ElimImpl => (
+            "an implication and its antecedent",
+            "the implication, then the antecedent",
+            "(A => B), A  ⊢  B",
+        ),
+        Rule::IntroIff => (
+            "a subproof from each side to the other",
+            "the first row of the left subproof, then of the right one",
+            "[A ... B], [B ... A]  ⊢  (A <=> B)",
+        ),
+        Rule::ElimIff => (
+            "a double implication and one of its sides",
+            "the double implication, then the side that holds",
+            "(A <=> B), A  ⊢  B",
+        ),
+        Rule::IntroVerum => ("nothing", "nothing, ⊤ is added right away", "⊢  ⊤"),
+        Rule::IntroXor => (
+            "the negation of a double implication",
+            "the negated double implication",
+            "(~(A <=> B))  ⊢  (A ^ B)",
+        ),
+        Rule::ElimXor => (
+            "an exclusive or",
+            "the exclusive or",
+            "(A ^ B)  ⊢  (~(A <=> B))",
+        ),
+        Rule::IntroNand => (
+            "a negated conjunction",
+            "the negated conjunction",
+            "(~(A & B))  ⊢  (A ~& B)",
+        ),
+        Rule::ElimNand => ("a NAND", "the NAND", "(A ~& B)  ⊢  (~(A & B))"),
+        Rule::IntroNor => (
+            "a negated disjunction",
+            "the negated disjunction",
+            "(~(A | B))  ⊢  (A ~| B)",
+        ),
+        Rule::ElimNor => ("a NOR", "the NOR", "(A ~| B)  ⊢  (~(A | B))"),
+    };
+    RuleHelp {
+        premises,
+        select,
+        example,
+    }
+}
+
+/// The content of the help overlay.
+pub fn help_text(normal_keys: &[(Key, Action)]) -> Vec<String> {
+    let mut res = vec!["Keys".to_string()];
+    normal_keys
+        .iter()
+        .for_each(|(key, action)| res.push(format!("  {}", key_label(*key, action.name()))));
+    res.push("  Up/Down: move the focused row".to_string());
+    res.push("  Themes: run `dark`, `light`, `high-contrast` or `monochrome theme`".to_string());
+    res.push(
+        "  Normal forms: run `convert to negation normal form`, `... conjunctive ...` and so on"
+            .to_string(),
+    );
+    res.push(String::new());
+    res.push("Expression box".to_string());
+    [
+        "  Enter: confirm   Esc: cancel   Tab/Shift-Tab: complete a subformula",
+        "  Home/End, Ctrl-A/E, Ctrl-Left/Right: move   Delete, Ctrl-W/Alt-Backspace: delete",
+        "  Up/Down: previously entered expressions",
+        "  Syntax: A, #, %, (~A), (A & B), (A | B), (A => B), (A <=> B), (A ^ B), (A ~& B),",
+        "    (A ~| B)",
+        "  Unicode works too: ⊥, ⊤, (¬A), (A ∧ B), (A ∨ B), (A → B), (A ↔ B), (A ⊕ B), (A ↑ B),",
+        "    (A ↓ B)",
+        "  Atoms are a letter followed by letters, digits, _ and ', e.g. P, Rain, p_2 or q'",
+        "  Metavariables for rule schemata are Greek letters or ? and a name, e.g. φ, ψ₁ or ?p",
+    ]
+    .iter()
+    .for_each(|l| res.push(l.to_string()));
+
+    // Lemmas are cited by name, so their symbol is spelled out instead of looked up
+    let rules = Rule::ALL.iter().map(|rule| (*rule, rule.symbol()));
+    rules
+        .chain([
+            (Rule::Lemma(0), "Lemma <name>".to_string()),
+            (Rule::Custom(0), "<name>".to_string()),
+        ])
+        .for_each(|(rule, symbol)| {
+            let help = rule_help(rule);
+            res.push(String::new());
+            res.push(format!("{}  ({})", rule.name(), symbol));
+            res.push(format!("  premises: {}", help.premises));
+            res.push(format!("  select:   {}", help.select));
+            res.push(format!("  example:  {}", help.example));
+        });
+
+    let custom = CustomRule::all();
+    if !custom.is_empty() {
+        res.push(String::new());
+        res.push("Custom rules".to_string());
+        custom
+            .into_iter()
+            .for_each(|(_, rule)| res.push(format!("  {}", rule)));
+    }
+    res
+}
@@ -0,0 +1,370 @@
+//! Proofs as JSON, for tools that grade or analyse them. A proof file looks like
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "rules": "fitch",
+//!   "goal": { "implies": [{ "atom": "A" }, { "atom": "A" }] },
+//!   "rows": [
+//!     { "kind": "assumption", "level": 1, "formula": { "atom": "A" } },
+//!     { "kind": "deduction", "level": 1, "formula": { "atom": "A" },
+//!       "rule": "reiterate", "citations": [0] },
+//!     { "kind": "deduction", "level": 0,
+//!       "formula": { "implies": [{ "atom": "A" }, { "atom": "A" }] },
+//!       "rule": "introduce implies", "citations": [0] }
+//!   ]
+//! }
+//! ```
+//!
+//! Formulas are `"absurdum"`, `"verum"`, `{ "atom": name }`, `{ "meta": name }` for a
+//! metavariable, `{ "not": formula }`, `and` or `or` holding a list of two or more formulas, or
+//! one of `implies`, `iff`, `xor`, `nand` and `nor` holding a pair of formulas. Rows are
+//! `premise` (no level), `assumption`, `deduction` or `unjustified`, rules are named as in
+//! [`Rule::name`], `lemma <name>` for a registered [`Lemma`] or `custom <name>` for a registered
+//! [`CustomRule`], and citations are 0-based row numbers. `goal` may be left out.
+//!
+//! Files carry the version of the schema they were written with. Changes to the schema bump
+//! [`VERSION`] and keep [`load`] reading the older versions.
+
+use crate::{
+    custom::CustomRule,
+    fitch::{Fitch, FitchComponent, Proposition, Rule, RULE_SYSTEM},
+    lemma::Lemma,
+};
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+use std::rc::Rc;
+
+pub const VERSION: u32 = 1;
+
+/// A proof along with what it sets out to prove.
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct Document {
+    pub version: u32,
+    pub rules: String,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_goal"
+    )]
+    pub goal: Option<Rc<Proposition>>,
+    pub rows: Fitch,
+}
+
+impl Document {
+    pub fn new(proof: Fitch, goal: Option<Rc<Proposition>>) -> Document {
+        Document {
+            version: VERSION,
+            rules: RULE_SYSTEM.to_string(),
+            goal,
+            rows: proof,
+        }
+    }
+}
+
+pub fn save(document: &Document) -> String {
+    serde_json::to_string_pretty(document).unwrap()
+}
+
+pub fn load(text: &str) -> Result<Document, String> {
+    let value: serde_json::Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    let version = value
+        .get("version")
+        .and_then(serde_json::Value::as_u64)
+        .ok_or("missing schema version")?;
+    let document: Document = match version {
+        1 => serde_json::from_value(value).map_err(|e| e.to_string())?,
+        v => return Err(format!("unsupported schema version {}", v)),
+    };
+    if document.rules != RULE_SYSTEM {
+        return Err(format!("unknown rule system `{}`", document.rules));
+    }
+    Ok(Document {
+        version: VERSION,
+        ..document
+    })
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Formula {
+    Absurdum,
+    Atom(String),
+    Not(Box<Formula>),
+    And(Vec<Formula>),
+    Or(Vec<Formula>),
+    Implies(Box<Formula>, Box<Formula>),
+    Iff(Box<Formula>, Box<Formula>),
+    Verum,
+    Xor(Box<Formula>, Box<Formula>),
+    Nand(Box<Formula>, Box<Formula>),
+    Nor(Box<Formula>, Box<Formula>),
+    Meta(String),
+}
+
+impl From<&Proposition> for Formula {
+    fn from(prop: &Proposition) -> Formula {
+        let pair = |l: &Proposition, r: &Proposition| (Box::new(l.into()), Box::new(r.into()));
+        match prop {
+            Proposition::Absurdum => Formula::Absurdum,
+            Proposition::Term(name) => Formula::Atom(name.clone()),
+            Proposition::Not(p) => Formula::Not(Box::new(p.as_ref().into())),
+            Proposition::And(conjuncts) => {
+                Formula::And(conjuncts.iter().map(|p| p.as_ref().into()).collect())
+            }
+            Proposition::Or(disjuncts) => {
+                Formula::Or(disjuncts.iter().map(|p| p.as_ref().into()).collect())
+            }
+            Proposition::Implies(l, r) => {
+                let (l, r) = pair(l, r);
+                Formula::Implies(l, r)
+            }
+            Proposition::Iff(l, r) => {
+                let (l, r) = pair(l, r);
+                Formula::Iff(l, r)
+            }
+            Proposition::Verum => Formula::Verum,
+            Proposition::Xor(l, r) => {
+                let (l, r) = pair(l, r);
+                Formula::Xor(l, r)
+            }
+            Proposition::Nand(l, r) => {
+                let (l, r) = pair(l, r);
+                Formula::Nand(l, r)
+            }
+            Proposition::Nor(l, r) => {
+                let (l, r) = pair(l, r);
+                Formula::Nor(l, r)
+            }
+            Proposition::Meta(name) => Formula::Meta(name.clone()),
+        }
+    }
+}
+
+impl Formula {
+    fn proposition(&self) -> Result<Rc<Proposition>, &'static str> {
+        let pair = |l: &Formula, r: &Formula| Ok::<_, &str>((l.proposition()?, r.proposition()?));
+        let operands = |operands: &[Formula]| -> Result<Vec<_>, _> {
+            match operands {
+                [_, _, ..] => operands.iter().map(Formula::proposition).collect(),
+                _ => Err("conjunctions and disjunctions need at least two operands"),
+            }
+        };
+        let prop = match self {
+            Formula::Absurdum => Proposition::new_absurdum(),
+            Formula::Atom(name) => Proposition::new_term(name),
+            Formula::Meta(name) => Proposition::new_meta(name),
+            Formula::Not(p) => Proposition::new_not(&p.proposition()?),
+            Formula::And(conjuncts) => Proposition::new_conjunction(&operands(conjuncts)?),
+            Formula::Or(disjuncts) => Proposition::new_disjunction(&operands(disjuncts)?),
+            Formula::Implies(l, r) => {
+                let (l, r) = pair(l, r)?;
+                Proposition::new_implies(&l, &r)
+            }
+            Formula::Iff(l, r) => {
+                let (l, r) = pair(l, r)?;
+                Proposition::new_iff(&l, &r)
+            }
+            Formula::Verum => Proposition::new_verum(),
+            Formula::Xor(l, r) => {
+                let (l, r) = pair(l, r)?;
+                Proposition::new_xor(&l, &r)
+            }
+            Formula::Nand(l, r) => {
+                let (l, r) = pair(l, r)?;
+                Proposition::new_nand(&l, &r)
+            }
+            Formula::Nor(l, r) => {
+                let (l, r) = pair(l, r)?;
+                Proposition::new_nor(&l, &r)
+            }
+        };
+        Ok(prop)
+    }
+}
+
+impl Serialize for Proposition {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Formula::from(self).serialize(serializer)
+    }
+}
+
+// Formulas are only read behind an `Rc`, built with the constructors so they are interned.
+fn deserialize_goal<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Rc<Proposition>>, D::Error> {
+    Option::<Formula>::deserialize(deserializer)?
+        .map(|formula| formula.proposition().map_err(D::Error::custom))
+        .transpose()
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+enum Row {
+    Premise {
+        formula: Formula,
+    },
+    Assumption {
+        level: usize,
+        formula: Formula,
+    },
+    Deduction {
+        level: usize,
+        formula: Formula,
+        rule: String,
+        citations: Vec<usize>,
+    },
+    Unjustified {
+        level: usize,
+        formula: Formula,
+    },
+}
+
+/// How a row names `rule`, `None` for a lemma or custom rule that is not registered.
+fn rule_name(rule: &Rule) -> Option<String> {
+    Some(match rule {
+        Rule::Lemma(id) => format!("lemma {}", Lemma::get(*id)?.name),
+        Rule::Custom(id) => format!("custom {}", CustomRule::get(*id)?.name),
+        rule => rule.name().to_string(),
+    })
+}
+
+impl Serialize for Fitch {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let rows: Vec<Row> = self
+            .statements()
+            .iter()
+            .enumerate()
+            .map(|(i, (level, comp))| {
+                let level = *level;
+                let formula = comp.unwrap().as_ref().into();
+                match comp {
+                    FitchComponent::Assumption(_) if i < self.start_of_deductions() => {
+                        Row::Premise { formula }
+                    }
+                    FitchComponent::Assumption(_) => Row::Assumption { level, formula },
+                    // A lemma or custom rule that is not registered is saved as unjustified
+                    FitchComponent::Deduction(_, rule, citations) => match rule_name(rule) {
+                        Some(rule) => Row::Deduction {
+                            level,
+                            formula,
+                            rule,
+                            citations: citations.clone(),
+                        },
+                        None => Row::Unjustified { level, formula },
+                    },
+                    FitchComponent::Unjustified(_) => Row::Unjustified { level, formula },
+                }
+            })
+            .collect();
+        rows.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Fitch {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Fitch, D::Error> {
+        let mut fitch = Fitch::new();
+        let mut level = 0;
+        for (i, row) in Vec::<Row>::deserialize(deserializer)?
+            .into_iter()
+            .enumerate()
+        {
+            let error = |message: &str| D::Error::custom(format!("row {}: {}", i, message));
+            let (target, formula) = match &row {
+                Row::Assumption { level: 0, .. } => {
+                    return Err(error("subproof assumptions need a level above 0"))
+                }
+                Row::Premise { formula } => {
+                    if !fitch.add_assumption(&formula.proposition().map_err(error)?) {
+                        return Err(error("premises have to come first"));
+                    }
+                    continue;
+                }
+                Row::Assumption { level, formula } => (level - 1, formula),
+                Row::Deduction { level, formula, .. } | Row::Unjustified { level, formula } => {
+                    (*level, formula)
+                }
+            };
+            if target > level {
+                return Err(error("rows can only open one subproof at a time"));
+            }
+            (target..level).for_each(|_| fitch.end_subproof());
+            level = target;
+            let prop = formula.proposition().map_err(error)?;
+            match row {
+                Row::Assumption { .. } => {
+                    fitch.add_subproof(&prop);
+                    level += 1;
+                }
+                Row::Deduction {
+                    rule, citations, ..
+                } => {
+                    let rule = if let Some(name) = rule.strip_prefix("lemma ") {
+                        Lemma::find(name)
+                    } else if let Some(name) = rule.strip_prefix("custom ") {
+                        CustomRule::find(name)
+                    } else {
+                        Rule::ALL.into_iter().find(|r| r.name() == rule)
+                    }
+                    .ok_or_else(|| error(&format!("unknown rule `{}`", rule)))?;
+                    fitch.add_tentative(&prop, rule, citations);
+                }
+                _ => fitch.add_unjustified(&prop),
+            }
+        }
+        Ok(fitch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{load, save, Document};
+    use crate::fitch::{Fitch, Proposition};
+
+    #[test]
+    fn round_trip() {
+        let a = Proposition::new_term("A");
+        let b = Proposition::new_term("B");
+        let mut fitch = Fitch::new();
+        fitch.add_assumption(&b);
+        fitch.add_subproof(&a);
+        fitch.add_subproof(&Proposition::new_absurdum());
+        fitch.reiterate(1);
+        fitch.end_subproof();
+        fitch.end_subproof();
+        fitch.introduce_implies(1);
+        fitch.add_unjustified(&Proposition::new_disjunction(&[
+            a.clone(),
+            Proposition::new_not(&b),
+            Proposition::new_verum(),
+        ]));
+
+        let document = Document::new(fitch, Some(Proposition::new_implies(&a, &a)));
+        let text = save(&document);
+        assert!(text.contains(r#""kind": "premise""#));
+        assert!(text.contains(r#""rule": "introduce implies""#));
+        assert!(load(&text).unwrap() == document);
+    }
+
+    #[test]
+    fn versions() {
+        let v1 = r#"{ "version": 1, "rules": "fitch", "rows": [
+            { "kind": "assumption", "level": 1, "formula": { "atom": "A" } },
+            { "kind": "deduction", "level": 0, "formula": { "implies": [{ "atom": "A" }, { "atom": "A" }] },
+              "rule": "introduce implies", "citations": [0] }
+        ] }"#;
+        let document = load(v1).unwrap();
+        assert_eq!(document.rows.len(), 2);
+        assert!(document.goal.is_none());
+
+        assert!(load(&v1.replace(r#""version": 1"#, r#""version": 99"#)).is_err());
+        assert!(load(&v1.replace(r#""version": 1,"#, "")).is_err());
+        assert!(load(&v1.replace(r#""fitch""#, r#""natural deduction""#)).is_err());
+        assert!(load(&v1.replace(r#""level": 1"#, r#""level": 2"#)).is_err());
+        assert!(load(&v1.replace("introduce implies", "modus ponens")).is_err());
+        let single = v1.replace(
+            r#"{ "implies": [{ "atom": "A" }, { "atom": "A" }] }"#,
+            r#"{ "and": [{ "atom": "A" }] }"#,
+        );
+        assert!(load(&single).is_err());
+    }
+}
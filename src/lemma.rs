@@ -0,0 +1,240 @@
+//! Theorems proved once and reused as a single step of later proofs. A [`Lemma`] is the sequent
+//! a finished proof establishes, `premises ⊢ conclusion`, and stands for all its substitution
+//! instances: its atoms are placeholders for any formula, so a lemma proved for `(A => B)` also
+//! justifies `((P & Q) => (~R))`.
+//!
+//! Lemmas are registered on the thread they are used on and cited as [`Rule::Lemma`]. A
+//! library of lemmas is kept in a text file, one per line:
+//!
+//! ```text
+//! contraposition: (A => B) |- ((~B) => (~A))
+//! excluded-middle: |- (A | (~A))
+//! ```
+
+use crate::{
+    fitch::{Fitch, Proposition, RowStatus, Rule},
+    schema::{valid_name, Sequent},
+};
+use std::{cell::RefCell, fmt::Display, rc::Rc};
+
+thread_local! {
+    static LEMMAS: RefCell<Vec<Rc<Lemma>>> = const { RefCell::new(Vec::new()) };
+}
+
+#[derive(PartialEq, Debug)]
+pub struct Lemma {
+    pub name: String,
+    pub sequent: Sequent,
+}
+
+impl Lemma {
+    /// The sequent proved by `proof`, which has to be finished: every row holds and the last one
+    /// is outside of any subproof.
+    pub fn from_proof(name: &str, proof: &Fitch) -> Result<Lemma, String> {
+        if !valid_name(name) {
+            return Err(format!(
+                "`{}` is not a valid lemma name, use a letter followed by letters, digits, _ or -",
+                name
+            ));
+        }
+        let (level, last) = proof.statements().last().ok_or("The proof is empty")?;
+        if *level != 0 {
+            return Err("The proof ends inside a subproof".to_string());
+        }
+        if let Some(row) = (0..proof.len()).find(|row| proof.row_status(*row) != RowStatus::Valid) {
+            return Err(format!("Row {} does not hold", row));
+        }
+        Ok(Lemma {
+            name: name.to_string(),
+            sequent: Sequent {
+                premises: proof.statements()[..proof.start_of_deductions()]
+                    .iter()
+                    .map(|(_, comp)| comp.unwrap().clone())
+                    .collect(),
+                conclusion: last.unwrap().clone(),
+            },
+        })
+    }
+
+    /// Makes the lemma available to proofs on this thread, returning the rule that cites it.
+    /// Registering the same lemma again returns the same rule.
+    pub fn register(self) -> Rule {
+        LEMMAS.with(|lemmas| {
+            let mut lemmas = lemmas.borrow_mut();
+            match lemmas.iter().position(|l| **l == self) {
+                Some(id) => Rule::Lemma(id),
+                None => {
+                    lemmas.push(Rc::new(self));
+                    Rule::Lemma(lemmas.len() - 1)
+                }
+            }
+        })
+    }
+
+    /// The last lemma registered under `name`.
+    pub fn find(name: &str) -> Option<Rule> {
+        LEMMAS.with(|lemmas| {
+            lemmas
+                .borrow()
+                .iter()
+                .rposition(|l| l.name == name)
+                .map(Rule::Lemma)
+        })
+    }
+
+    /// The lemma cited by [`Rule::Lemma`]`(id)`, if there is one registered under `id`.
+    pub fn get(id: usize) -> Option<Rc<Lemma>> {
+        LEMMAS.with(|lemmas| lemmas.borrow().get(id).cloned())
+    }
+
+    /// Whether the cited premises alone settle the conclusion, that is every atom of the
+    /// conclusion appears in some premise.
+    pub fn determines_conclusion(&self) -> bool {
+        self.sequent.generalize().determines_conclusion()
+    }
+
+    /// Checks that `cited` are instances of the premises, in order, under a single substitution
+    /// and returns the matching instance of the conclusion. `conclusion` is the instance the user
+    /// chose, needed when the premises don't settle it.
+    pub fn instantiate(
+        &self,
+        cited: &[&Rc<Proposition>],
+        conclusion: Option<&Rc<Proposition>>,
+    ) -> Result<Rc<Proposition>, &'static str> {
+        self.sequent.generalize().instance(cited, conclusion)
+    }
+}
+
+impl Display for Lemma {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.name, self.sequent)
+    }
+}
+
+/// Reads a library of lemmas, one `name: premise, ... |- conclusion` per line. `⊢` can be used
+/// in place of `|-`, blank lines and lines starting with `#` are skipped.
+pub fn parse_library(text: &str) -> Result<Vec<Lemma>, String> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(n, line)| parse_lemma(line).map_err(|e| format!("line {}: {}", n + 1, e)))
+        .collect()
+}
+
+fn parse_lemma(line: &str) -> Result<Lemma, &'static str> {
+    let (name, sequent) = line.split_once(':').ok_or("missing `:` after the name")?;
+    let name = name.trim();
+    if !valid_name(name) {
+        return Err("invalid lemma name");
+    }
+    Ok(Lemma {
+        name: name.to_string(),
+        sequent: Sequent::parse(sequent)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_library, Lemma};
+    use crate::{
+        fitch::{Fitch, RowStatus, Rule, SymbolStyle},
+        parser::{expr, parse_justification},
+    };
+
+    fn contraposition() -> Lemma {
+        let mut proof = Fitch::new();
+        proof.add_assumption(&expr("(A => B)"));
+        proof.add_subproof(&expr("(~B)"));
+        proof.add_subproof(&expr("A"));
+        proof.eliminate_implies(0, 2);
+        proof.reiterate(1);
+        proof.introduce_absurdum(3, 4);
+        proof.end_subproof();
+        proof.introduce_not(2);
+        proof.end_subproof();
+        proof.introduce_implies(1);
+        Lemma::from_proof("contraposition", &proof).unwrap()
+    }
+
+    #[test]
+    fn from_proof() {
+        let lemma = contraposition();
+        assert_eq!(
+            lemma.to_string(),
+            "contraposition: (A => B) |- ((~B) => (~A))"
+        );
+        assert!(lemma.determines_conclusion());
+
+        let mut proof = Fitch::new();
+        assert!(Lemma::from_proof("empty", &proof).is_err());
+        proof.add_subproof(&expr("A"));
+        assert!(Lemma::from_proof("open", &proof).is_err());
+        proof.end_subproof();
+        proof.add_tentative(&expr("B"), Rule::Reiter, vec![0]);
+        assert!(Lemma::from_proof("invalid", &proof).is_err());
+        assert!(Lemma::from_proof("1st", &Fitch::new()).is_err());
+    }
+
+    #[test]
+    fn instantiate() {
+        let rule = contraposition().register();
+        assert_eq!(contraposition().register(), rule);
+        assert_eq!(Lemma::find("contraposition"), Some(rule));
+
+        let mut proof = Fitch::new();
+        proof.add_assumption(&expr("((P & Q) => (~R))"));
+        proof.add_assumption(&expr("(P => P)"));
+        assert!(proof.apply_lemma(rule, &[0], None));
+        assert_eq!(proof.proposition(2), Some(&expr("((~(~R)) => (~(P & Q)))")));
+        assert!(!proof.apply_lemma(rule, &[0, 1], None));
+        assert!(!proof.apply_lemma(rule, &[2], Some(&expr("((~R) => (~P))"))));
+
+        proof.add_tentative(&expr("((~P) => (~P))"), rule, vec![1]);
+        assert_eq!(proof.row_status(3), RowStatus::Valid);
+        assert_eq!(
+            proof.layout(SymbolStyle::Ascii)[3].justification,
+            "Lemma contraposition [1]"
+        );
+        assert_eq!(
+            parse_justification("Lemma contraposition 1"),
+            Some((rule, vec![1]))
+        );
+        assert_eq!(parse_justification("Lemma unknown 1"), None);
+
+        let excluded = parse_library("excluded-middle: ⊢ (A | (~A))")
+            .unwrap()
+            .pop()
+            .unwrap();
+        assert!(!excluded.determines_conclusion());
+        let rule = excluded.register();
+        assert!(!proof.apply_lemma(rule, &[], None));
+        assert!(proof.apply_lemma(rule, &[], Some(&expr("((P & Q) | (~(P & Q)))"))));
+
+        // Made-up ids don't panic
+        let (unknown, custom) = (Rule::Lemma(usize::MAX), Rule::Custom(usize::MAX));
+        assert!(Lemma::get(usize::MAX).is_none());
+        proof.add_tentative(&expr("P"), unknown, vec![0]);
+        proof.add_tentative(&expr("P"), custom, vec![0]);
+        let last = proof.len() - 1;
+        assert_eq!(
+            proof.row_status(last - 1),
+            RowStatus::Invalid("Unknown lemma")
+        );
+        assert_eq!(proof.row_status(last), RowStatus::Invalid("Unknown rule"));
+        assert_eq!(unknown.symbol(), "Lemma ?");
+    }
+
+    #[test]
+    fn library() {
+        let text =
+            "# course lemmas\n\ncontraposition: (A => B) |- ((~B) => (~A))\nmp: (A => B), A ⊢ B\n";
+        let lemmas = parse_library(text).unwrap();
+        assert_eq!(lemmas.len(), 2);
+        assert_eq!(lemmas[0], contraposition());
+        assert_eq!(lemmas[1].to_string(), "mp: (A => B), A |- B");
+        assert!(parse_library("mp (A => B) |- B").is_err());
+        assert!(parse_library("mp: (A => B) A |- B").is_err());
+        assert!(parse_library("mp: (A => B) |- B C").is_err());
+        assert!(parse_library("m p: A |- A").is_err());
+    }
+}
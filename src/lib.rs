@@ -0,0 +1,43 @@
+//! Fitch-style natural deduction proofs for propositional logic: building them, checking them,
+//! reading them and exporting them. The proof assistant TUI is built on top of this crate and
+//! needs the `tui` feature; everything here works with `default-features = false`.
+//!
+//! ```
+//! use fitch::{parser::parse_proof, RowStatus};
+//!
+//! let proof = parse_proof(
+//!     " 0 | (A & B)
+//! ------------------
+//!  1 | A           E & [0]
+//!  2 | (B & A)     I & [1, 0]",
+//! )
+//! .unwrap();
+//! assert_eq!(proof.row_status(1), RowStatus::Valid);
+//! assert!(matches!(proof.row_status(2), RowStatus::Invalid(_)));
+//! ```
+//!
+//! [`Fitch`] holds a proof row by row and [`Fitch::row_status`] checks a row against the ones
+//! above it. Proofs can be built with its methods, read with [`parser::parse_proof`] and
+//! [`carnap::import`] or, with the `json` feature, [`json::load`], and written out with
+//! [`export::export`].
+//!
+//! For the logic itself, [`normal`] converts formulas to normal forms, [`sat`] decides
+//! satisfiability and entailment, and [`dimacs`] exchanges clauses with other SAT solvers.
+
+pub mod carnap;
+pub mod custom;
+pub mod dimacs;
+pub mod export;
+mod fitch;
+#[cfg(feature = "json")]
+pub mod json;
+pub mod lemma;
+pub mod normal;
+pub mod parser;
+pub mod sat;
+pub mod schema;
+
+pub use fitch::{
+    Fitch, FitchComponent, Fragment, ProofLine, Proposition, RowLayout, RowStatus, Rule, Styled,
+    SymbolStyle, RULE_SYSTEM,
+};
@@ -1,12 +1,38 @@
+mod action;
 mod app;
-mod fitch;
-mod parser;
+mod cli;
+mod clipboard;
+mod config;
+mod editor;
+mod help;
 mod state;
+mod theme;
 mod ui;
 
 use app::App;
+use config::Config;
 
 fn main() {
-    let mut app = App::new().unwrap();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Err(e) = Config::load_lemmas().and_then(|_| Config::load_rules()) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+    if !args.is_empty() {
+        if let Err(e) = cli::run(&args) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let config = match Config::load() {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    let mut app = App::new(config).unwrap();
     app.listen();
 }
@@ -0,0 +1,685 @@
+//! Normal forms: implication-free form, negation normal form (NNF), conjunctive normal form
+//! (CNF) and disjunctive normal form (DNF). [`trace`] rewrites a formula one law at a time,
+//! outermost first, so every intermediate formula can be shown along with the law applied:
+//!
+//! ```
+//! use fitch::{normal::{trace, Form, Law}, parser::{parse_expression, Result}};
+//!
+//! let Result::Success(prop, _) = parse_expression("(~(A => B))") else { panic!() };
+//! let steps = trace(&prop, Form::Negation).unwrap();
+//! assert_eq!(steps[0].law, Law::Implication);
+//! assert_eq!(steps.last().unwrap().result.to_string(), "(A & (~B))");
+//! ```
+//!
+//! Distribution can blow a formula up exponentially. [`tseitin`] gives a CNF linear in the size
+//! of the input instead, which is satisfiable exactly when the input is.
+
+use crate::fitch::Proposition;
+use std::{borrow::Borrow, collections::HashMap, fmt::Display, rc::Rc};
+
+/// How many rewriting steps [`trace`] takes before giving up.
+pub const STEP_LIMIT: usize = 500;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Form {
+    /// Only `¬`, `∧` and `∨` left, besides atoms and constants.
+    ImplicationFree,
+    /// Implication-free, with negations on atoms only.
+    Negation,
+    /// A conjunction of disjunctions of literals.
+    Conjunctive,
+    /// A disjunction of conjunctions of literals.
+    Disjunctive,
+}
+
+impl Form {
+    pub const ALL: [Form; 4] = [
+        Form::ImplicationFree,
+        Form::Negation,
+        Form::Conjunctive,
+        Form::Disjunctive,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Form::ImplicationFree => "implication-free form",
+            Form::Negation => "negation normal form",
+            Form::Conjunctive => "conjunctive normal form",
+            Form::Disjunctive => "disjunctive normal form",
+        }
+    }
+}
+
+/// The equivalences the rewriting steps apply.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Law {
+    /// `(A → B)` is `(¬A ∨ B)`.
+    Implication,
+    /// `(A ↔ B)` is `((¬A ∨ B) ∧ (A ∨ ¬B))`.
+    Biconditional,
+    /// `(A ⊕ B)` is `¬(A ↔ B)`.
+    Xor,
+    /// `(A ↑ B)` is `¬(A ∧ B)`.
+    Nand,
+    /// `(A ↓ B)` is `¬(A ∨ B)`.
+    Nor,
+    /// `¬¬A` is `A`.
+    DoubleNegation,
+    /// `¬(A ∧ B)` is `(¬A ∨ ¬B)` and `¬(A ∨ B)` is `(¬A ∧ ¬B)`.
+    DeMorgan,
+    /// `¬⊤` is `⊥` and `¬⊥` is `⊤`.
+    Constant,
+    /// `(A ∨ (B ∧ C))` is `((A ∨ B) ∧ (A ∨ C))`, or the other way round for DNF.
+    Distribution,
+    /// `(A ∧ (B ∧ C))` is `(A ∧ B ∧ C)`, and the same for `∨`.
+    Associativity,
+}
+
+impl Law {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Law::Implication => "implication elimination",
+            Law::Biconditional => "biconditional elimination",
+            Law::Xor => "XOR definition",
+            Law::Nand => "NAND definition",
+            Law::Nor => "NOR definition",
+            Law::DoubleNegation => "double negation",
+            Law::DeMorgan => "De Morgan",
+            Law::Constant => "negated constant",
+            Law::Distribution => "distribution",
+            Law::Associativity => "associativity",
+        }
+    }
+}
+
+/// Rewrites a formula at its root with some law, if one applies.
+type Rewrite = fn(&Rc<Proposition>) -> Option<(Law, Rc<Proposition>)>;
+
+/// A rewriting step: the whole formula after applying `law` once.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Step {
+    pub law: Law,
+    pub result: Rc<Proposition>,
+}
+
+/// The steps turning `prop` into the normal form `form`, none if it already is in it. Fails
+/// when it takes more than [`STEP_LIMIT`] steps.
+pub fn trace(prop: &Rc<Proposition>, form: Form) -> Result<Vec<Step>, &'static str> {
+    let mut stages: Vec<Rewrite> = vec![eliminate_implications];
+    match form {
+        Form::ImplicationFree => (),
+        Form::Negation => stages.push(push_negations),
+        Form::Conjunctive => stages.extend([push_negations, distribute_or]),
+        Form::Disjunctive => stages.extend([push_negations, distribute_and]),
+    }
+    let mut steps: Vec<Step> = Vec::new();
+    let mut current = prop.clone();
+    for law in stages {
+        while let Some((law, result)) = rewrite_once(&current, law) {
+            if steps.len() == STEP_LIMIT {
+                return Err("The conversion takes too many steps, try the Tseitin CNF");
+            }
+            current = result.clone();
+            steps.push(Step { law, result });
+        }
+    }
+    Ok(steps)
+}
+
+/// `prop` in the normal form `form`, see [`trace`].
+pub fn normal_form(prop: &Rc<Proposition>, form: Form) -> Result<Rc<Proposition>, &'static str> {
+    Ok(trace(prop, form)?
+        .pop()
+        .map_or_else(|| prop.clone(), |step| step.result))
+}
+
+fn children(prop: &Proposition) -> Vec<Rc<Proposition>> {
+    match prop {
+        Proposition::Absurdum
+        | Proposition::Verum
+        | Proposition::Term(_)
+        | Proposition::Meta(_) => Vec::new(),
+        Proposition::Not(p) => vec![p.clone()],
+        Proposition::And(ps) | Proposition::Or(ps) => ps.clone(),
+        Proposition::Implies(l, r)
+        | Proposition::Iff(l, r)
+        | Proposition::Xor(l, r)
+        | Proposition::Nand(l, r)
+        | Proposition::Nor(l, r) => vec![l.clone(), r.clone()],
+    }
+}
+
+/// `prop` with its children replaced by `children`, which come in the same number.
+fn with_children(prop: &Rc<Proposition>, children: &[Rc<Proposition>]) -> Rc<Proposition> {
+    match prop.as_ref() {
+        Proposition::Absurdum
+        | Proposition::Verum
+        | Proposition::Term(_)
+        | Proposition::Meta(_) => prop.clone(),
+        Proposition::Not(_) => Proposition::new_not(&children[0]),
+        Proposition::And(_) => Proposition::new_conjunction(children),
+        Proposition::Or(_) => Proposition::new_disjunction(children),
+        Proposition::Implies(_, _) => Proposition::new_implies(&children[0], &children[1]),
+        Proposition::Iff(_, _) => Proposition::new_iff(&children[0], &children[1]),
+        Proposition::Xor(_, _) => Proposition::new_xor(&children[0], &children[1]),
+        Proposition::Nand(_, _) => Proposition::new_nand(&children[0], &children[1]),
+        Proposition::Nor(_, _) => Proposition::new_nor(&children[0], &children[1]),
+    }
+}
+
+/// Applies `law` once, at the outermost and then leftmost subformula it applies to.
+fn rewrite_once(prop: &Rc<Proposition>, law: Rewrite) -> Option<(Law, Rc<Proposition>)> {
+    if let Some(res) = law(prop) {
+        return Some(res);
+    }
+    let mut children = children(prop);
+    for i in 0..children.len() {
+        if let Some((applied, child)) = rewrite_once(&children[i], law) {
+            children[i] = child;
+            return Some((applied, with_children(prop, &children)));
+        }
+    }
+    None
+}
+
+fn negate_all(props: &[Rc<Proposition>]) -> Vec<Rc<Proposition>> {
+    props.iter().map(Proposition::new_not).collect()
+}
+
+fn eliminate_implications(prop: &Rc<Proposition>) -> Option<(Law, Rc<Proposition>)> {
+    let not = Proposition::new_not;
+    match prop.as_ref() {
+        Proposition::Implies(l, r) => Some((Law::Implication, Proposition::new_or(&not(l), r))),
+        Proposition::Iff(l, r) => Some((
+            Law::Biconditional,
+            Proposition::new_and(
+                &Proposition::new_or(&not(l), r),
+                &Proposition::new_or(l, &not(r)),
+            ),
+        )),
+        Proposition::Xor(l, r) => Some((Law::Xor, not(&Proposition::new_iff(l, r)))),
+        Proposition::Nand(l, r) => Some((Law::Nand, not(&Proposition::new_and(l, r)))),
+        Proposition::Nor(l, r) => Some((Law::Nor, not(&Proposition::new_or(l, r)))),
+        _ => None,
+    }
+}
+
+fn push_negations(prop: &Rc<Proposition>) -> Option<(Law, Rc<Proposition>)> {
+    let Proposition::Not(inner) = prop.as_ref() else {
+        return None;
+    };
+    match inner.as_ref() {
+        Proposition::Not(p) => Some((Law::DoubleNegation, p.clone())),
+        Proposition::And(ps) => {
+            Some((Law::DeMorgan, Proposition::new_disjunction(&negate_all(ps))))
+        }
+        Proposition::Or(ps) => Some((Law::DeMorgan, Proposition::new_conjunction(&negate_all(ps)))),
+        Proposition::Verum => Some((Law::Constant, Proposition::new_absurdum())),
+        Proposition::Absurdum => Some((Law::Constant, Proposition::new_verum())),
+        _ => None,
+    }
+}
+
+/// Flattens nested conjunctions and disjunctions, and distributes `outer` over `inner`.
+fn distribute(
+    prop: &Rc<Proposition>,
+    outer: fn(&Proposition) -> Option<&[Rc<Proposition>]>,
+    new_outer: fn(&[Rc<Proposition>]) -> Rc<Proposition>,
+    inner: fn(&Proposition) -> Option<&[Rc<Proposition>]>,
+    new_inner: fn(&[Rc<Proposition>]) -> Rc<Proposition>,
+) -> Option<(Law, Rc<Proposition>)> {
+    if let Some(ps) = outer(prop).or_else(|| inner(prop)) {
+        let same = if outer(prop).is_some() { outer } else { inner };
+        if ps.iter().any(|p| same(p).is_some()) {
+            let flat: Vec<Rc<Proposition>> = ps
+                .iter()
+                .flat_map(|p| same(p).map_or_else(|| vec![p.clone()], <[_]>::to_vec))
+                .collect();
+            let new = if outer(prop).is_some() {
+                new_outer
+            } else {
+                new_inner
+            };
+            return Some((Law::Associativity, new(&flat)));
+        }
+    }
+    let operands = outer(prop)?;
+    let (i, spread) = operands
+        .iter()
+        .enumerate()
+        .find_map(|(i, p)| Some((i, inner(p)?)))?;
+    let distributed: Vec<Rc<Proposition>> = spread
+        .iter()
+        .map(|p| {
+            let mut operands = operands.to_vec();
+            operands[i] = p.clone();
+            new_outer(&operands)
+        })
+        .collect();
+    Some((Law::Distribution, new_inner(&distributed)))
+}
+
+fn conjuncts(prop: &Proposition) -> Option<&[Rc<Proposition>]> {
+    match prop {
+        Proposition::And(ps) => Some(ps),
+        _ => None,
+    }
+}
+
+fn disjuncts(prop: &Proposition) -> Option<&[Rc<Proposition>]> {
+    match prop {
+        Proposition::Or(ps) => Some(ps),
+        _ => None,
+    }
+}
+
+fn distribute_or(prop: &Rc<Proposition>) -> Option<(Law, Rc<Proposition>)> {
+    distribute(
+        prop,
+        disjuncts,
+        Proposition::new_disjunction,
+        conjuncts,
+        Proposition::new_conjunction,
+    )
+}
+
+fn distribute_and(prop: &Rc<Proposition>) -> Option<(Law, Rc<Proposition>)> {
+    distribute(
+        prop,
+        conjuncts,
+        Proposition::new_conjunction,
+        disjuncts,
+        Proposition::new_disjunction,
+    )
+}
+
+/// A formula in clausal form: a conjunction of clauses, each a disjunction of literals. As in
+/// DIMACS, literal `v` stands for the 1-based variable `v` and `-v` for its negation.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Clauses {
+    pub variables: Vec<Rc<Proposition>>,
+    pub clauses: Vec<Vec<i32>>,
+}
+
+impl Clauses {
+    pub fn literal(&self, literal: i32) -> Rc<Proposition> {
+        let variable = &self.variables[literal.unsigned_abs() as usize - 1];
+        match literal > 0 {
+            true => variable.clone(),
+            false => Proposition::new_not(variable),
+        }
+    }
+
+    /// The clause as a formula. The empty clause is `⊥`.
+    pub fn clause(&self, clause: &[i32]) -> Rc<Proposition> {
+        let literals: Vec<Rc<Proposition>> = clause.iter().map(|l| self.literal(*l)).collect();
+        match &literals[..] {
+            [] => Proposition::new_absurdum(),
+            [literal] => literal.clone(),
+            _ => Proposition::new_disjunction(&literals),
+        }
+    }
+
+    /// The conjunction of the clauses. No clauses is `⊤`.
+    pub fn to_proposition(&self) -> Rc<Proposition> {
+        let clauses: Vec<Rc<Proposition>> = self.clauses.iter().map(|c| self.clause(c)).collect();
+        match &clauses[..] {
+            [] => Proposition::new_verum(),
+            [clause] => clause.clone(),
+            _ => Proposition::new_conjunction(&clauses),
+        }
+    }
+
+    /// The clauses of `prop` if it already is a CNF, with nested conjunctions flattened. `⊤`
+    /// adds no clause and `⊥` the empty one.
+    pub fn from_cnf(prop: &Rc<Proposition>) -> Option<Clauses> {
+        let mut res = Builder::default();
+        res.add_conjunct(prop).then_some(res.clauses)
+    }
+}
+
+/// [`Clauses`] being built, with the variable given to each atom so far.
+#[derive(Default)]
+struct Builder {
+    clauses: Clauses,
+    variables: HashMap<Rc<Proposition>, i32>,
+}
+
+impl Builder {
+    fn add(&mut self, clause: Vec<i32>) {
+        self.clauses.clauses.push(clause);
+    }
+
+    fn add_conjunct(&mut self, prop: &Rc<Proposition>) -> bool {
+        match prop.as_ref() {
+            Proposition::And(ps) => ps.iter().all(|p| self.add_conjunct(p)),
+            Proposition::Verum => true,
+            Proposition::Absurdum => {
+                self.add(Vec::new());
+                true
+            }
+            Proposition::Or(ps) => {
+                let clause: Option<Vec<i32>> = ps.iter().map(|p| self.literal_of(p)).collect();
+                clause.map(|clause| self.add(clause)).is_some()
+            }
+            _ => self
+                .literal_of(prop)
+                .map(|literal| self.add(vec![literal]))
+                .is_some(),
+        }
+    }
+
+    fn literal_of(&mut self, prop: &Rc<Proposition>) -> Option<i32> {
+        match prop.as_ref() {
+            Proposition::Term(_) | Proposition::Meta(_) => Some(self.variable(prop)),
+            Proposition::Not(p)
+                if matches!(p.as_ref(), Proposition::Term(_) | Proposition::Meta(_)) =>
+            {
+                Some(-self.variable(p))
+            }
+            _ => None,
+        }
+    }
+
+    fn variable(&mut self, prop: &Rc<Proposition>) -> i32 {
+        let variables = &mut self.clauses.variables;
+        *self.variables.entry(prop.clone()).or_insert_with(|| {
+            variables.push(prop.clone());
+            variables.len() as i32
+        })
+    }
+}
+
+/// The Tseitin encoding of a formula: its clauses and the fresh atoms they introduce, each
+/// standing for a subformula.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Tseitin {
+    pub clauses: Clauses,
+    pub definitions: Vec<(Rc<Proposition>, Rc<Proposition>)>,
+}
+
+impl Display for Tseitin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (atom, definition) in &self.definitions {
+            writeln!(f, "{} := {}", atom, definition)?;
+        }
+        write!(f, "{}", self.clauses.to_proposition())
+    }
+}
+
+/// Converts `prop` to a CNF that is satisfiable exactly when `prop` is, by naming each compound
+/// subformula with a fresh atom. Unlike distribution, this grows linearly with `prop`.
+pub fn tseitin(prop: &Rc<Proposition>) -> Tseitin {
+    let mut taken: Vec<String> = Vec::new();
+    for p in Proposition::subformulas(prop) {
+        if let Proposition::Term(name) = p.as_ref() {
+            taken.push(name.clone());
+        }
+    }
+    let mut encoder = Encoder {
+        builder: Builder::default(),
+        definitions: Vec::new(),
+        literals: HashMap::new(),
+        taken,
+        fresh: 0,
+    };
+    let root = encoder.encode(prop);
+    encoder.add(vec![root]);
+    Tseitin {
+        clauses: encoder.builder.clauses,
+        definitions: encoder.definitions,
+    }
+}
+
+struct Encoder {
+    builder: Builder,
+    definitions: Vec<(Rc<Proposition>, Rc<Proposition>)>,
+    /// The literal each subformula encoded so far is equivalent to.
+    literals: HashMap<Rc<Proposition>, i32>,
+    /// Atom names the fresh atoms have to avoid.
+    taken: Vec<String>,
+    fresh: usize,
+}
+
+impl Encoder {
+    /// A fresh variable for `definition`, named `t1`, `t2` and so on.
+    fn gate(&mut self, definition: &Rc<Proposition>) -> i32 {
+        let name = loop {
+            self.fresh += 1;
+            let name = format!("t{}", self.fresh);
+            if !self.taken.contains(&name) {
+                break name;
+            }
+        };
+        let atom = Proposition::new_term(&name);
+        self.definitions.push((atom.clone(), definition.clone()));
+        self.builder.variable(&atom)
+    }
+
+    fn add(&mut self, clause: Vec<i32>) {
+        self.builder.add(clause);
+    }
+
+    /// `g` stands for the conjunction of `operands`.
+    fn and_gate(&mut self, g: i32, operands: &[i32]) {
+        operands.iter().for_each(|o| self.add(vec![-g, *o]));
+        let mut clause: Vec<i32> = operands.iter().map(|o| -o).collect();
+        clause.push(g);
+        self.add(clause);
+    }
+
+    /// The literal equivalent to `prop` under the clauses added so far.
+    fn encode(&mut self, prop: &Rc<Proposition>) -> i32 {
+        if let Some(literal) = self.literals.get(prop) {
+            return *literal;
+        }
+        let res = match prop.borrow() {
+            Proposition::Term(_) | Proposition::Meta(_) => self.builder.variable(prop),
+            Proposition::Verum | Proposition::Absurdum => {
+                let g = self.gate(&Proposition::new_verum());
+                self.add(vec![g]);
+                match prop.borrow() {
+                    Proposition::Verum => g,
+                    _ => -g,
+                }
+            }
+            Proposition::Not(p) => -self.encode(p),
+            Proposition::And(ps) | Proposition::Or(ps) => {
+                let operands: Vec<i32> = ps.iter().map(|p| self.encode(p)).collect();
+                let g = self.gate(prop);
+                match prop.borrow() {
+                    Proposition::And(_) => self.and_gate(g, &operands),
+                    // g ↔ (a ∨ b) is ¬g ↔ (¬a ∧ ¬b)
+                    _ => self.and_gate(-g, &operands.iter().map(|o| -o).collect::<Vec<_>>()),
+                }
+                g
+            }
+            Proposition::Implies(l, r) => {
+                let (l, r) = (self.encode(l), self.encode(r));
+                let g = self.gate(prop);
+                self.and_gate(-g, &[l, -r]);
+                g
+            }
+            Proposition::Nand(l, r) | Proposition::Nor(l, r) => {
+                let (l, r) = (self.encode(l), self.encode(r));
+                let g = self.gate(prop);
+                match prop.borrow() {
+                    Proposition::Nand(_, _) => self.and_gate(-g, &[l, r]),
+                    _ => self.and_gate(g, &[-l, -r]),
+                }
+                g
+            }
+            Proposition::Iff(l, r) | Proposition::Xor(l, r) => {
+                let (l, r) = (self.encode(l), self.encode(r));
+                let g = self.gate(prop);
+                // An XOR gate is a negated biconditional one
+                let iff = match prop.borrow() {
+                    Proposition::Iff(_, _) => g,
+                    _ => -g,
+                };
+                self.add(vec![-iff, -l, r]);
+                self.add(vec![-iff, l, -r]);
+                self.add(vec![iff, l, r]);
+                self.add(vec![iff, -l, -r]);
+                g
+            }
+        };
+        self.literals.insert(prop.clone(), res);
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normal_form, trace, tseitin, Clauses, Form, Law};
+    use crate::{fitch::Proposition, parser::expr};
+    use std::rc::Rc;
+
+    fn atoms(prop: &Rc<Proposition>) -> Vec<String> {
+        let mut res = Vec::new();
+        for p in Proposition::subformulas(prop) {
+            if let Proposition::Term(name) = p.as_ref() {
+                if !res.contains(name) {
+                    res.push(name.clone());
+                }
+            }
+        }
+        res
+    }
+
+    /// Whether `l` and `r` have the same truth table over the atoms of `l`.
+    fn equivalent(l: &Rc<Proposition>, r: &Rc<Proposition>) -> bool {
+        let atoms = atoms(l);
+        (0..1 << atoms.len()).all(|row: u32| {
+            let value = |name: &str| {
+                let i = atoms.iter().position(|a| a == name).unwrap();
+                row & (1 << i) != 0
+            };
+            l.evaluate(&value) == r.evaluate(&value)
+        })
+    }
+
+    fn satisfiable(clauses: &Clauses) -> bool {
+        (0..1u32 << clauses.variables.len()).any(|row| {
+            clauses.clauses.iter().all(|clause| {
+                clause
+                    .iter()
+                    .any(|l| (row & (1 << (l.unsigned_abs() - 1)) != 0) == (*l > 0))
+            })
+        })
+    }
+
+    #[test]
+    fn steps() {
+        let steps = trace(&expr("(~(A & (B => C)))"), Form::Negation).unwrap();
+        let laws: Vec<Law> = steps.iter().map(|s| s.law).collect();
+        assert_eq!(
+            laws,
+            [
+                Law::Implication,
+                Law::DeMorgan,
+                Law::DeMorgan,
+                Law::DoubleNegation
+            ]
+        );
+        assert_eq!(steps[1].result, expr("((~A) | (~((~B) | C)))"));
+        assert_eq!(steps[3].result, expr("((~A) | (B & (~C)))"));
+
+        assert_eq!(trace(&expr("(A | (~B))"), Form::Conjunctive).unwrap(), []);
+        assert_eq!(
+            normal_form(&expr("(~%)"), Form::Negation).unwrap(),
+            Proposition::new_absurdum()
+        );
+        assert_eq!(
+            normal_form(&expr("(A ^ B)"), Form::ImplicationFree).unwrap(),
+            expr("(~(((~A) | B) & (A | (~B))))")
+        );
+    }
+
+    #[test]
+    fn forms() {
+        assert_eq!(
+            normal_form(&expr("(A | (B & C))"), Form::Conjunctive).unwrap(),
+            expr("((A | B) & (A | C))")
+        );
+        assert_eq!(
+            normal_form(&expr("((A | B) & C)"), Form::Disjunctive).unwrap(),
+            expr("((A & C) | (B & C))")
+        );
+        assert_eq!(
+            normal_form(&expr("(A & (B & (C | (D & E))))"), Form::Conjunctive).unwrap(),
+            expr("(A & B & (C | D) & (C | E))")
+        );
+        for text in [
+            "((A => B) <=> (~(C ^ A)))",
+            "(~((A ~& B) ~| (C & (~D))))",
+            "((A | B) & (C | (D => A)) & (~(B & C)))",
+        ] {
+            let prop = expr(text);
+            for form in Form::ALL {
+                let res = normal_form(&prop, form).unwrap();
+                assert!(equivalent(&prop, &res), "{} in {}", text, form.name());
+            }
+        }
+    }
+
+    #[test]
+    fn step_limit() {
+        let big = (0..12).fold(expr("A"), |acc, i| {
+            let atom = |name: &str| Proposition::new_term(&format!("{}{}", name, i));
+            Proposition::new_or(&acc, &Proposition::new_and(&atom("P"), &atom("Q")))
+        });
+        assert!(trace(&big, Form::Conjunctive).is_err());
+        let encoded = tseitin(&big);
+        assert!(encoded.clauses.clauses.len() < 100);
+        assert!(satisfiable(&tseitin(&expr("(A & (B <=> (~A)))")).clauses));
+    }
+
+    #[test]
+    fn clauses() {
+        let cnf = Clauses::from_cnf(&expr("((A | (~B)) & ((~A) & (C | A)) & %)")).unwrap();
+        assert_eq!(cnf.variables, [expr("A"), expr("B"), expr("C")]);
+        assert_eq!(cnf.clauses, [vec![1, -2], vec![-1], vec![3, 1]]);
+        assert_eq!(cnf.to_proposition(), expr("((A | (~B)) & (~A) & (C | A))"));
+        assert_eq!(
+            Clauses::from_cnf(&expr("#")).unwrap().clauses,
+            [Vec::<i32>::new()]
+        );
+        assert_eq!(
+            Clauses::from_cnf(&expr("%")).unwrap().to_proposition(),
+            expr("%")
+        );
+        assert!(Clauses::from_cnf(&expr("(A | (B & C))")).is_none());
+        assert!(Clauses::from_cnf(&expr("(~(~A))")).is_none());
+    }
+
+    #[test]
+    fn tseitin_encoding() {
+        let encoded = tseitin(&expr("(t1 & (~(A => t1)))"));
+        assert_eq!(encoded.definitions.len(), 2);
+        assert_eq!(encoded.definitions[0].0, expr("t2"));
+        assert_eq!(encoded.definitions[0].1, expr("(A => t1)"));
+        assert!(!satisfiable(&encoded.clauses));
+
+        for text in [
+            "((A => B) <=> (~(C ^ A)))",
+            "(A ~| (B ~& %))",
+            "(A & (~A))",
+            "(# | (A <=> (~A)))",
+        ] {
+            let prop = expr(text);
+            let satisfiable_prop = (0..1u32 << atoms(&prop).len()).any(|row| {
+                let atoms = atoms(&prop);
+                prop.evaluate(&|name| {
+                    row & (1 << atoms.iter().position(|a| a == name).unwrap()) != 0
+                })
+            });
+            assert_eq!(
+                satisfiable(&tseitin(&prop).clauses),
+                satisfiable_prop,
+                "{}",
+                text
+            );
+        }
+    }
+}
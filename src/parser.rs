@@ -1,251 +1,479 @@
-use crate::fitch::Proposition;
-use std::rc::Rc;
-
+use crate::{
+    custom::CustomRule,
+    fitch::{numeric_suffix, Fitch, Proposition, Rule, SymbolStyle},
+    lemma::Lemma,
+};
+use std::{ops::Range, rc::Rc};
+
+/// The parsed expression and the input left after it.
 #[derive(Clone)]
 pub enum Result<'a> {
     Success(Rc<Proposition>, &'a str),
     Failure,
 }
 
-fn parse_absurdum(queue: &str) -> Result {
-    let queue = queue.trim_start();
-    if queue.is_empty() || !queue.starts_with('#') {
-        Result::Failure
-    } else {
-        Result::Success(Proposition::new_absurdum(), &queue[1..])
-    }
+/// Strips the connective returned by `symbol` in any of the [`SymbolStyle`]s.
+fn strip_symbol(queue: &str, symbol: fn(&SymbolStyle) -> &'static str) -> Option<&str> {
+    [SymbolStyle::Ascii, SymbolStyle::Unicode]
+        .iter()
+        .find_map(|style| queue.strip_prefix(symbol(style)))
 }
 
-fn parse_term(queue: &str) -> Result {
+fn parse_absurdum(queue: &str) -> Result<'_> {
     let queue = queue.trim_start();
-    if queue.is_empty() {
-        return Result::Failure;
+    match queue.chars().next() {
+        Some(c @ ('#' | '⊥')) => {
+            Result::Success(Proposition::new_absurdum(), &queue[c.len_utf8()..])
+        }
+        _ => Result::Failure,
     }
+}
 
-    let mut buffer = String::new();
-    let mut index = 0;
-    for (i, x) in queue.chars().enumerate() {
-        if x.is_ascii_uppercase() {
-            buffer.push(x);
-        } else {
-            index = i;
-            break;
+fn parse_verum(queue: &str) -> Result<'_> {
+    let queue = queue.trim_start();
+    match queue.chars().next() {
+        Some(c @ ('%' | '⊤')) => {
+            Result::Success(Proposition::new_verum(), &queue[c.len_utf8()..])
         }
+        _ => Result::Failure,
     }
+}
 
-    if buffer.is_empty() {
-        Result::Failure
-    } else {
-        Result::Success(Proposition::new_term(&buffer), &queue[index..])
-    }
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '\'' || ('₀'..='₉').contains(&c)
 }
 
-fn parse_and(queue: &str) -> Result {
-    let mut queue = queue.trim_start();
-    if queue.is_empty() || !queue.starts_with('(') {
+/// Parses an atom: a letter followed by letters, digits, underscores and primes, e.g. `P`,
+/// `Rain`, `p_2` or `q'`. A numeric suffix can be written as `p2`, `p_2` or `p₂`, which are all
+/// the same atom. A name directly followed by `(` is not an atom, that is left for predicates.
+fn parse_term(queue: &str) -> Result<'_> {
+    let queue = queue.trim_start();
+    if !queue.starts_with(|c: char| c.is_ascii_alphabetic()) {
         return Result::Failure;
     }
-    queue = queue[1..].trim_start();
-
-    match parse_expression(queue) {
-        Result::Failure => Result::Failure,
-        Result::Success(left, rest) => {
-            queue = rest.trim_start();
-            if queue.is_empty() || !queue.starts_with('&') {
-                return Result::Failure;
-            }
-            queue = queue[1..].trim_start();
-
-            match parse_expression(queue) {
-                Result::Failure => Result::Failure,
-                Result::Success(right, rest) => {
-                    queue = rest.trim_start();
-                    if queue.is_empty() || !queue.starts_with(')') {
-                        return Result::Failure;
-                    }
-
-                    queue = &queue[1..];
-                    Result::Success(Proposition::new_and(&left, &right), queue)
-                }
-            }
-        }
+    match parse_name(queue) {
+        Some((name, rest)) => Result::Success(Proposition::new_term(&name), rest),
+        None => Result::Failure,
     }
 }
 
-fn parse_or(queue: &str) -> Result {
-    let mut queue = queue.trim_start();
-    if queue.is_empty() || !queue.starts_with('(') {
-        return Result::Failure;
+/// Parses a metavariable: a lowercase Greek letter, e.g. `φ` or `ψ₁`, or in ASCII `?` followed
+/// by a name like an atom's, e.g. `?p`. The rest of the name follows the rules for atoms.
+fn parse_meta(queue: &str) -> Result<'_> {
+    let queue = queue.trim_start();
+    let name = match queue.strip_prefix('?') {
+        Some(name) if name.starts_with(|c: char| c.is_ascii_alphabetic()) => name,
+        Some(_) => return Result::Failure,
+        None if queue.starts_with(is_greek) => queue,
+        None => return Result::Failure,
+    };
+    match parse_name(name) {
+        Some((name, rest)) => Result::Success(Proposition::new_meta(&name), rest),
+        None => Result::Failure,
     }
-    queue = queue[1..].trim_start();
+}
 
-    match parse_expression(queue) {
-        Result::Failure => Result::Failure,
-        Result::Success(left, rest) => {
-            queue = rest.trim_start();
-            if queue.is_empty() || !queue.starts_with('|') {
-                return Result::Failure;
-            }
-            queue = queue[1..].trim_start();
+fn is_greek(c: char) -> bool {
+    ('α'..='ω').contains(&c)
+}
 
-            match parse_expression(queue) {
-                Result::Failure => Result::Failure,
-                Result::Success(right, rest) => {
-                    queue = rest.trim_start();
-                    if queue.is_empty() || !queue.starts_with(')') {
-                        return Result::Failure;
-                    }
+/// Splits off the name `queue` starts with, with its numeric suffix written canonically.
+fn parse_name(queue: &str) -> Option<(String, &str)> {
+    let first = queue.chars().next()?.len_utf8();
+    let end = queue[first..]
+        .find(|c| !is_name_char(c))
+        .map_or(queue.len(), |end| first + end);
+    let (name, rest) = queue.split_at(end);
+    if rest.starts_with('(') {
+        return None;
+    }
+    Some((canonical_name(name), rest))
+}
 
-                    queue = &queue[1..];
-                    Result::Success(Proposition::new_or(&left, &right), queue)
-                }
-            }
-        }
+/// `name` with its numeric suffix written in plain digits and without the `_` before it, so
+/// that `p2`, `p_2` and `p₂` are the same name.
+pub(crate) fn canonical_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| match c {
+            '₀'..='₉' => char::from_digit(c as u32 - '₀' as u32, 10).unwrap(),
+            c => c,
+        })
+        .collect();
+    let (stem, digits) = numeric_suffix(&name);
+    match stem.strip_suffix('_') {
+        Some(stem) if !digits.is_empty() => format!("{}{}", stem, digits),
+        _ => name.clone(),
     }
 }
 
-fn parse_not(queue: &str) -> Result {
-    let mut queue = queue.trim_start();
-    if queue.is_empty() || !queue.starts_with('(') {
-        return Result::Failure;
+/// The connectives written between two operands, with how to build them.
+const BINARY: [(Symbol, Binary); 5] = [
+    (SymbolStyle::implies, Proposition::new_implies),
+    (SymbolStyle::iff, Proposition::new_iff),
+    (SymbolStyle::xor, Proposition::new_xor),
+    (SymbolStyle::nand, Proposition::new_nand),
+    (SymbolStyle::nor, Proposition::new_nor),
+];
+
+type Symbol = fn(&SymbolStyle) -> &'static str;
+type Binary = fn(&Rc<Proposition>, &Rc<Proposition>) -> Rc<Proposition>;
+
+/// Conjunction and disjunction, which take two or more operands.
+const NARY: [(Symbol, Nary); 2] = [
+    (SymbolStyle::and, Proposition::new_conjunction),
+    (SymbolStyle::or, Proposition::new_disjunction),
+];
+
+type Nary = fn(&[Rc<Proposition>]) -> Rc<Proposition>;
+
+/// Expects the `)` closing a compound expression.
+fn close(queue: &str, prop: Rc<Proposition>) -> Result<'_> {
+    match queue.trim_start().strip_prefix(')') {
+        Some(rest) => Result::Success(prop, rest),
+        None => Result::Failure,
     }
-    queue = queue[1..].trim_start();
+}
 
-    if queue.is_empty() || !queue.starts_with('~') {
-        return Result::Failure;
+/// Parses what follows the `(` of a compound expression: a negation, or a first operand and
+/// then whichever connective comes after it. The first operand is only parsed once, so nesting
+/// stays linear.
+fn parse_compound(queue: &str) -> Result<'_> {
+    let queue = queue.trim_start();
+    if let Some(rest) = strip_symbol(queue, SymbolStyle::not) {
+        return match parse_expression(rest) {
+            Result::Success(operand, rest) => close(rest, Proposition::new_not(&operand)),
+            Result::Failure => Result::Failure,
+        };
     }
-    queue = queue[1..].trim_start();
+    let Result::Success(first, rest) = parse_expression(queue) else {
+        return Result::Failure;
+    };
+    let rest = rest.trim_start();
 
-    match parse_expression(queue) {
-        Result::Failure => Result::Failure,
-        Result::Success(t, rest) => {
-            queue = rest;
-            if queue.is_empty() || !queue.starts_with(')') {
-                return Result::Failure;
+    for (symbol, new) in NARY {
+        if strip_symbol(rest, symbol).is_none() {
+            continue;
+        }
+        let mut operands = vec![first];
+        let mut queue = rest;
+        while let Some(rest) = strip_symbol(queue, symbol) {
+            match parse_expression(rest) {
+                Result::Success(operand, rest) => {
+                    operands.push(operand);
+                    queue = rest.trim_start();
+                }
+                Result::Failure => return Result::Failure,
             }
-            queue = &queue[1..];
-
-            Result::Success(Proposition::new_not(&t), queue)
         }
+        return close(queue, new(&operands));
     }
+    for (symbol, new) in BINARY {
+        if let Some(rest) = strip_symbol(rest, symbol) {
+            return match parse_expression(rest) {
+                Result::Success(second, rest) => close(rest, new(&first, &second)),
+                Result::Failure => Result::Failure,
+            };
+        }
+    }
+    Result::Failure
 }
 
-fn parse_implies(queue: &str) -> Result {
-    let mut queue = queue.trim_start();
-    if queue.is_empty() || !queue.starts_with('(') {
-        return Result::Failure;
+/// Parses a fully parenthesised expression, e.g. `(A & (~B))`, written with ASCII or Unicode
+/// connectives. `#` and `%` stand for `⊥` and `⊤`. Conjunctions and disjunctions can chain
+/// more than two operands inside one pair of parentheses, e.g. `(A | B | C)`. Metavariables
+/// such as `φ` or `?p` can stand in for subformulas, see [`crate::schema`].
+pub fn parse_expression(queue: &str) -> Result<'_> {
+    let queue = queue.trim_start();
+    if let Some(rest) = queue.strip_prefix('(') {
+        return parse_compound(rest);
     }
-    queue = queue[1..].trim_start();
+    [parse_absurdum, parse_verum, parse_term, parse_meta]
+        .iter()
+        .map(|func| func(queue))
+        .find(|r| matches!(r, Result::Success(_, _)))
+        .unwrap_or(Result::Failure)
+}
 
-    match parse_expression(queue) {
-        Result::Failure => Result::Failure,
-        Result::Success(left, rest) => {
-            queue = rest.trim_start();
-            if queue.len() < 2 || &queue[0..2] != "=>" {
-                return Result::Failure;
-            }
-            queue = queue[2..].trim_start();
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TokenKind {
+    Absurdum,
+    Verum,
+    Term,
+    Meta,
+    Connective,
+    LeftParen,
+    RightParen,
+    Whitespace,
+    Unknown,
+}
 
-            match parse_expression(queue) {
-                Result::Failure => Result::Failure,
-                Result::Success(right, rest) => {
-                    queue = rest.trim_start();
-                    if queue.is_empty() || !queue.starts_with(')') {
-                        return Result::Failure;
-                    }
-                    queue = &queue[1..];
-                    Result::Success(Proposition::new_implies(&left, &right), queue)
-                }
+/// Splits the input into the tokens [`parse_expression`] recognises. Ranges are in chars, not
+/// bytes, and together they cover the whole input.
+pub fn tokenize(queue: &str) -> Vec<(TokenKind, Range<usize>)> {
+    let chars: Vec<char> = queue.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let rest = &chars[i..];
+        let (kind, len) = match rest[0] {
+            '#' | '⊥' => (TokenKind::Absurdum, 1),
+            '%' | '⊤' => (TokenKind::Verum, 1),
+            '(' => (TokenKind::LeftParen, 1),
+            ')' => (TokenKind::RightParen, 1),
+            '&' | '|' | '~' | '^' | '∧' | '∨' | '¬' | '→' | '↔' | '⊕' | '↑' | '↓' => {
+                (TokenKind::Connective, 1)
             }
-        }
+            '=' if rest.starts_with(&['=', '>']) => (TokenKind::Connective, 2),
+            '<' if rest.starts_with(&['<', '=', '>']) => (TokenKind::Connective, 3),
+            c if c.is_ascii_alphabetic() => (
+                TokenKind::Term,
+                rest.iter().take_while(|c| is_name_char(**c)).count(),
+            ),
+            c if is_greek(c) => (
+                TokenKind::Meta,
+                1 + rest[1..].iter().take_while(|c| is_name_char(**c)).count(),
+            ),
+            '?' if rest.get(1).is_some_and(|c| c.is_ascii_alphabetic()) => (
+                TokenKind::Meta,
+                1 + rest[1..].iter().take_while(|c| is_name_char(**c)).count(),
+            ),
+            c if c.is_whitespace() => (
+                TokenKind::Whitespace,
+                rest.iter().take_while(|c| c.is_whitespace()).count(),
+            ),
+            _ => (TokenKind::Unknown, 1),
+        };
+        tokens.push((kind, i..i + len));
+        i += len;
     }
+    tokens
 }
 
-fn parse_iff(queue: &str) -> Result {
-    let mut queue = queue.trim_start();
-    if queue.is_empty() || !queue.starts_with('(') {
-        return Result::Failure;
+/// Splits a rule cited by name from the citations after it.
+fn split_name(queue: &str) -> (&str, &str) {
+    let queue = queue.trim_start();
+    let (name, citations) = queue.split_at(queue.find([' ', '[']).unwrap_or(queue.len()));
+    (name, citations.trim_start().trim_start_matches('['))
+}
+
+/// Parses a justification written the way the proof shows it, e.g. `I & [0, 1]`, `E=> 2 0` or
+/// `Lemma contraposition [3]` for a registered lemma, or a registered custom rule by its name,
+/// e.g. `contradiction [2, 5]`. `#` and `%` can be used in place of `⊥` and `⊤`.
+pub fn parse_justification(queue: &str) -> Option<(Rule, Vec<usize>)> {
+    if let Some(rest) = queue.trim_start().strip_prefix("Lemma ") {
+        let (name, citations) = split_name(rest);
+        return Some((Lemma::find(name)?, parse_citations(citations)?));
     }
-    queue = queue[1..].trim_start();
+    let (name, citations) = split_name(queue);
+    if let Some(rule) = CustomRule::find(name) {
+        return Some((rule, parse_citations(citations)?));
+    }
+    let split = queue
+        .find(|c: char| c.is_ascii_digit())
+        .unwrap_or(queue.len());
+    let (name, citations) = queue.split_at(split);
+    let name: String = name
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '[')
+        .map(|c| match c {
+            '#' => '⊥',
+            '%' => '⊤',
+            c => c,
+        })
+        .collect();
+
+    let rule = Rule::ALL.into_iter().find(|r| {
+        [SymbolStyle::Ascii, SymbolStyle::Unicode]
+            .iter()
+            .any(|style| {
+                r.styled_symbol(*style)
+                    .chars()
+                    .filter(|c| !c.is_whitespace())
+                    .eq(name.chars())
+            })
+    })?;
+
+    Some((rule, parse_citations(citations)?))
+}
 
-    match parse_expression(queue) {
-        Result::Failure => Result::Failure,
-        Result::Success(left, rest) => {
-            queue = rest.trim_start();
-            if queue.len() < 3 || &queue[0..3] != "<=>" {
-                return Result::Failure;
-            }
-            queue = queue[3..].trim_start();
+/// Parses row numbers separated by commas or spaces, e.g. `0, 1, 2]`.
+pub fn parse_citations(queue: &str) -> Option<Vec<usize>> {
+    queue
+        .split(|c: char| c == ',' || c == ']' || c.is_whitespace())
+        .filter(|c| !c.is_empty())
+        .map(|c| c.parse().ok())
+        .collect()
+}
 
-            match parse_expression(queue) {
-                Result::Failure => Result::Failure,
-                Result::Success(right, rest) => {
-                    queue = rest.trim_start();
-                    if queue.is_empty() || !queue.starts_with(')') {
-                        return Result::Failure;
-                    }
-                    queue = &queue[1..];
-                    Result::Success(Proposition::new_iff(&left, &right), queue)
-                }
+/// Reads a proof laid out the way [`Fitch`] displays it, with either ASCII or Unicode bars:
+///
+/// ```text
+///  0 | A
+/// ------------------
+///  1 | -   B
+///  2 |     (A & B)    I & [0, 1]
+///  3 | (B => (A & B))    I => [1]
+/// ```
+///
+/// Rows are taken as they are, so it is up to [`Fitch::row_status`] to tell whether they hold.
+pub fn parse_proof(text: &str) -> std::result::Result<Fitch, String> {
+    let mut fitch = Fitch::new();
+    let mut level = 0;
+    let mut premises = true;
+    for (n, line) in text.lines().enumerate() {
+        let error = |message: &str| format!("line {}: {}", n + 1, message);
+        let row = match line.split_once(['|', '│']) {
+            // The line between premises and deductions
+            None if line.trim_start().starts_with(['-', '─']) => {
+                premises = false;
+                continue;
+            }
+            // Blank lines and the cursor
+            None => continue,
+            Some((_, row)) => row.strip_prefix(' ').unwrap_or(row),
+        };
+        let body = row.trim_start_matches([' ', '-', '─']);
+        let indent = row.chars().count() - body.chars().count();
+        if indent % 4 != 0 {
+            return Err(error("rows have to be indented by 4 spaces per subproof"));
+        }
+        let row_level = indent / 4;
+
+        let (prop, rest) = match parse_expression(body) {
+            Result::Failure => return Err(error("invalid expression")),
+            Result::Success(prop, rest) => (prop, rest.trim()),
+        };
+        if premises {
+            if row_level > 0 || !rest.is_empty() {
+                return Err(error(
+                    "premises can't be in a subproof or have a justification",
+                ));
+            }
+            fitch.add_assumption(&prop);
+        } else if row.starts_with(['-', '─']) {
+            while level >= row_level {
+                fitch.end_subproof();
+                level -= 1;
+            }
+            if row_level != level + 1 {
+                return Err(error("subproof opened inside no subproof"));
+            }
+            fitch.add_subproof(&prop);
+            level = row_level;
+        } else {
+            if row_level > level {
+                return Err(error("row is deeper than the subproof it is in"));
+            }
+            while level > row_level {
+                fitch.end_subproof();
+                level -= 1;
+            }
+            match rest {
+                "?" => fitch.add_unjustified(&prop),
+                _ => match parse_justification(rest) {
+                    None => return Err(error("invalid justification")),
+                    Some((rule, citations)) => fitch.add_tentative(&prop, rule, citations),
+                },
             }
         }
     }
+    Ok(fitch)
 }
 
-pub fn parse_expression(queue: &str) -> Result {
-    let queue = queue.trim_start();
-    let result = [
-        parse_absurdum,
-        parse_term,
-        parse_and,
-        parse_or,
-        parse_not,
-        parse_implies,
-        parse_iff,
-    ]
-    .iter()
-    .map(|func| func(queue))
-    .find(|r| match r {
-        Result::Success(_, _) => true,
-        Result::Failure => false,
-    });
-
-    match result {
-        None => Result::Failure,
-        Some(r) => r,
+/// Parses `text` whole, panicking when it is not an expression. For tests.
+#[cfg(test)]
+pub(crate) fn expr(text: &str) -> Rc<Proposition> {
+    match parse_expression(text) {
+        Result::Success(p, "") => p,
+        _ => panic!("{}", text),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::{
-        parse_absurdum, parse_and, parse_expression, parse_iff, parse_implies, parse_not, parse_or,
-        parse_term, Result,
+        parse_absurdum, parse_expression, parse_justification, parse_meta, parse_proof, parse_term,
+        tokenize, Result, TokenKind,
     };
-    use crate::fitch::Proposition;
+    use crate::fitch::{Fitch, Proposition, RowStatus, Rule};
 
     #[test]
     fn parse_absurdum_test() {
         let queue = "  #  ";
         match parse_absurdum(queue) {
-            Result::Failure => assert!(false),
+            Result::Failure => panic!(),
             Result::Success(p, rest) => {
                 assert_eq!(p, Proposition::new_absurdum());
                 assert_eq!(rest, "  ");
             }
         }
+        match parse_absurdum("⊥)") {
+            Result::Failure => panic!(),
+            Result::Success(p, rest) => {
+                assert_eq!(p, Proposition::new_absurdum());
+                assert_eq!(rest, ")");
+            }
+        }
     }
 
     #[test]
     fn parse_term_test() {
         let queue = "  CIAO  ";
         match parse_term(queue) {
-            Result::Failure => assert!(false),
+            Result::Failure => panic!(),
             Result::Success(p, rest) => {
                 assert_eq!(p, Proposition::new_term("CIAO"));
                 assert_eq!(rest, "  ");
             }
         }
+        for (queue, name, rest) in [
+            ("Rain)", "Rain", ")"),
+            ("q' ", "q'", " "),
+            ("P1 & Q", "P1", " & Q"),
+            ("p_2)", "p2", ")"),
+            ("p₁₂ ", "p12", " "),
+            ("big_dog", "big_dog", ""),
+        ] {
+            match parse_term(queue) {
+                Result::Failure => panic!("{}", queue),
+                Result::Success(p, r) => {
+                    assert_eq!(p, Proposition::new_term(name));
+                    assert_eq!(r, rest);
+                }
+            }
+        }
+        for queue in ["1P", "_p", "P(a)", "'"] {
+            assert!(matches!(parse_term(queue), Result::Failure));
+        }
+    }
+
+    #[test]
+    fn parse_meta_test() {
+        for (queue, name, rest) in [
+            (" φ)", "φ", ")"),
+            ("ψ₁ ", "ψ1", " "),
+            ("?p_2)", "p2", ")"),
+            ("?Phi", "Phi", ""),
+        ] {
+            match parse_meta(queue) {
+                Result::Failure => panic!("{}", queue),
+                Result::Success(p, r) => {
+                    assert_eq!(p, Proposition::new_meta(name));
+                    assert_eq!(r, rest);
+                }
+            }
+        }
+        for queue in ["P", "?", "?1", "Φ"] {
+            assert!(matches!(parse_meta(queue), Result::Failure));
+        }
+        assert_ne!(Proposition::new_meta("p"), Proposition::new_term("p"));
+        match parse_expression("(φ → ?q)") {
+            Result::Failure => panic!(),
+            Result::Success(p, _) => {
+                assert_eq!(p.to_string(), "(φ => ?q)");
+            }
+        }
     }
 
     #[test]
@@ -253,8 +481,8 @@ mod tests {
         let queue = "  (A & B)  ";
         let a = Proposition::new_term("A");
         let b = Proposition::new_term("B");
-        match parse_and(queue) {
-            Result::Failure => assert!(false),
+        match parse_expression(queue) {
+            Result::Failure => panic!(),
             Result::Success(p, rest) => {
                 assert_eq!(p, Proposition::new_and(&a, &b));
                 assert_eq!(rest, "  ");
@@ -267,8 +495,8 @@ mod tests {
         let queue = "  (A | B)  ";
         let a = Proposition::new_term("A");
         let b = Proposition::new_term("B");
-        match parse_or(queue) {
-            Result::Failure => assert!(false),
+        match parse_expression(queue) {
+            Result::Failure => panic!(),
             Result::Success(p, rest) => {
                 assert_eq!(p, Proposition::new_or(&a, &b));
                 assert_eq!(rest, "  ");
@@ -280,8 +508,8 @@ mod tests {
     fn parse_not_test() {
         let queue = "  (~A)  ";
         let a = Proposition::new_term("A");
-        match parse_not(queue) {
-            Result::Failure => assert!(false),
+        match parse_expression(queue) {
+            Result::Failure => panic!(),
             Result::Success(p, rest) => {
                 assert_eq!(p, Proposition::new_not(&a));
                 assert_eq!(rest, "  ");
@@ -294,8 +522,8 @@ mod tests {
         let queue = "  (A => B)  ";
         let a = Proposition::new_term("A");
         let b = Proposition::new_term("B");
-        match parse_implies(queue) {
-            Result::Failure => assert!(false),
+        match parse_expression(queue) {
+            Result::Failure => panic!(),
             Result::Success(p, rest) => {
                 assert_eq!(p, Proposition::new_implies(&a, &b));
                 assert_eq!(rest, "  ");
@@ -308,8 +536,8 @@ mod tests {
         let queue = "  (A <=> B)  ";
         let a = Proposition::new_term("A");
         let b = Proposition::new_term("B");
-        match parse_iff(queue) {
-            Result::Failure => assert!(false),
+        match parse_expression(queue) {
+            Result::Failure => panic!(),
             Result::Success(p, rest) => {
                 assert_eq!(p, Proposition::new_iff(&a, &b));
                 assert_eq!(rest, "  ");
@@ -317,6 +545,34 @@ mod tests {
         }
     }
 
+    #[test]
+    fn deep_nesting() {
+        // Left-nested operands used to be parsed again for every connective tried
+        for (connective, new) in [
+            ("~|", Proposition::new_nor as fn(&_, &_) -> _),
+            ("^", Proposition::new_xor),
+            ("=>", Proposition::new_implies),
+        ] {
+            let a = Proposition::new_term("A");
+            let mut text = "A".to_string();
+            let mut expected = a.clone();
+            for _ in 0..40 {
+                text = format!("({} {} A)", text, connective);
+                expected = new(&expected, &a);
+            }
+            match parse_expression(&text) {
+                Result::Failure => panic!("{}", text),
+                Result::Success(p, rest) => {
+                    assert_eq!(p, expected);
+                    assert_eq!(rest, "");
+                }
+            }
+        }
+        assert!(matches!(parse_expression("(A & B | C)"), Result::Failure));
+        assert!(matches!(parse_expression("(A ~& B ~& C)"), Result::Failure));
+        assert!(matches!(parse_expression("(A)"), Result::Failure));
+    }
+
     #[test]
     fn parse_expression_test() {
         let a = Proposition::new_term("A");
@@ -326,11 +582,151 @@ mod tests {
         let ris = Proposition::new_and(&left, &right);
         let queue = "  ((A | B) & (A & B))  ";
         match parse_expression(queue) {
-            Result::Failure => assert!(false),
+            Result::Failure => panic!(),
             Result::Success(p, rest) => {
                 assert_eq!(p, ris);
                 assert_eq!(rest, "  ");
             }
         }
     }
+
+    #[test]
+    fn parse_nary_test() {
+        let a = Proposition::new_term("A");
+        let b = Proposition::new_term("B");
+        let c = Proposition::new_term("C");
+        let ris = Proposition::new_disjunction(&[
+            Proposition::new_conjunction(&[a.clone(), b.clone(), c.clone()]),
+            Proposition::new_and(&Proposition::new_and(&a, &b), &c),
+            c.clone(),
+        ]);
+        match parse_expression("((A & B & C) | ((A & B) & C) | C)") {
+            Result::Failure => panic!(),
+            Result::Success(p, rest) => {
+                assert_eq!(p, ris);
+                assert_eq!(rest, "");
+            }
+        }
+        assert_eq!(ris.to_string(), "((A & B & C) | ((A & B) & C) | C)");
+        assert!(matches!(parse_expression("(A & B | C)"), Result::Failure));
+        assert!(matches!(parse_expression("(A)"), Result::Failure));
+        assert_eq!(
+            parse_justification("E | 0, 1, 4, 7"),
+            Some((Rule::ElimOr, vec![0, 1, 4, 7]))
+        );
+    }
+
+    #[test]
+    fn parse_gates_test() {
+        let a = Proposition::new_term("A");
+        let b = Proposition::new_term("B");
+        let ris = Proposition::new_xor(
+            &Proposition::new_nand(&a, &Proposition::new_verum()),
+            &Proposition::new_nor(&a, &b),
+        );
+        for queue in ["((A ~& %) ^ (A ~| B))", "((A ↑ ⊤) ⊕ (A ↓ B))"] {
+            match parse_expression(queue) {
+                Result::Failure => panic!(),
+                Result::Success(p, rest) => {
+                    assert_eq!(p, ris);
+                    assert_eq!(rest, "");
+                }
+            }
+        }
+        assert_eq!(ris.to_string(), "((A ~& ⊤) ^ (A ~| B))");
+        assert_eq!(parse_justification("I %"), Some((Rule::IntroVerum, vec![])));
+        assert_eq!(parse_justification("E~| 3"), Some((Rule::ElimNor, vec![3])));
+        assert_eq!(
+            parse_justification("I ⊕ 1"),
+            Some((Rule::IntroXor, vec![1]))
+        );
+    }
+
+    #[test]
+    fn parse_justification_test() {
+        assert_eq!(
+            parse_justification("I & [0, 1]"),
+            Some((Rule::IntroAnd, vec![0, 1]))
+        );
+        assert_eq!(parse_justification(" E# 3"), Some((Rule::ElimAbs, vec![3])));
+        assert_eq!(
+            parse_justification("I<=> 2 4"),
+            Some((Rule::IntroIff, vec![2, 4]))
+        );
+        assert_eq!(parse_justification("X 1"), None);
+        assert_eq!(parse_justification("Re 1, a"), None);
+    }
+
+    #[test]
+    fn tokenize_test() {
+        assert_eq!(
+            tokenize("(φ ^ ?q)"),
+            vec![
+                (TokenKind::LeftParen, 0..1),
+                (TokenKind::Meta, 1..2),
+                (TokenKind::Whitespace, 2..3),
+                (TokenKind::Connective, 3..4),
+                (TokenKind::Whitespace, 4..5),
+                (TokenKind::Meta, 5..7),
+                (TokenKind::RightParen, 7..8),
+            ]
+        );
+        let tokens = tokenize("(p₁ <=> #)?");
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::LeftParen, 0..1),
+                (TokenKind::Term, 1..3),
+                (TokenKind::Whitespace, 3..4),
+                (TokenKind::Connective, 4..7),
+                (TokenKind::Whitespace, 7..8),
+                (TokenKind::Absurdum, 8..9),
+                (TokenKind::RightParen, 9..10),
+                (TokenKind::Unknown, 10..11),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_unicode_test() {
+        let a = Proposition::new_term("A");
+        let b = Proposition::new_term("B");
+        let ris = Proposition::new_iff(
+            &Proposition::new_and(&a, &Proposition::new_not(&b)),
+            &Proposition::new_implies(&a, &Proposition::new_or(&a, &b)),
+        );
+        match parse_expression("((A ∧ (¬B)) ↔ (A → (A ∨ B)))") {
+            Result::Failure => panic!(),
+            Result::Success(p, rest) => {
+                assert_eq!(p, ris);
+                assert_eq!(rest, "");
+            }
+        }
+        assert_eq!(parse_justification("E ∧ 2"), Some((Rule::ElimAnd, vec![2])));
+    }
+
+    #[test]
+    fn parse_proof_test() {
+        let a = Proposition::new_term("A");
+        let b = Proposition::new_term("B");
+        let mut fitch = Fitch::new();
+        fitch.add_assumption(&a);
+        fitch.add_subproof(&b);
+        fitch.introduce_and(&[0, 1]);
+        fitch.end_subproof();
+        fitch.add_subproof(&a);
+        fitch.end_subproof();
+        fitch.introduce_implies(1);
+        fitch.add_unjustified(&a);
+
+        let parsed = parse_proof(&fitch.to_string()).unwrap();
+        assert!(parsed == fitch);
+        assert_eq!(parsed.row_status(5), RowStatus::Unjustified);
+
+        assert_eq!(parse_proof(" 0 | A").unwrap().len(), 1);
+        assert!(parse_proof(" 0 | A\n 1 | (A & A)    I & [0, 0]").is_err());
+        assert!(parse_proof("---\n 0 |     A    Re [0]").is_err());
+        assert!(parse_proof("---\n 0 | -   A\n 1 |   A    Re [0]").is_err());
+        assert!(parse_proof("---\n 0 | -   A\n 1 |     A    X [0]").is_err());
+    }
 }
@@ -0,0 +1,423 @@
+//! Satisfiability, validity and entailment checks that scale past truth tables. Formulas are
+//! turned into clauses with [`tseitin`] and handed to a CDCL solver: unit propagation over two
+//! watched literals per clause, clause learning at the first unique implication point and
+//! non-chronological backjumping, deciding on the most active variable.
+//!
+//! ```
+//! use fitch::{parser::{parse_expression, Result}, sat};
+//!
+//! let Result::Success(prop, _) = parse_expression("((A => B) | (B => A))") else { panic!() };
+//! assert!(sat::counter_model(&prop).is_none());
+//! ```
+
+use crate::{
+    fitch::Proposition,
+    normal::{tseitin, Clauses},
+};
+use std::{fmt::Display, mem, rc::Rc};
+
+/// Truth values for the atoms and metavariables of a formula, in order of appearance.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Model(pub Vec<(Rc<Proposition>, bool)>);
+
+impl Model {
+    pub fn value(&self, atom: &Rc<Proposition>) -> Option<bool> {
+        self.0.iter().find(|(a, _)| a == atom).map(|(_, v)| *v)
+    }
+}
+
+impl Display for Model {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, (atom, value)) in self.0.iter().enumerate() {
+            let separator = if i == 0 { "" } else { ", " };
+            let value = if *value { "true" } else { "false" };
+            write!(f, "{}{} {}", separator, atom, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// A valuation making `prop` true, if there is one.
+pub fn satisfiable(prop: &Rc<Proposition>) -> Option<Model> {
+    let encoded = tseitin(prop);
+    let values = solve(&encoded.clauses)?;
+    let fresh: Vec<&Rc<Proposition>> = encoded.definitions.iter().map(|(atom, _)| atom).collect();
+    let model = encoded
+        .clauses
+        .variables
+        .iter()
+        .zip(values)
+        .filter(|(variable, _)| !fresh.contains(variable))
+        .map(|(variable, value)| (variable.clone(), value))
+        .collect();
+    Some(Model(model))
+}
+
+/// A valuation making `prop` false, none when it is valid.
+pub fn counter_model(prop: &Rc<Proposition>) -> Option<Model> {
+    satisfiable(&Proposition::new_not(prop))
+}
+
+/// A valuation making every premise true and `conclusion` false, none when the premises entail
+/// the conclusion.
+pub fn counterexample(premises: &[Rc<Proposition>], conclusion: &Rc<Proposition>) -> Option<Model> {
+    let mut conjuncts = premises.to_vec();
+    conjuncts.push(Proposition::new_not(conclusion));
+    match &conjuncts[..] {
+        [prop] => satisfiable(prop),
+        _ => satisfiable(&Proposition::new_conjunction(&conjuncts)),
+    }
+}
+
+/// A value for each variable satisfying every clause, none when they are unsatisfiable.
+pub fn solve(clauses: &Clauses) -> Option<Vec<bool>> {
+    let mut solver = Solver::new(clauses.variables.len());
+    for clause in &clauses.clauses {
+        if !solver.add_clause(clause) {
+            return None;
+        }
+    }
+    solver.run()
+}
+
+/// Where in the watch lists a literal goes: `2(v - 1)` for `v` and one more for `-v`.
+fn index(literal: i32) -> usize {
+    2 * (literal.unsigned_abs() as usize - 1) + usize::from(literal < 0)
+}
+
+fn variable(literal: i32) -> usize {
+    literal.unsigned_abs() as usize - 1
+}
+
+struct Solver {
+    clauses: Vec<Vec<i32>>,
+    /// The clauses watching each literal, which look at it when it becomes false. The watched
+    /// literals of a clause are its first two.
+    watches: Vec<Vec<usize>>,
+    values: Vec<Option<bool>>,
+    /// The decision level each variable was assigned at.
+    levels: Vec<usize>,
+    /// The clause that forced each variable, none for decisions.
+    reasons: Vec<Option<usize>>,
+    /// The assigned literals in order, and where each decision level starts in it.
+    trail: Vec<i32>,
+    decisions: Vec<usize>,
+    /// How far along the trail propagation has got.
+    propagated: usize,
+    activity: Vec<f64>,
+    bump: f64,
+    /// The value each variable last had, tried first when deciding on it again.
+    phases: Vec<bool>,
+}
+
+impl Solver {
+    fn new(variables: usize) -> Solver {
+        Solver {
+            clauses: Vec::new(),
+            watches: vec![Vec::new(); 2 * variables],
+            values: vec![None; variables],
+            levels: vec![0; variables],
+            reasons: vec![None; variables],
+            trail: Vec::new(),
+            decisions: Vec::new(),
+            propagated: 0,
+            activity: vec![0.0; variables],
+            bump: 1.0,
+            phases: vec![false; variables],
+        }
+    }
+
+    fn value(&self, literal: i32) -> Option<bool> {
+        self.values[variable(literal)].map(|v| v == (literal > 0))
+    }
+
+    fn assign(&mut self, literal: i32, reason: Option<usize>) {
+        let v = variable(literal);
+        self.values[v] = Some(literal > 0);
+        self.levels[v] = self.decisions.len();
+        self.reasons[v] = reason;
+        self.trail.push(literal);
+    }
+
+    /// Adds a clause before solving, returning false when it makes the clauses unsatisfiable
+    /// right away.
+    fn add_clause(&mut self, clause: &[i32]) -> bool {
+        let mut literals: Vec<i32> = Vec::new();
+        for literal in clause {
+            if literals.contains(&-literal) {
+                return true;
+            }
+            if !literals.contains(literal) {
+                literals.push(*literal);
+            }
+        }
+        match literals[..] {
+            [] => false,
+            [literal] => match self.value(literal) {
+                Some(value) => value,
+                None => {
+                    self.assign(literal, None);
+                    true
+                }
+            },
+            _ => {
+                self.watch(literals);
+                true
+            }
+        }
+    }
+
+    fn watch(&mut self, clause: Vec<i32>) -> usize {
+        let id = self.clauses.len();
+        self.watches[index(clause[0])].push(id);
+        self.watches[index(clause[1])].push(id);
+        self.clauses.push(clause);
+        id
+    }
+
+    /// Assigns every literal forced by the trail, returning a clause that became false if any.
+    fn propagate(&mut self) -> Option<usize> {
+        while self.propagated < self.trail.len() {
+            let falsified = -self.trail[self.propagated];
+            self.propagated += 1;
+            let watching = mem::take(&mut self.watches[index(falsified)]);
+            let mut kept = Vec::with_capacity(watching.len());
+            let mut conflict = None;
+            for (i, &id) in watching.iter().enumerate() {
+                if conflict.is_some() {
+                    kept.extend_from_slice(&watching[i..]);
+                    break;
+                }
+                let clause = &mut self.clauses[id];
+                if clause[0] == falsified {
+                    clause.swap(0, 1);
+                }
+                let first = clause[0];
+                if self.values[variable(first)] == Some(first > 0) {
+                    kept.push(id);
+                    continue;
+                }
+                let replacement = (2..clause.len()).find(|&k| {
+                    let literal = clause[k];
+                    self.values[variable(literal)] != Some(literal < 0)
+                });
+                match replacement {
+                    Some(k) => {
+                        clause.swap(1, k);
+                        let literal = clause[1];
+                        self.watches[index(literal)].push(id);
+                    }
+                    None => {
+                        kept.push(id);
+                        match self.value(first) {
+                            Some(false) => conflict = Some(id),
+                            _ => self.assign(first, Some(id)),
+                        }
+                    }
+                }
+            }
+            self.watches[index(falsified)] = kept;
+            if conflict.is_some() {
+                return conflict;
+            }
+        }
+        None
+    }
+
+    /// Learns a clause from a conflict, with the negation of the first unique implication point
+    /// first, and returns it with the level to jump back to.
+    fn analyze(&mut self, conflict: usize) -> (Vec<i32>, usize) {
+        let level = self.decisions.len();
+        let mut seen = vec![false; self.values.len()];
+        let mut learnt = vec![0];
+        let mut pending = 0;
+        let mut position = self.trail.len();
+        let mut clause = conflict;
+        let uip = loop {
+            for k in 0..self.clauses[clause].len() {
+                let literal = self.clauses[clause][k];
+                let v = variable(literal);
+                if seen[v] || self.levels[v] == 0 || self.reasons[v] == Some(clause) {
+                    continue;
+                }
+                seen[v] = true;
+                self.bump_activity(v);
+                if self.levels[v] == level {
+                    pending += 1;
+                } else {
+                    learnt.push(literal);
+                }
+            }
+            let literal = loop {
+                position -= 1;
+                if seen[variable(self.trail[position])] {
+                    break self.trail[position];
+                }
+            };
+            pending -= 1;
+            if pending == 0 {
+                break literal;
+            }
+            clause = self.reasons[variable(literal)].unwrap();
+        };
+        learnt[0] = -uip;
+        let mut backjump = 0;
+        for k in 1..learnt.len() {
+            let literal_level = self.levels[variable(learnt[k])];
+            if literal_level > backjump {
+                backjump = literal_level;
+                learnt.swap(1, k);
+            }
+        }
+        self.bump *= 1.05;
+        (learnt, backjump)
+    }
+
+    fn bump_activity(&mut self, v: usize) {
+        self.activity[v] += self.bump;
+        if self.activity[v] > 1e100 {
+            self.activity.iter_mut().for_each(|a| *a *= 1e-100);
+            self.bump *= 1e-100;
+        }
+    }
+
+    fn backtrack(&mut self, level: usize) {
+        if self.decisions.len() <= level {
+            return;
+        }
+        for literal in self.trail.drain(self.decisions[level]..) {
+            let v = variable(literal);
+            self.phases[v] = literal > 0;
+            self.values[v] = None;
+            self.reasons[v] = None;
+        }
+        self.decisions.truncate(level);
+        self.propagated = self.trail.len();
+    }
+
+    fn run(mut self) -> Option<Vec<bool>> {
+        loop {
+            if let Some(conflict) = self.propagate() {
+                if self.decisions.is_empty() {
+                    return None;
+                }
+                let (learnt, backjump) = self.analyze(conflict);
+                self.backtrack(backjump);
+                let first = learnt[0];
+                let reason = (learnt.len() > 1).then(|| self.watch(learnt));
+                self.assign(first, reason);
+                continue;
+            }
+            let unassigned = (0..self.values.len()).filter(|&v| self.values[v].is_none());
+            let Some(v) = unassigned.max_by(|&l, &r| self.activity[l].total_cmp(&self.activity[r]))
+            else {
+                return Some(self.values.iter().map(|v| v.unwrap()).collect());
+            };
+            self.decisions.push(self.trail.len());
+            let literal = v as i32 + 1;
+            self.assign(if self.phases[v] { literal } else { -literal }, None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{counter_model, counterexample, satisfiable, solve};
+    use crate::{fitch::Proposition, normal::Clauses, parser::expr};
+    use std::rc::Rc;
+
+    fn clauses(variables: usize, clauses: &[&[i32]]) -> Clauses {
+        Clauses {
+            variables: (1..=variables)
+                .map(|v| Proposition::new_term(&format!("x{}", v)))
+                .collect(),
+            clauses: clauses.iter().map(|c| c.to_vec()).collect(),
+        }
+    }
+
+    fn satisfies(clauses: &Clauses, values: &[bool]) -> bool {
+        clauses.clauses.iter().all(|clause| {
+            clause
+                .iter()
+                .any(|l| values[l.unsigned_abs() as usize - 1] == (*l > 0))
+        })
+    }
+
+    /// Whether `n + 1` pigeons fit in `n` holes, one per hole: never.
+    fn pigeonhole(n: usize) -> Clauses {
+        let var = |pigeon: usize, hole: usize| (pigeon * n + hole + 1) as i32;
+        let mut res: Vec<Vec<i32>> = (0..=n)
+            .map(|p| (0..n).map(|h| var(p, h)).collect())
+            .collect();
+        for h in 0..n {
+            for p in 0..=n {
+                for q in p + 1..=n {
+                    res.push(vec![-var(p, h), -var(q, h)]);
+                }
+            }
+        }
+        let refs: Vec<&[i32]> = res.iter().map(|c| &c[..]).collect();
+        clauses((n + 1) * n, &refs)
+    }
+
+    #[test]
+    fn solving() {
+        let sat = clauses(3, &[&[1, 2], &[-1, 3], &[-2, -3], &[-3, 1, 2]]);
+        assert!(satisfies(&sat, &solve(&sat).unwrap()));
+        assert_eq!(solve(&clauses(1, &[&[1], &[-1]])), None);
+        assert_eq!(solve(&clauses(1, &[&[]])), None);
+        assert_eq!(
+            solve(&clauses(2, &[&[1, -1], &[2, 2]])),
+            Some(vec![false, true])
+        );
+        assert_eq!(solve(&pigeonhole(5)), None);
+
+        // Every 3-colouring of a 12-cycle with a chord, pinned down by a few unit clauses
+        let colour = |node: i32, c: i32| node * 3 + c + 1;
+        let mut cycle: Vec<Vec<i32>> = Vec::new();
+        for node in 0..12 {
+            cycle.push((0..3).map(|c| colour(node, c)).collect());
+            for c in 0..3 {
+                cycle.push(vec![-colour(node, c), -colour((node + 1) % 12, c)]);
+                cycle.push(vec![-colour(node, c), -colour((node + 6) % 12, c)]);
+            }
+        }
+        cycle.push(vec![colour(0, 0)]);
+        let refs: Vec<&[i32]> = cycle.iter().map(|c| &c[..]).collect();
+        let coloured = clauses(36, &refs);
+        assert!(satisfies(&coloured, &solve(&coloured).unwrap()));
+    }
+
+    #[test]
+    fn queries() {
+        let model = satisfiable(&expr("((A | B) & (~A) & (C <=> B))")).unwrap();
+        assert_eq!(model.to_string(), "A false, B true, C true");
+        assert_eq!(model.value(&expr("C")), Some(true));
+        assert_eq!(satisfiable(&expr("(A & (A => B) & (~B))")), None);
+        assert_eq!(satisfiable(&expr("#")), None);
+        assert_eq!(satisfiable(&expr("%")).unwrap().0, []);
+
+        assert!(counter_model(&expr("((A => B) => ((~B) => (~A)))")).is_none());
+        let counter = counter_model(&expr("((A => B) => (B => A))")).unwrap();
+        assert_eq!(counter.to_string(), "A false, B true");
+
+        let premises = [expr("(A | B)"), expr("(A => C)"), expr("(B => C)")];
+        assert!(counterexample(&premises, &expr("C")).is_none());
+        assert!(counterexample(&premises[..2], &expr("C")).is_some());
+        assert!(counterexample(&[], &expr("(A | (~A))")).is_none());
+    }
+
+    #[test]
+    fn many_atoms() {
+        // A chain of 60 implications has a truth table far too big to go through
+        let atom = |i: usize| Proposition::new_term(&format!("P{}", i));
+        let premises: Vec<Rc<Proposition>> = (0..60)
+            .map(|i| Proposition::new_implies(&atom(i), &atom(i + 1)))
+            .chain([atom(0)])
+            .collect();
+        assert!(counterexample(&premises, &atom(60)).is_none());
+        let model = counterexample(&premises[..60], &atom(60)).unwrap();
+        assert_eq!(model.value(&atom(0)), Some(false));
+        assert_eq!(model.value(&atom(60)), Some(false));
+    }
+}
@@ -0,0 +1,325 @@
+//! Rule schemata: sequents written over metavariables such as `φ` and `ψ`, which stand for any
+//! formula, e.g. `(φ → ψ), φ ⊢ ψ`. A concrete sequent is an instance of a schema when some
+//! substitution of the metavariables turns the schema into it. Metavariables are lowercase Greek
+//! letters or, in ASCII, names starting with `?`, like `?p`.
+
+use crate::{
+    fitch::Proposition,
+    parser::{self, parse_expression},
+};
+use std::{collections::HashMap, fmt::Display, rc::Rc};
+
+/// The formula each metavariable stands for.
+pub type Substitution = HashMap<String, Rc<Proposition>>;
+
+/// Extends `substitution` so that it turns `schema` into `prop`, returning whether it could.
+/// The substitution is left half-extended when it could not.
+pub fn matches(
+    schema: &Rc<Proposition>,
+    prop: &Rc<Proposition>,
+    substitution: &mut Substitution,
+) -> bool {
+    let all = |l: &[Rc<Proposition>], r: &[Rc<Proposition>], s: &mut Substitution| {
+        l.len() == r.len() && l.iter().zip(r).all(|(l, r)| matches(l, r, s))
+    };
+    match (schema.as_ref(), prop.as_ref()) {
+        (Proposition::Meta(name), _) => match substitution.get(name) {
+            Some(bound) => bound == prop,
+            None => {
+                substitution.insert(name.clone(), prop.clone());
+                true
+            }
+        },
+        (Proposition::Not(l), Proposition::Not(r)) => matches(l, r, substitution),
+        (Proposition::And(l), Proposition::And(r)) | (Proposition::Or(l), Proposition::Or(r)) => {
+            all(l, r, substitution)
+        }
+        (Proposition::Implies(l0, l1), Proposition::Implies(r0, r1))
+        | (Proposition::Iff(l0, l1), Proposition::Iff(r0, r1))
+        | (Proposition::Xor(l0, l1), Proposition::Xor(r0, r1))
+        | (Proposition::Nand(l0, l1), Proposition::Nand(r0, r1))
+        | (Proposition::Nor(l0, l1), Proposition::Nor(r0, r1)) => {
+            matches(l0, r0, substitution) && matches(l1, r1, substitution)
+        }
+        _ => schema == prop,
+    }
+}
+
+/// The substitution that turns `schema` into `prop`, if there is one.
+pub fn find_match(schema: &Rc<Proposition>, prop: &Rc<Proposition>) -> Option<Substitution> {
+    let mut substitution = Substitution::new();
+    matches(schema, prop, &mut substitution).then_some(substitution)
+}
+
+/// `schema` with its metavariables replaced, or `None` if one of them has no replacement.
+pub fn substitute(
+    schema: &Rc<Proposition>,
+    substitution: &Substitution,
+) -> Option<Rc<Proposition>> {
+    rebuild(schema, &|prop| match prop {
+        Proposition::Meta(name) => Some(substitution.get(name).cloned()),
+        _ => None,
+    })
+}
+
+/// `prop` with its atoms turned into metavariables of the same name, so that it stands for all
+/// of its substitution instances.
+pub fn generalize(prop: &Rc<Proposition>) -> Rc<Proposition> {
+    rebuild(prop, &|prop| match prop {
+        Proposition::Term(name) => Some(Some(Proposition::new_meta(name))),
+        _ => None,
+    })
+    .unwrap()
+}
+
+/// Rebuilds `prop` bottom up, replacing the leaves for which `leaf` returns something.
+fn rebuild(
+    prop: &Rc<Proposition>,
+    leaf: &impl Fn(&Proposition) -> Option<Option<Rc<Proposition>>>,
+) -> Option<Rc<Proposition>> {
+    if let Some(replaced) = leaf(prop) {
+        return replaced;
+    }
+    let pair = |l, r| Some((rebuild(l, leaf)?, rebuild(r, leaf)?));
+    let all = |ps: &[Rc<Proposition>]| {
+        ps.iter()
+            .map(|p| rebuild(p, leaf))
+            .collect::<Option<Vec<_>>>()
+    };
+    let res = match prop.as_ref() {
+        Proposition::Absurdum
+        | Proposition::Verum
+        | Proposition::Term(_)
+        | Proposition::Meta(_) => prop.clone(),
+        Proposition::Not(p) => Proposition::new_not(&rebuild(p, leaf)?),
+        Proposition::And(ps) => Proposition::new_conjunction(&all(ps)?),
+        Proposition::Or(ps) => Proposition::new_disjunction(&all(ps)?),
+        Proposition::Implies(l, r) => {
+            let (l, r) = pair(l, r)?;
+            Proposition::new_implies(&l, &r)
+        }
+        Proposition::Iff(l, r) => {
+            let (l, r) = pair(l, r)?;
+            Proposition::new_iff(&l, &r)
+        }
+        Proposition::Xor(l, r) => {
+            let (l, r) = pair(l, r)?;
+            Proposition::new_xor(&l, &r)
+        }
+        Proposition::Nand(l, r) => {
+            let (l, r) = pair(l, r)?;
+            Proposition::new_nand(&l, &r)
+        }
+        Proposition::Nor(l, r) => {
+            let (l, r) = pair(l, r)?;
+            Proposition::new_nor(&l, &r)
+        }
+    };
+    Some(res)
+}
+
+/// The metavariables of `prop`, in order of appearance.
+pub fn metavariables(prop: &Rc<Proposition>) -> Vec<String> {
+    let mut res: Vec<String> = Vec::new();
+    for p in Proposition::subformulas(prop) {
+        if let Proposition::Meta(name) = p.as_ref() {
+            if !res.contains(name) {
+                res.push(name.clone());
+            }
+        }
+    }
+    res
+}
+
+/// Premises and a conclusion, written `premise, ... ⊢ conclusion` or with `|-`.
+#[derive(PartialEq, Debug)]
+pub struct Sequent {
+    pub premises: Vec<Rc<Proposition>>,
+    pub conclusion: Rc<Proposition>,
+}
+
+impl Sequent {
+    pub fn parse(text: &str) -> Result<Sequent, &'static str> {
+        let (premises, conclusion) = parse_sequent(text, |text| match parse_expression(text) {
+            parser::Result::Success(premise, rest) => Ok((premise, rest)),
+            parser::Result::Failure => Err("invalid premise"),
+        })?;
+        Ok(Sequent {
+            premises,
+            conclusion,
+        })
+    }
+
+    /// The sequent with its atoms turned into metavariables, see [`generalize`].
+    pub fn generalize(&self) -> Sequent {
+        Sequent {
+            premises: self.premises.iter().map(generalize).collect(),
+            conclusion: generalize(&self.conclusion),
+        }
+    }
+
+    /// Whether the premises settle the conclusion: every metavariable of the conclusion appears
+    /// in some premise.
+    pub fn determines_conclusion(&self) -> bool {
+        let premises: Vec<String> = self.premises.iter().flat_map(metavariables).collect();
+        metavariables(&self.conclusion)
+            .iter()
+            .all(|m| premises.contains(m))
+    }
+
+    /// Checks that `premises` are instances of the premises of this schema, in order, under a
+    /// single substitution and returns the matching instance of the conclusion. `conclusion` is
+    /// the instance chosen by the user, needed when the premises don't settle it.
+    pub fn instance(
+        &self,
+        premises: &[&Rc<Proposition>],
+        conclusion: Option<&Rc<Proposition>>,
+    ) -> Result<Rc<Proposition>, &'static str> {
+        if premises.len() != self.premises.len() {
+            return Err("Cite one row per premise");
+        }
+        let mut substitution = Substitution::new();
+        for (schema, premise) in self.premises.iter().zip(premises) {
+            if !matches(schema, premise, &mut substitution) {
+                return Err("Cited rows are not an instance of the premises");
+            }
+        }
+        match conclusion {
+            Some(prop) if matches(&self.conclusion, prop, &mut substitution) => Ok(prop.clone()),
+            Some(_) => Err("Expression is not an instance of the conclusion"),
+            None => {
+                substitute(&self.conclusion, &substitution).ok_or("Missing expression to introduce")
+            }
+        }
+    }
+
+    /// The substitution that turns this schema into `instance`.
+    pub fn check_instance(&self, instance: &Sequent) -> Result<Substitution, &'static str> {
+        if instance.premises.len() != self.premises.len() {
+            return Err("The instance has a different number of premises");
+        }
+        let mut substitution = Substitution::new();
+        for (schema, premise) in self.premises.iter().zip(&instance.premises) {
+            if !matches(schema, premise, &mut substitution) {
+                return Err("A premise does not match the schema");
+            }
+        }
+        if !matches(&self.conclusion, &instance.conclusion, &mut substitution) {
+            return Err("The conclusion does not match the schema");
+        }
+        Ok(substitution)
+    }
+}
+
+impl Display for Sequent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_sequent(f, &self.premises, &self.conclusion)
+    }
+}
+
+/// Strips the `|-` or `⊢` `text` starts with.
+pub(crate) fn strip_turnstile(text: &str) -> Option<&str> {
+    let text = text.trim_start();
+    text.strip_prefix("|-").or_else(|| text.strip_prefix('⊢'))
+}
+
+/// Parses `premise, ... |- conclusion`, reading each premise with `premise`. Lemmas take
+/// formulas as premises, custom rules subproofs as well.
+pub fn parse_sequent<'a, P>(
+    text: &'a str,
+    premise: impl Fn(&'a str) -> Result<(P, &'a str), &'static str>,
+) -> Result<(Vec<P>, Rc<Proposition>), &'static str> {
+    let mut rest = text;
+    let mut premises = Vec::new();
+    loop {
+        if let Some(conclusion) = strip_turnstile(rest) {
+            return match parse_expression(conclusion) {
+                parser::Result::Success(conclusion, r) if r.trim().is_empty() => {
+                    Ok((premises, conclusion))
+                }
+                _ => Err("invalid conclusion"),
+            };
+        }
+        if !premises.is_empty() {
+            rest = rest
+                .trim_start()
+                .strip_prefix(',')
+                .ok_or("missing `,` between premises")?;
+        }
+        let (p, r) = premise(rest)?;
+        premises.push(p);
+        rest = r;
+    }
+}
+
+/// Writes `premise, ... |- conclusion`, the way [`parse_sequent`] reads it.
+pub fn write_sequent(
+    f: &mut std::fmt::Formatter<'_>,
+    premises: &[impl Display],
+    conclusion: &Rc<Proposition>,
+) -> std::fmt::Result {
+    for (i, premise) in premises.iter().enumerate() {
+        let separator = if i + 1 == premises.len() { " " } else { ", " };
+        write!(f, "{}{}", premise, separator)?;
+    }
+    write!(f, "|- {}", conclusion)
+}
+
+/// Lemma and rule names are a letter followed by letters, digits, `_` or `-`.
+pub fn valid_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_match, generalize, substitute, Sequent};
+    use crate::{fitch::Proposition, parser::expr};
+
+    #[test]
+    fn matching() {
+        let schema = expr("((φ → ψ) ∧ φ)");
+        let substitution = find_match(&schema, &expr("(((A | B) => C) & (A | B))")).unwrap();
+        assert_eq!(substitution["φ"], expr("(A | B)"));
+        assert_eq!(substitution["ψ"], expr("C"));
+        assert_eq!(
+            substitute(&expr("(ψ | (~φ))"), &substitution),
+            Some(expr("(C | (~(A | B)))"))
+        );
+        assert_eq!(substitute(&expr("χ"), &substitution), None);
+
+        assert!(find_match(&schema, &expr("((A => C) & B)")).is_none());
+        assert!(find_match(&expr("(?p => A)"), &expr("(B => A)")).is_some());
+        assert!(find_match(&expr("(?p => A)"), &expr("(B => C)")).is_none());
+        assert!(find_match(&expr("(φ & ψ)"), &expr("(A & B & C)")).is_none());
+        assert_eq!(generalize(&expr("(A => B)")), expr("(?A => ?B)"));
+    }
+
+    #[test]
+    fn sequents() {
+        let rule = Sequent::parse("(φ → ψ), φ ⊢ ψ").unwrap();
+        assert_eq!(rule.to_string(), "(φ => ψ), φ |- ψ");
+        assert!(rule.determines_conclusion());
+        let instance = Sequent::parse("((~A) => B), (~A) |- B").unwrap();
+        assert_eq!(rule.check_instance(&instance).unwrap().len(), 2);
+        let wrong = Sequent::parse("((~A) => B), A |- B").unwrap();
+        assert!(rule.check_instance(&wrong).is_err());
+        assert!(rule
+            .check_instance(&Sequent::parse("(A => B) |- B").unwrap())
+            .is_err());
+
+        let explosion = Sequent::parse("⊥ |- φ").unwrap();
+        assert!(!explosion.determines_conclusion());
+        let absurdum = Proposition::new_absurdum();
+        assert!(explosion.instance(&[&absurdum], None).is_err());
+        assert_eq!(
+            explosion.instance(&[&absurdum], Some(&expr("(A & B)"))),
+            Ok(expr("(A & B)"))
+        );
+        assert_eq!(Sequent::parse("|- (φ | (¬φ))").unwrap().premises.len(), 0);
+        assert!(Sequent::parse("φ ψ |- ψ").is_err());
+        assert!(Sequent::parse("φ, ψ").is_err());
+    }
+}
@@ -1,3 +1,6 @@
+use fitch::{export::Format, normal::Form, Proposition, Rule};
+use std::rc::Rc;
+
 #[derive(PartialEq)]
 pub enum State {
     Noraml,
@@ -6,15 +9,73 @@ pub enum State {
     AddAssumption,
     AddSubproof,
     Reiterate,
+    AddTentative,
+    TentativeJustification(Rc<Proposition>),
+    Justify(usize),
+    Help(u16),
+    CommandPalette(usize),
+    Export(Format),
+    ImportCarnap,
+    /// Asking for the proof file to open, or to save the proof to.
+    Open,
+    Save,
+    SaveLemma,
+    LemmaState(LemmaState),
+    CheckSoundness,
+    /// Converting an expression to a normal form, or to the Tseitin CNF for `None`.
+    Normalize(Option<Form>),
+    /// A report to scroll through: its title, its lines and how far it is scrolled.
+    Report(&'static str, Vec<String>, u16),
     AbsurdumState(AbsurdumState),
     AndState(AndState),
     OrState(OrState),
     NotState(NotState),
     ImpliesState(ImpliesState),
     IffState(IffState),
+    /// Folding or unfolding the definition of XOR, NAND or NOR in the cited row.
+    Definition(Rule),
     Quit,
 }
 
+impl State {
+    /// The first step of applying `rule`. Introducing ⊤ takes no steps, so it stays in the
+    /// normal state.
+    pub fn start(rule: Rule) -> State {
+        match rule {
+            Rule::Reiter => State::Reiterate,
+            Rule::IntroAbs => State::AbsurdumState(AbsurdumState::IntroduceGetAssumption1),
+            Rule::ElimAbs => State::AbsurdumState(AbsurdumState::EliminateGetAssumption),
+            Rule::IntroAnd => State::AndState(AndState::IntroduceGetAssumptions),
+            Rule::ElimAnd => State::AndState(AndState::EliminateGetAssumption),
+            Rule::IntroOr => State::OrState(OrState::IntroduceGetAssumption),
+            Rule::ElimOr => State::OrState(OrState::EliminateGetAssumption),
+            Rule::IntroNot => State::NotState(NotState::Introduce),
+            Rule::ElimNot => State::NotState(NotState::Eliminate),
+            Rule::IntroImpl => State::ImpliesState(ImpliesState::Introduce),
+            Rule::ElimImpl => State::ImpliesState(ImpliesState::EliminateGetAssumption),
+            Rule::IntroIff => State::IffState(IffState::IntroduceGetLeftSubproof),
+            Rule::ElimIff => State::IffState(IffState::EliminateGetAssumption),
+            Rule::IntroVerum => State::Noraml,
+            Rule::IntroXor
+            | Rule::ElimXor
+            | Rule::IntroNand
+            | Rule::ElimNand
+            | Rule::IntroNor
+            | Rule::ElimNor => State::Definition(rule),
+            Rule::Lemma(_) | Rule::Custom(_) => State::LemmaState(LemmaState::CitePremises(rule)),
+        }
+    }
+}
+
+/// Applying a lemma or a custom rule: choosing the lemma, citing the premises and, when they
+/// don't settle it, typing the instance of the conclusion.
+#[derive(PartialEq)]
+pub enum LemmaState {
+    Choose,
+    CitePremises(Rule),
+    TypeConclusion(Rule, Vec<usize>),
+}
+
 #[derive(PartialEq)]
 pub enum AbsurdumState {
     IntroduceGetAssumption1,
@@ -25,10 +86,9 @@ pub enum AbsurdumState {
 
 #[derive(PartialEq)]
 pub enum AndState {
-    IntroduceGetLeftAssumption,
-    IntroduceGetRightAssumption(usize),
+    IntroduceGetAssumptions,
     EliminateGetAssumption,
-    EliminateGetProposition(usize),
+    EliminateChooseConjunct(usize),
 }
 
 #[derive(PartialEq)]
@@ -36,8 +96,7 @@ pub enum OrState {
     IntroduceGetAssumption,
     IntroduceGetProposition(usize),
     EliminateGetAssumption,
-    EliminateGetLeftSubproof(usize),
-    EliminateGetRightSubproof(usize, usize),
+    EliminateGetSubproofs(usize),
 }
 
 #[derive(PartialEq)]
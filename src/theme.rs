@@ -0,0 +1,199 @@
+use ratatui::style::{Color, Modifier, Style};
+use std::env;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ThemeName {
+    Dark,
+    Light,
+    HighContrast,
+    Monochrome,
+}
+
+/// The styles of everything the TUI draws.
+#[derive(Clone, Copy)]
+pub struct Theme {
+    pub valid: Style,
+    pub invalid: Style,
+    pub unjustified: Style,
+    pub focus: Style,
+    pub line_number: Style,
+    pub bar: Style,
+    pub rule: Style,
+    pub info: Style,
+    pub warning: Style,
+    pub border: Style,
+    pub expression_box: Style,
+    pub selected: Style,
+    pub term: Style,
+    pub absurdum: Style,
+    pub connective: Style,
+    pub paren: Style,
+    pub unknown: Style,
+    pub matching_paren: Style,
+    pub parses: Style,
+    pub does_not_parse: Style,
+}
+
+impl ThemeName {
+    pub const ALL: [ThemeName; 4] = [
+        ThemeName::Dark,
+        ThemeName::Light,
+        ThemeName::HighContrast,
+        ThemeName::Monochrome,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ThemeName::Dark => "dark",
+            ThemeName::Light => "light",
+            ThemeName::HighContrast => "high-contrast",
+            ThemeName::Monochrome => "monochrome",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<ThemeName> {
+        ThemeName::ALL.into_iter().find(|t| t.name() == name)
+    }
+
+    /// The theme used when none is configured: monochrome if `NO_COLOR` is set, dark otherwise.
+    pub fn from_env() -> ThemeName {
+        match env::var_os("NO_COLOR") {
+            Some(v) if !v.is_empty() => ThemeName::Monochrome,
+            _ => ThemeName::Dark,
+        }
+    }
+
+    pub fn theme(&self) -> Theme {
+        match self {
+            ThemeName::Dark => Theme::dark(),
+            ThemeName::Light => Theme::light(),
+            ThemeName::HighContrast => Theme::high_contrast(),
+            ThemeName::Monochrome => Theme::monochrome(),
+        }
+    }
+}
+
+impl Theme {
+    fn fg(colour: Color) -> Style {
+        Style::default().fg(colour)
+    }
+
+    pub fn dark() -> Theme {
+        Theme {
+            valid: Theme::fg(Color::Green),
+            invalid: Theme::fg(Color::Red),
+            unjustified: Theme::fg(Color::Yellow),
+            focus: Style::default().add_modifier(Modifier::REVERSED),
+            line_number: Theme::fg(Color::DarkGray),
+            bar: Theme::fg(Color::Blue),
+            rule: Theme::fg(Color::Magenta),
+            info: Style::default(),
+            warning: Theme::fg(Color::Red).add_modifier(Modifier::BOLD),
+            border: Style::default(),
+            expression_box: Theme::fg(Color::Cyan),
+            selected: Style::default().add_modifier(Modifier::REVERSED),
+            term: Theme::fg(Color::Cyan),
+            absurdum: Theme::fg(Color::Magenta),
+            connective: Theme::fg(Color::Yellow),
+            paren: Style::default().add_modifier(Modifier::BOLD),
+            unknown: Theme::fg(Color::Red),
+            matching_paren: Style::default().add_modifier(Modifier::REVERSED),
+            parses: Theme::fg(Color::Green),
+            does_not_parse: Theme::fg(Color::Red),
+        }
+    }
+
+    /// Darker colours that stay readable on a white background.
+    pub fn light() -> Theme {
+        let orange = Color::Indexed(130);
+        Theme {
+            valid: Theme::fg(Color::Indexed(28)),
+            invalid: Theme::fg(Color::Indexed(124)),
+            unjustified: Theme::fg(orange),
+            line_number: Theme::fg(Color::Gray),
+            bar: Theme::fg(Color::Indexed(25)),
+            rule: Theme::fg(Color::Indexed(90)),
+            warning: Theme::fg(Color::Indexed(124)).add_modifier(Modifier::BOLD),
+            expression_box: Theme::fg(Color::Indexed(25)),
+            term: Theme::fg(Color::Indexed(25)),
+            absurdum: Theme::fg(Color::Indexed(90)),
+            connective: Theme::fg(orange),
+            unknown: Theme::fg(Color::Indexed(124)),
+            parses: Theme::fg(Color::Indexed(28)),
+            does_not_parse: Theme::fg(Color::Indexed(124)),
+            ..Theme::dark()
+        }
+    }
+
+    /// Bright colours and bold text only, with warnings on a solid background.
+    pub fn high_contrast() -> Theme {
+        let bold = |colour| Theme::fg(colour).add_modifier(Modifier::BOLD);
+        Theme {
+            valid: bold(Color::LightGreen),
+            invalid: bold(Color::LightRed).add_modifier(Modifier::UNDERLINED),
+            unjustified: bold(Color::LightYellow),
+            line_number: Theme::fg(Color::White),
+            bar: bold(Color::White),
+            rule: bold(Color::LightCyan),
+            info: Theme::fg(Color::White),
+            warning: bold(Color::White).bg(Color::Red),
+            border: bold(Color::White),
+            expression_box: bold(Color::LightYellow),
+            term: bold(Color::LightCyan),
+            absurdum: bold(Color::LightMagenta),
+            connective: bold(Color::LightYellow),
+            paren: bold(Color::White),
+            unknown: bold(Color::White).bg(Color::Red),
+            parses: bold(Color::LightGreen),
+            does_not_parse: bold(Color::LightRed),
+            ..Theme::dark()
+        }
+    }
+
+    /// No colours at all, for limited terminals and screen readers. Row status is told apart by
+    /// modifiers only, and the info bar spells it out for the focused row.
+    pub fn monochrome() -> Theme {
+        let plain = Style::default();
+        Theme {
+            valid: plain,
+            invalid: plain.add_modifier(Modifier::UNDERLINED),
+            unjustified: plain.add_modifier(Modifier::ITALIC),
+            focus: plain.add_modifier(Modifier::REVERSED),
+            line_number: plain,
+            bar: plain,
+            rule: plain,
+            info: plain,
+            warning: plain.add_modifier(Modifier::BOLD),
+            border: plain,
+            expression_box: plain,
+            selected: plain.add_modifier(Modifier::REVERSED),
+            term: plain,
+            absurdum: plain,
+            connective: plain,
+            paren: plain,
+            unknown: plain.add_modifier(Modifier::UNDERLINED),
+            matching_paren: plain.add_modifier(Modifier::REVERSED),
+            parses: plain,
+            does_not_parse: plain.add_modifier(Modifier::BOLD),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ThemeName;
+
+    #[test]
+    fn theme_names() {
+        for theme in ThemeName::ALL {
+            assert_eq!(ThemeName::from_name(theme.name()), Some(theme));
+        }
+        assert_eq!(ThemeName::from_name("solarized"), None);
+
+        let mono = ThemeName::Monochrome.theme();
+        for style in [mono.valid, mono.invalid, mono.warning, mono.rule, mono.term] {
+            assert_eq!(style.fg, None);
+            assert_eq!(style.bg, None);
+        }
+    }
+}
@@ -1,71 +1,183 @@
-use crate::fitch::Fitch;
+use crate::theme::Theme;
 use crossterm::{
+    event::{DisableBracketedPaste, EnableBracketedPaste},
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand,
 };
+use fitch::{
+    parser::{self, parse_expression, tokenize, TokenKind},
+    Fitch, RowStatus, SymbolStyle,
+};
 use ratatui::{
     backend::CrosstermBackend,
-    layout::{Constraint, Direction, Layout, Rect},
-    widgets::{Block, BorderType, Borders, Paragraph},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::Style,
+    text::{Line, Span, Text},
+    widgets::{block::Title, Block, BorderType, Borders, Clear, Paragraph, Tabs, Wrap},
     Terminal,
 };
-use std::io::{stdout, Stdout};
+use std::{
+    io::{stdout, Stdout},
+    ops::Range,
+};
 
 const INFO_AREA_HEIGHT: u16 = 3;
+const TAB_BAR_HEIGHT: u16 = 1;
 
 // TODO Use tokio and use cancellation token if I ever decide to implement a solver (Taut CON, Ana
 // CON, etc.)
+pub struct ExpressionBox<'a> {
+    pub title: &'a str,
+    pub buffer: &'a str,
+    pub cursor_offset: u16,
+    /// Whether the box holds an expression, enabling syntax highlighting and the parse indicator.
+    pub highlight: bool,
+}
+
+pub struct TabBar<'a> {
+    pub titles: Vec<&'a str>,
+    pub selected: usize,
+}
+
+/// The text below the proof, shown with the warning style when something went wrong.
+pub struct Info<'a> {
+    pub text: &'a str,
+    pub warning: bool,
+}
+
+/// A bordered list drawn over the proof: below the expression box when there is one, centered
+/// otherwise.
+pub struct Popup {
+    pub title: &'static str,
+    pub lines: Vec<String>,
+    pub selected: Option<usize>,
+    pub scroll: u16,
+}
+
 pub struct Renderer {
     terminal: Terminal<CrosstermBackend<Stdout>>,
+    symbols: SymbolStyle,
+    bars: SymbolStyle,
+    theme: Theme,
 }
 
 impl Renderer {
-    pub fn new() -> std::io::Result<Renderer> {
+    pub fn new(symbols: SymbolStyle, bars: SymbolStyle, theme: Theme) -> std::io::Result<Renderer> {
         stdout().execute(EnterAlternateScreen)?;
+        stdout().execute(EnableBracketedPaste)?;
         enable_raw_mode()?;
         let mut renderer = Renderer {
             terminal: Terminal::new(CrosstermBackend::new(stdout()))?,
+            symbols,
+            bars,
+            theme,
         };
         renderer.terminal.clear()?;
         Ok(renderer)
     }
 
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
     pub fn render(
         &mut self,
+        tabs: TabBar,
         model: &Fitch,
-        info: &str,
-        title: &str,
-        buffer: &str,
-        cursor_offset: u16,
-        render_box: bool,
+        info: Info,
+        expression_box: Option<ExpressionBox>,
+        popup: Option<Popup>,
+        focus: Option<usize>,
     ) {
+        let (symbols, bars) = (self.symbols, self.bars);
+        let theme = self.theme;
         self.terminal
             .draw(|frame| {
-                let (f_a, i_a) = base_area(frame.size());
-                let fitch_widget = Paragraph::new(model.to_string()).block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .border_type(BorderType::Rounded),
-                );
-                let info_widget = Paragraph::new(info);
+                let (t_a, f_a, i_a) = base_area(frame.size());
+                let tabs_widget = Tabs::new(tabs.titles)
+                    .select(tabs.selected)
+                    .style(theme.line_number)
+                    .highlight_style(theme.selected);
+                let fitch_widget = Paragraph::new(proof_text(model, symbols, bars, &theme, focus))
+                    .block(
+                        Block::default()
+                            .borders(Borders::ALL)
+                            .border_type(BorderType::Rounded)
+                            .border_style(theme.border),
+                    );
+                let info_widget =
+                    Paragraph::new(info.text)
+                        .wrap(Wrap { trim: true })
+                        .style(if info.warning {
+                            theme.warning
+                        } else {
+                            theme.info
+                        });
 
+                frame.render_widget(tabs_widget, t_a);
                 frame.render_widget(fitch_widget, f_a);
                 frame.render_widget(info_widget, i_a);
 
-                if !render_box {
-                    return;
+                if let Some(popup) = popup {
+                    let area = if expression_box.is_some() {
+                        below_expression_box_area(frame.size())
+                    } else {
+                        popup_area(frame.size())
+                    };
+                    let lines: Vec<Line> = popup
+                        .lines
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, l)| {
+                            if popup.selected == Some(i) {
+                                Line::styled(l, theme.selected)
+                            } else {
+                                Line::raw(l)
+                            }
+                        })
+                        .collect();
+                    let popup_widget = Paragraph::new(lines).scroll((popup.scroll, 0)).block(
+                        Block::default()
+                            .borders(Borders::ALL)
+                            .border_type(BorderType::Rounded)
+                            .border_style(theme.border)
+                            .title(popup.title),
+                    );
+                    frame.render_widget(Clear, area);
+                    frame.render_widget(popup_widget, area);
                 }
 
+                let expression_box = match expression_box {
+                    None => return,
+                    Some(v) => v,
+                };
+
                 // Render expression BOX
                 let area = expression_box_area(frame.size());
-                let expression_widget = Paragraph::new(buffer).block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .border_type(BorderType::Rounded)
-                        .title(title),
-                );
+                let mut block = Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(theme.expression_box)
+                    .title(expression_box.title);
+                let content = if expression_box.highlight {
+                    block = block.title(
+                        Title::from(parse_indicator(expression_box.buffer, &theme))
+                            .alignment(Alignment::Right),
+                    );
+                    expression_line(
+                        expression_box.buffer,
+                        expression_box.cursor_offset as usize,
+                        &theme,
+                    )
+                } else {
+                    Line::raw(expression_box.buffer)
+                };
+                let expression_widget = Paragraph::new(content).block(block);
                 frame.render_widget(expression_widget, area);
-                frame.set_cursor(area.left() + 1 + cursor_offset, area.top() + 1);
+                frame.set_cursor(
+                    area.left() + 1 + expression_box.cursor_offset,
+                    area.top() + 1,
+                );
             })
             .unwrap();
     }
@@ -73,21 +185,127 @@ impl Renderer {
 
 impl Drop for Renderer {
     fn drop(&mut self) {
+        stdout().execute(DisableBracketedPaste).unwrap();
         stdout().execute(LeaveAlternateScreen).unwrap();
         disable_raw_mode().unwrap();
     }
 }
 
-fn base_area(whole: Rect) -> (Rect, Rect) {
+fn proof_text(
+    model: &Fitch,
+    symbols: SymbolStyle,
+    bars: SymbolStyle,
+    theme: &Theme,
+    focus: Option<usize>,
+) -> Text<'static> {
+    model
+        .proof_lines(symbols, bars)
+        .into_iter()
+        .map(|line| match line.row {
+            None => Line::styled(line.bars, theme.bar),
+            Some(row) => {
+                let mut expression = match model.row_status(row) {
+                    RowStatus::Valid => theme.valid,
+                    RowStatus::Invalid(_) => theme.invalid,
+                    RowStatus::Unjustified => theme.unjustified,
+                };
+                if focus == Some(row) {
+                    expression = expression.patch(theme.focus);
+                }
+                Line::from(vec![
+                    Span::styled(line.number, theme.line_number),
+                    Span::styled(line.bars, theme.bar),
+                    Span::styled(line.expression, expression),
+                    Span::styled(line.justification, theme.rule),
+                ])
+            }
+        })
+        .collect::<Vec<Line>>()
+        .into()
+}
+
+fn parse_indicator(buffer: &str, theme: &Theme) -> Span<'static> {
+    match parse_expression(buffer) {
+        parser::Result::Success(_, _) => Span::styled(" parses ", theme.parses),
+        parser::Result::Failure => Span::styled(" does not parse ", theme.does_not_parse),
+    }
+}
+
+fn expression_line(buffer: &str, cursor: usize, theme: &Theme) -> Line<'static> {
+    let tokens = tokenize(buffer);
+    let chars: Vec<char> = buffer.chars().collect();
+    let matched = matching_parens(&tokens, cursor);
+    tokens
+        .into_iter()
+        .map(|(kind, range)| {
+            let mut style = match kind {
+                TokenKind::Term | TokenKind::Meta => theme.term,
+                TokenKind::Absurdum | TokenKind::Verum => theme.absurdum,
+                TokenKind::Connective => theme.connective,
+                TokenKind::LeftParen | TokenKind::RightParen => theme.paren,
+                TokenKind::Unknown => theme.unknown,
+                TokenKind::Whitespace => Style::default(),
+            };
+            if let Some((open, close)) = matched {
+                if range.start == open || range.start == close {
+                    style = style.patch(theme.matching_paren);
+                }
+            }
+            Span::styled(chars[range].iter().collect::<String>(), style)
+        })
+        .collect::<Vec<Span>>()
+        .into()
+}
+
+/// Finds the parenthesis under or right before the cursor and the one it pairs with.
+fn matching_parens(tokens: &[(TokenKind, Range<usize>)], cursor: usize) -> Option<(usize, usize)> {
+    let is_paren = |(kind, _): &&(TokenKind, Range<usize>)| {
+        matches!(kind, TokenKind::LeftParen | TokenKind::RightParen)
+    };
+    let parens: Vec<&(TokenKind, Range<usize>)> = tokens.iter().filter(is_paren).collect();
+    let at = parens
+        .iter()
+        .position(|(_, r)| r.start == cursor)
+        .or_else(|| parens.iter().position(|(_, r)| r.end == cursor))?;
+
+    let mut depth = 0;
+    if parens[at].0 == TokenKind::LeftParen {
+        for (kind, range) in &parens[at..] {
+            depth += if *kind == TokenKind::LeftParen { 1 } else { -1 };
+            if depth == 0 {
+                return Some((parens[at].1.start, range.start));
+            }
+        }
+    } else {
+        for (kind, range) in parens[..=at].iter().rev() {
+            depth += if *kind == TokenKind::RightParen {
+                1
+            } else {
+                -1
+            };
+            if depth == 0 {
+                return Some((range.start, parens[at].1.start));
+            }
+        }
+    }
+    None
+}
+
+fn base_area(whole: Rect) -> (Rect, Rect, Rect) {
     let temp = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(whole.height - INFO_AREA_HEIGHT),
+            Constraint::Length(TAB_BAR_HEIGHT),
+            Constraint::Length(
+                whole
+                    .height
+                    .saturating_sub(TAB_BAR_HEIGHT + INFO_AREA_HEIGHT),
+            ),
             Constraint::Length(INFO_AREA_HEIGHT),
         ])
         .split(whole);
 
-    (temp[0], temp[1])
+    (temp[0], temp[1], temp[2])
 }
 
 fn expression_box_area(whole: Rect) -> Rect {
@@ -108,3 +326,41 @@ fn expression_box_area(whole: Rect) -> Rect {
         ])
         .split(temp)[1]
 }
+
+fn below_expression_box_area(whole: Rect) -> Rect {
+    let temp = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(whole.height / 2),
+            Constraint::Length((whole.height / 2).saturating_sub(INFO_AREA_HEIGHT)),
+            Constraint::Length(INFO_AREA_HEIGHT),
+        ])
+        .split(whole)[1];
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(18),
+            Constraint::Percentage(64),
+            Constraint::Percentage(18),
+        ])
+        .split(temp)[1]
+}
+
+fn popup_area(whole: Rect) -> Rect {
+    let temp = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage(10),
+            Constraint::Percentage(80),
+            Constraint::Percentage(10),
+        ])
+        .split(whole)[1];
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(10),
+            Constraint::Percentage(80),
+            Constraint::Percentage(10),
+        ])
+        .split(temp)[1]
+}